@@ -10,13 +10,122 @@ pub struct Model {
     #[serde(skip_deserializing)]
     pub id: i32,
     pub title: String,
+    /// Set once the user renames the conversation, generated titles never replace theirs.
+    #[serde(skip_deserializing)]
+    pub title_edited: bool,
+    /// Template the summary is written with, picked by the auto-selection rules when unset.
+    pub summary_template_id: Option<i32>,
+    /// Comma separated labels the user filed the conversation under.
+    pub tags: Option<String>,
+    /// Comma separated speakers of the transcript, updated when the conversation is indexed.
+    #[serde(skip_deserializing)]
+    pub participants: Option<String>,
     #[serde(skip_deserializing)]
     pub created_at: String,
     #[serde(skip_deserializing)]
     pub updated_at: String,
 }
 
+impl Model {
+    pub fn tag_list(&self) -> Vec<String> {
+        split_list(self.tags.as_deref())
+    }
+
+    pub fn participant_list(&self) -> Vec<String> {
+        split_list(self.participants.as_deref())
+    }
+}
+
+fn split_list(list: Option<&str>) -> Vec<String> {
+    list.unwrap_or_default()
+        .split(',')
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
-pub enum Relation {}
+pub enum Relation {
+    #[sea_orm(has_many = "super::action_item::Entity")]
+    ActionItem,
+    #[sea_orm(has_many = "super::artifact::Entity")]
+    Artifact,
+    #[sea_orm(has_many = "super::audio_redaction::Entity")]
+    AudioRedaction,
+    #[sea_orm(has_many = "super::chat_message::Entity")]
+    ChatMessage,
+    #[sea_orm(has_many = "super::embedding::Entity")]
+    Embedding,
+    #[sea_orm(has_many = "super::insight::Entity")]
+    Insight,
+    #[sea_orm(has_many = "super::job::Entity")]
+    Job,
+    #[sea_orm(has_many = "super::output_version::Entity")]
+    OutputVersion,
+    #[sea_orm(has_many = "super::redaction::Entity")]
+    Redaction,
+    #[sea_orm(has_many = "super::transcript_edit::Entity")]
+    TranscriptEdit,
+}
+
+impl Related<super::action_item::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ActionItem.def()
+    }
+}
+
+impl Related<super::artifact::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Artifact.def()
+    }
+}
+
+impl Related<super::audio_redaction::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::AudioRedaction.def()
+    }
+}
+
+impl Related<super::chat_message::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::ChatMessage.def()
+    }
+}
+
+impl Related<super::embedding::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Embedding.def()
+    }
+}
+
+impl Related<super::insight::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Insight.def()
+    }
+}
+
+impl Related<super::job::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Job.def()
+    }
+}
+
+impl Related<super::output_version::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::OutputVersion.def()
+    }
+}
+
+impl Related<super::redaction::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Redaction.def()
+    }
+}
+
+impl Related<super::transcript_edit::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::TranscriptEdit.def()
+    }
+}
 
 impl ActiveModelBehavior for ActiveModel {}
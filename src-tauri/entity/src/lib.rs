@@ -2,4 +2,19 @@
 
 pub mod prelude;
 
+pub mod action_item;
+pub mod artifact;
+pub mod audio_redaction;
+pub mod chat_message;
 pub mod conversation;
+pub mod custom_prompt;
+pub mod embedding;
+pub mod glossary_term;
+pub mod insight;
+pub mod job;
+pub mod output_version;
+pub mod prompt;
+pub mod redaction;
+pub mod setting;
+pub mod summary_template;
+pub mod transcript_edit;
@@ -1,3 +1,18 @@
 //! `SeaORM` Entity. Generated by sea-orm-codegen 0.12.15
 
+pub use super::action_item::Entity as ActionItem;
+pub use super::artifact::Entity as Artifact;
+pub use super::audio_redaction::Entity as AudioRedaction;
+pub use super::chat_message::Entity as ChatMessage;
 pub use super::conversation::Entity as Conversation;
+pub use super::custom_prompt::Entity as CustomPrompt;
+pub use super::embedding::Entity as Embedding;
+pub use super::glossary_term::Entity as GlossaryTerm;
+pub use super::insight::Entity as Insight;
+pub use super::job::Entity as Job;
+pub use super::output_version::Entity as OutputVersion;
+pub use super::prompt::Entity as Prompt;
+pub use super::redaction::Entity as Redaction;
+pub use super::setting::Entity as Setting;
+pub use super::summary_template::Entity as SummaryTemplate;
+pub use super::transcript_edit::Entity as TranscriptEdit;
@@ -3,6 +3,24 @@ pub use sea_orm_migration::prelude::*;
 mod m20240523_173708_create_conversation_table;
 mod m20240523_214940_add_dates_to_conversation_table;
 mod m20240523_225333_add_updated_at_to_conversation_table;
+mod m20240601_120000_create_job_table;
+mod m20240605_090000_create_setting_table;
+mod m20240605_091500_create_glossary_term_table;
+mod m20240610_100000_create_transcript_edit_table;
+mod m20240612_090000_create_output_version_table;
+mod m20240612_091000_add_version_to_job_table;
+mod m20240614_090000_create_redaction_table;
+mod m20240614_100000_create_audio_redaction_table;
+mod m20240616_090000_create_summary_template_table;
+mod m20240616_100000_add_title_edited_to_conversation_table;
+mod m20240617_090000_create_action_item_table;
+mod m20240618_090000_create_chat_message_table;
+mod m20240619_090000_create_embedding_table;
+mod m20240620_090000_add_tags_and_participants_to_conversation_table;
+mod m20240621_090000_create_insight_table;
+mod m20240622_090000_create_custom_prompt_table;
+mod m20240622_091000_create_artifact_table;
+mod m20240623_090000_create_prompt_table;
 
 pub struct Migrator;
 
@@ -13,6 +31,24 @@ impl MigratorTrait for Migrator {
             Box::new(m20240523_173708_create_conversation_table::Migration),
             Box::new(m20240523_214940_add_dates_to_conversation_table::Migration),
             Box::new(m20240523_225333_add_updated_at_to_conversation_table::Migration),
+            Box::new(m20240601_120000_create_job_table::Migration),
+            Box::new(m20240605_090000_create_setting_table::Migration),
+            Box::new(m20240605_091500_create_glossary_term_table::Migration),
+            Box::new(m20240610_100000_create_transcript_edit_table::Migration),
+            Box::new(m20240612_090000_create_output_version_table::Migration),
+            Box::new(m20240612_091000_add_version_to_job_table::Migration),
+            Box::new(m20240614_090000_create_redaction_table::Migration),
+            Box::new(m20240614_100000_create_audio_redaction_table::Migration),
+            Box::new(m20240616_090000_create_summary_template_table::Migration),
+            Box::new(m20240616_100000_add_title_edited_to_conversation_table::Migration),
+            Box::new(m20240617_090000_create_action_item_table::Migration),
+            Box::new(m20240618_090000_create_chat_message_table::Migration),
+            Box::new(m20240619_090000_create_embedding_table::Migration),
+            Box::new(m20240620_090000_add_tags_and_participants_to_conversation_table::Migration),
+            Box::new(m20240621_090000_create_insight_table::Migration),
+            Box::new(m20240622_090000_create_custom_prompt_table::Migration),
+            Box::new(m20240622_091000_create_artifact_table::Migration),
+            Box::new(m20240623_090000_create_prompt_table::Migration),
         ]
     }
 }
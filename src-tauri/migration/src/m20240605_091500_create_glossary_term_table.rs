@@ -0,0 +1,55 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(GlossaryTerm::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(GlossaryTerm::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(GlossaryTerm::Term).string().not_null())
+                    .col(ColumnDef::new(GlossaryTerm::MisheardAs).string())
+                    .col(
+                        ColumnDef::new(GlossaryTerm::CreatedAt)
+                            .date_time()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(GlossaryTerm::UpdatedAt)
+                            .date_time()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(GlossaryTerm::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum GlossaryTerm {
+    Table,
+    Id,
+    Term,
+    MisheardAs,
+    CreatedAt,
+    UpdatedAt,
+}
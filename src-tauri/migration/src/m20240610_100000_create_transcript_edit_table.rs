@@ -0,0 +1,83 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(TranscriptEdit::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(TranscriptEdit::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(TranscriptEdit::ConversationId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(TranscriptEdit::Kind).string().not_null())
+                    .col(
+                        ColumnDef::new(TranscriptEdit::SegmentT0)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(TranscriptEdit::SegmentT1)
+                            .big_integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(TranscriptEdit::Text).string())
+                    .col(ColumnDef::new(TranscriptEdit::Speaker).string())
+                    .col(ColumnDef::new(TranscriptEdit::SplitOffset).integer())
+                    .col(
+                        ColumnDef::new(TranscriptEdit::CreatedAt)
+                            .date_time()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-transcript_edit-conversation_id")
+                            .from(TranscriptEdit::Table, TranscriptEdit::ConversationId)
+                            .to(Conversation::Table, Conversation::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(TranscriptEdit::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum TranscriptEdit {
+    Table,
+    Id,
+    ConversationId,
+    Kind,
+    SegmentT0,
+    SegmentT1,
+    Text,
+    Speaker,
+    SplitOffset,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Conversation {
+    Table,
+    Id,
+}
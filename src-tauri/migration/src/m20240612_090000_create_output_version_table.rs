@@ -0,0 +1,76 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(OutputVersion::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(OutputVersion::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(OutputVersion::ConversationId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(OutputVersion::JobId).integer())
+                    .col(ColumnDef::new(OutputVersion::Stages).string().not_null())
+                    .col(ColumnDef::new(OutputVersion::Overrides).string())
+                    .col(
+                        ColumnDef::new(OutputVersion::IsCurrent)
+                            .boolean()
+                            .not_null()
+                            .default(false),
+                    )
+                    .col(
+                        ColumnDef::new(OutputVersion::CreatedAt)
+                            .date_time()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-output_version-conversation_id")
+                            .from(OutputVersion::Table, OutputVersion::ConversationId)
+                            .to(Conversation::Table, Conversation::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(OutputVersion::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum OutputVersion {
+    Table,
+    Id,
+    ConversationId,
+    JobId,
+    Stages,
+    Overrides,
+    IsCurrent,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Conversation {
+    Table,
+    Id,
+}
@@ -0,0 +1,69 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Redaction::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Redaction::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(Redaction::ConversationId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(Redaction::Kind).string().not_null())
+                    .col(ColumnDef::new(Redaction::Placeholder).string().not_null())
+                    .col(ColumnDef::new(Redaction::Original).string())
+                    .col(
+                        ColumnDef::new(Redaction::CreatedAt)
+                            .date_time()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-redaction-conversation_id")
+                            .from(Redaction::Table, Redaction::ConversationId)
+                            .to(Conversation::Table, Conversation::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Redaction::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Redaction {
+    Table,
+    Id,
+    ConversationId,
+    Kind,
+    Placeholder,
+    Original,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Conversation {
+    Table,
+    Id,
+}
@@ -0,0 +1,77 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(AudioRedaction::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(AudioRedaction::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(AudioRedaction::ConversationId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(AudioRedaction::T0).big_integer().not_null())
+                    .col(ColumnDef::new(AudioRedaction::T1).big_integer().not_null())
+                    .col(ColumnDef::new(AudioRedaction::Method).string().not_null())
+                    .col(ColumnDef::new(AudioRedaction::Kind).string().not_null())
+                    .col(
+                        ColumnDef::new(AudioRedaction::Placeholder)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(AudioRedaction::CreatedAt)
+                            .date_time()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-audio_redaction-conversation_id")
+                            .from(AudioRedaction::Table, AudioRedaction::ConversationId)
+                            .to(Conversation::Table, Conversation::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(AudioRedaction::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum AudioRedaction {
+    Table,
+    Id,
+    ConversationId,
+    T0,
+    T1,
+    Method,
+    Kind,
+    Placeholder,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Conversation {
+    Table,
+    Id,
+}
@@ -0,0 +1,165 @@
+use sea_orm_migration::prelude::*;
+
+struct SeedTemplate {
+    name: &'static str,
+    prompt: &'static str,
+    sections: &'static str,
+    json_schema: &'static str,
+    title_keywords: Option<&'static str>,
+    transcript_keywords: Option<&'static str>,
+}
+
+/// Templates a fresh database starts with.
+const SEED_TEMPLATES: [SeedTemplate; 4] = [
+    SeedTemplate {
+        name: "General",
+        prompt: "Summarize this meeting transcript. Keep it short and factual, in Markdown.",
+        sections: r#"[{"key": "summary", "title": "Summary", "kind": "text"}]"#,
+        json_schema: r#"{"type": "object", "properties": {"summary": {"type": "string", "description": "Summary"}}, "required": ["summary"], "additionalProperties": false}"#,
+        title_keywords: None,
+        transcript_keywords: None,
+    },
+    SeedTemplate {
+        name: "Standup",
+        prompt: "Summarize this standup meeting. List each update and plan with the person it came from.",
+        sections: r#"[{"key": "updates", "title": "Done since last standup", "kind": "list"}, {"key": "plans", "title": "Planned next", "kind": "list"}, {"key": "blockers", "title": "Blockers", "kind": "list"}]"#,
+        json_schema: r#"{"type": "object", "properties": {"updates": {"type": "array", "items": {"type": "string"}, "description": "Done since last standup"}, "plans": {"type": "array", "items": {"type": "string"}, "description": "Planned next"}, "blockers": {"type": "array", "items": {"type": "string"}, "description": "Blockers"}}, "required": ["updates", "plans", "blockers"], "additionalProperties": false}"#,
+        title_keywords: Some("standup, stand-up, daily, scrum"),
+        transcript_keywords: None,
+    },
+    SeedTemplate {
+        name: "Sales discovery call",
+        prompt: "Summarize this sales discovery call from the seller's point of view.",
+        sections: r#"[{"key": "customer", "title": "Customer and context", "kind": "text"}, {"key": "pain_points", "title": "Pain points", "kind": "list"}, {"key": "requirements", "title": "Requirements", "kind": "list"}, {"key": "budget_and_timeline", "title": "Budget and timeline", "kind": "text"}, {"key": "next_steps", "title": "Next steps", "kind": "list"}]"#,
+        json_schema: r#"{"type": "object", "properties": {"customer": {"type": "string", "description": "Customer and context"}, "pain_points": {"type": "array", "items": {"type": "string"}, "description": "Pain points"}, "requirements": {"type": "array", "items": {"type": "string"}, "description": "Requirements"}, "budget_and_timeline": {"type": "string", "description": "Budget and timeline"}, "next_steps": {"type": "array", "items": {"type": "string"}, "description": "Next steps"}}, "required": ["customer", "pain_points", "requirements", "budget_and_timeline", "next_steps"], "additionalProperties": false}"#,
+        title_keywords: Some("discovery, sales, prospect, demo"),
+        transcript_keywords: Some("pricing, budget, procurement"),
+    },
+    SeedTemplate {
+        name: "1:1",
+        prompt: "Summarize this one-on-one meeting between a manager and a report.",
+        sections: r#"[{"key": "discussion", "title": "Discussion", "kind": "text"}, {"key": "feedback", "title": "Feedback", "kind": "list"}, {"key": "follow_ups", "title": "Follow-ups", "kind": "list"}]"#,
+        json_schema: r#"{"type": "object", "properties": {"discussion": {"type": "string", "description": "Discussion"}, "feedback": {"type": "array", "items": {"type": "string"}, "description": "Feedback"}, "follow_ups": {"type": "array", "items": {"type": "string"}, "description": "Follow-ups"}}, "required": ["discussion", "feedback", "follow_ups"], "additionalProperties": false}"#,
+        title_keywords: Some("1:1, 1-1, one on one, one-on-one"),
+        transcript_keywords: None,
+    },
+];
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(SummaryTemplate::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(SummaryTemplate::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(SummaryTemplate::Name).string().not_null())
+                    .col(ColumnDef::new(SummaryTemplate::Prompt).string().not_null())
+                    .col(
+                        ColumnDef::new(SummaryTemplate::Sections)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(SummaryTemplate::JsonSchema)
+                            .string()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(SummaryTemplate::TitleKeywords).string())
+                    .col(ColumnDef::new(SummaryTemplate::TranscriptKeywords).string())
+                    .col(
+                        ColumnDef::new(SummaryTemplate::CreatedAt)
+                            .date_time()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(SummaryTemplate::UpdatedAt)
+                            .date_time()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        let mut seed = Query::insert()
+            .into_table(SummaryTemplate::Table)
+            .columns([
+                SummaryTemplate::Name,
+                SummaryTemplate::Prompt,
+                SummaryTemplate::Sections,
+                SummaryTemplate::JsonSchema,
+                SummaryTemplate::TitleKeywords,
+                SummaryTemplate::TranscriptKeywords,
+            ])
+            .to_owned();
+        for template in SEED_TEMPLATES {
+            seed.values_panic([
+                template.name.into(),
+                template.prompt.into(),
+                template.sections.into(),
+                template.json_schema.into(),
+                template.title_keywords.into(),
+                template.transcript_keywords.into(),
+            ]);
+        }
+        manager.exec_stmt(seed).await?;
+
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Conversation::Table)
+                    .add_column_if_not_exists(
+                        ColumnDef::new(Conversation::SummaryTemplateId).integer(),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .alter_table(
+                Table::alter()
+                    .table(Conversation::Table)
+                    .drop_column(Conversation::SummaryTemplateId)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(SummaryTemplate::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum SummaryTemplate {
+    Table,
+    Id,
+    Name,
+    Prompt,
+    Sections,
+    JsonSchema,
+    TitleKeywords,
+    TranscriptKeywords,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Conversation {
+    Table,
+    SummaryTemplateId,
+}
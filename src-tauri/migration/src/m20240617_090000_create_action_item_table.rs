@@ -0,0 +1,80 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ActionItem::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ActionItem::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(ActionItem::ConversationId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(ActionItem::Title).string().not_null())
+                    .col(ColumnDef::new(ActionItem::Assignee).string())
+                    .col(ColumnDef::new(ActionItem::DueDate).string())
+                    .col(ColumnDef::new(ActionItem::Status).string().not_null())
+                    .col(ColumnDef::new(ActionItem::SourceT0).big_integer())
+                    .col(
+                        ColumnDef::new(ActionItem::CreatedAt)
+                            .date_time()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(ActionItem::UpdatedAt)
+                            .date_time()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-action_item-conversation_id")
+                            .from(ActionItem::Table, ActionItem::ConversationId)
+                            .to(Conversation::Table, Conversation::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ActionItem::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum ActionItem {
+    Table,
+    Id,
+    ConversationId,
+    Title,
+    Assignee,
+    DueDate,
+    Status,
+    SourceT0,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Conversation {
+    Table,
+    Id,
+}
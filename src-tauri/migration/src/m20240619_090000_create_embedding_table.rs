@@ -0,0 +1,84 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Embedding::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Embedding::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(Embedding::ConversationId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(Embedding::Source).string().not_null())
+                    .col(ColumnDef::new(Embedding::T0).big_integer())
+                    .col(ColumnDef::new(Embedding::Content).string().not_null())
+                    .col(ColumnDef::new(Embedding::Model).string().not_null())
+                    .col(ColumnDef::new(Embedding::Vector).blob().not_null())
+                    .col(
+                        ColumnDef::new(Embedding::CreatedAt)
+                            .date_time()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-embedding-conversation_id")
+                            .from(Embedding::Table, Embedding::ConversationId)
+                            .to(Conversation::Table, Conversation::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Searches load every vector of one model.
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-embedding-model")
+                    .table(Embedding::Table)
+                    .col(Embedding::Model)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Embedding::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Embedding {
+    Table,
+    Id,
+    ConversationId,
+    Source,
+    T0,
+    Content,
+    Model,
+    Vector,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Conversation {
+    Table,
+    Id,
+}
@@ -0,0 +1,76 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Insight::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Insight::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Insight::ConversationId).integer().not_null())
+                    .col(ColumnDef::new(Insight::Kind).string().not_null())
+                    .col(ColumnDef::new(Insight::Content).string().not_null())
+                    .col(ColumnDef::new(Insight::SourceT0).big_integer())
+                    .col(
+                        ColumnDef::new(Insight::CreatedAt)
+                            .date_time()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-insight-conversation_id")
+                            .from(Insight::Table, Insight::ConversationId)
+                            .to(Conversation::Table, Conversation::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // Lists are usually of one kind, across meetings.
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-insight-kind")
+                    .table(Insight::Table)
+                    .col(Insight::Kind)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Insight::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Insight {
+    Table,
+    Id,
+    ConversationId,
+    Kind,
+    Content,
+    SourceT0,
+    CreatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Conversation {
+    Table,
+    Id,
+}
@@ -0,0 +1,90 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Artifact::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Artifact::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(
+                        ColumnDef::new(Artifact::ConversationId)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(Artifact::Name).string().not_null())
+                    .col(ColumnDef::new(Artifact::Prompt).string().not_null())
+                    .col(ColumnDef::new(Artifact::Content).string().not_null())
+                    .col(ColumnDef::new(Artifact::Model).string().not_null())
+                    .col(
+                        ColumnDef::new(Artifact::CreatedAt)
+                            .date_time()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .col(
+                        ColumnDef::new(Artifact::UpdatedAt)
+                            .date_time()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-artifact-conversation_id")
+                            .from(Artifact::Table, Artifact::ConversationId)
+                            .to(Conversation::Table, Conversation::Id)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-artifact-conversation_id-name")
+                    .table(Artifact::Table)
+                    .col(Artifact::ConversationId)
+                    .col(Artifact::Name)
+                    .unique()
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Artifact::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Artifact {
+    Table,
+    Id,
+    ConversationId,
+    Name,
+    Prompt,
+    Content,
+    Model,
+    CreatedAt,
+    UpdatedAt,
+}
+
+#[derive(DeriveIden)]
+enum Conversation {
+    Table,
+    Id,
+}
@@ -0,0 +1,126 @@
+use sea_orm_migration::prelude::*;
+
+struct SeedPrompt {
+    key: &'static str,
+    template: &'static str,
+    variables: &'static str,
+}
+
+/// The prompts the pipeline was built with before they could be edited. Frozen, later changes
+/// to the defaults are seeded by migrations of their own.
+const SEED_PROMPTS: [SeedPrompt; 6] = [
+    SeedPrompt {
+        key: "summary",
+        template: "{{instructions}}\ntranscript: {{transcript}}",
+        variables: "instructions, transcript",
+    },
+    SeedPrompt {
+        key: "chunk_summary",
+        template: "Summarize this part of a meeting transcript, keeping decisions, numbers and who said what: {{transcript}}",
+        variables: "transcript",
+    },
+    SeedPrompt {
+        key: "combine_summaries",
+        template: "The following are summaries of consecutive parts of one meeting.\n\n{{summaries}}",
+        variables: "summaries",
+    },
+    SeedPrompt {
+        key: "action_items",
+        template: "Create action items from a transcript. Each line of the transcript starts with its timestamp.\ntranscript: {{transcript}}",
+        variables: "transcript",
+    },
+    SeedPrompt {
+        key: "insights",
+        template: "List the decisions, open questions, risks and topics of a transcript. Each line of the transcript starts with its timestamp.\ntranscript: {{transcript}}",
+        variables: "transcript",
+    },
+    SeedPrompt {
+        key: "title",
+        template: "Can you generate a short meeting title from this? Answer with the title only: {{summary}}",
+        variables: "summary",
+    },
+];
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(Prompt::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(Prompt::Id)
+                            .integer()
+                            .not_null()
+                            .auto_increment()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(Prompt::Key).string().not_null())
+                    .col(ColumnDef::new(Prompt::Version).integer().not_null())
+                    .col(ColumnDef::new(Prompt::Template).string().not_null())
+                    .col(ColumnDef::new(Prompt::Variables).string().not_null())
+                    .col(ColumnDef::new(Prompt::Model).string())
+                    .col(
+                        ColumnDef::new(Prompt::CreatedAt)
+                            .date_time()
+                            .not_null()
+                            .default(Expr::current_timestamp()),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-prompt-key-version")
+                    .table(Prompt::Table)
+                    .col(Prompt::Key)
+                    .col(Prompt::Version)
+                    .unique()
+                    .to_owned(),
+            )
+            .await?;
+
+        let mut seed = Query::insert()
+            .into_table(Prompt::Table)
+            .columns([
+                Prompt::Key,
+                Prompt::Version,
+                Prompt::Template,
+                Prompt::Variables,
+            ])
+            .to_owned();
+        for prompt in SEED_PROMPTS {
+            seed.values_panic([
+                prompt.key.into(),
+                1.into(),
+                prompt.template.into(),
+                prompt.variables.into(),
+            ]);
+        }
+        manager.exec_stmt(seed).await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(Prompt::Table).to_owned())
+            .await
+    }
+}
+
+#[derive(DeriveIden)]
+enum Prompt {
+    Table,
+    Id,
+    Key,
+    Version,
+    Template,
+    Variables,
+    Model,
+    CreatedAt,
+}
@@ -1,6 +1,18 @@
-use ::entity::{conversation, conversation::Entity as Conversation};
+use ::entity::{
+    action_item, action_item::ActionItemStatus, action_item::Entity as ActionItem, artifact,
+    artifact::Entity as Artifact, audio_redaction, audio_redaction::Entity as AudioRedaction,
+    chat_message, chat_message::Entity as ChatMessage, conversation,
+    conversation::Entity as Conversation, custom_prompt, custom_prompt::Entity as CustomPrompt,
+    embedding, embedding::Entity as Embedding, glossary_term,
+    glossary_term::Entity as GlossaryTerm, insight, insight::Entity as Insight, job,
+    job::Entity as Job, job::JobStatus, output_version, output_version::Entity as OutputVersion,
+    prompt, prompt::Entity as Prompt, redaction, redaction::Entity as Redaction,
+    redaction::RedactionKind, setting, setting::Entity as Setting, summary_template,
+    summary_template::Entity as SummaryTemplate, transcript_edit,
+    transcript_edit::Entity as TranscriptEdit,
+};
 use chrono::Utc;
-use sea_orm::*;
+use sea_orm::{sea_query::Expr, *};
 
 pub struct Mutation;
 
@@ -31,6 +43,10 @@ impl Mutation {
         conversation::ActiveModel {
             id: post.id,
             title: Set(form_data.title.to_owned()),
+            title_edited: Set(form_data.title_edited),
+            summary_template_id: post.summary_template_id,
+            tags: post.tags,
+            participants: post.participants,
             created_at: post.created_at,
             updated_at: Set(Utc::now().to_string()),
         }
@@ -38,6 +54,66 @@ impl Mutation {
         .await
     }
 
+    /// `None` leaves the choice to the auto-selection rules.
+    pub async fn set_conversation_summary_template(
+        db: &DbConn,
+        id: i32,
+        summary_template_id: Option<i32>,
+    ) -> Result<conversation::Model, DbErr> {
+        let conversation: conversation::ActiveModel = Conversation::find_by_id(id)
+            .one(db)
+            .await?
+            .ok_or(DbErr::Custom("Cannot find post.".to_owned()))
+            .map(Into::into)?;
+
+        conversation::ActiveModel {
+            summary_template_id: Set(summary_template_id),
+            updated_at: Set(Utc::now().to_string()),
+            ..conversation
+        }
+        .update(db)
+        .await
+    }
+
+    pub async fn set_conversation_tags(
+        db: &DbConn,
+        id: i32,
+        tags: Option<String>,
+    ) -> Result<conversation::Model, DbErr> {
+        let conversation: conversation::ActiveModel = Conversation::find_by_id(id)
+            .one(db)
+            .await?
+            .ok_or(DbErr::Custom("Cannot find post.".to_owned()))
+            .map(Into::into)?;
+
+        conversation::ActiveModel {
+            tags: Set(tags),
+            updated_at: Set(Utc::now().to_string()),
+            ..conversation
+        }
+        .update(db)
+        .await
+    }
+
+    pub async fn set_conversation_participants(
+        db: &DbConn,
+        id: i32,
+        participants: Option<String>,
+    ) -> Result<conversation::Model, DbErr> {
+        let conversation: conversation::ActiveModel = Conversation::find_by_id(id)
+            .one(db)
+            .await?
+            .ok_or(DbErr::Custom("Cannot find post.".to_owned()))
+            .map(Into::into)?;
+
+        conversation::ActiveModel {
+            participants: Set(participants),
+            ..conversation
+        }
+        .update(db)
+        .await
+    }
+
     pub async fn delete_conversation(db: &DbConn, id: i32) -> Result<DeleteResult, DbErr> {
         let conversation: conversation::ActiveModel = Conversation::find_by_id(id)
             .one(db)
@@ -51,4 +127,672 @@ impl Mutation {
     pub async fn delete_all_conversations(db: &DbConn) -> Result<DeleteResult, DbErr> {
         Conversation::delete_many().exec(db).await
     }
+
+    pub async fn create_job(db: &DbConn, form_data: job::Model) -> Result<job::ActiveModel, DbErr> {
+        job::ActiveModel {
+            conversation_id: Set(form_data.conversation_id),
+            stages: Set(form_data.stages.to_owned()),
+            stage: Set(form_data.stage),
+            status: Set(form_data.status),
+            progress: Set(form_data.progress),
+            attempts: Set(form_data.attempts),
+            error: Set(form_data.error.to_owned()),
+            version_id: Set(form_data.version_id),
+            overrides: Set(form_data.overrides.to_owned()),
+            ..Default::default()
+        }
+        .save(db)
+        .await
+    }
+
+    pub async fn update_job_by_id(
+        db: &DbConn,
+        id: i32,
+        form_data: job::Model,
+    ) -> Result<job::Model, DbErr> {
+        let job: job::ActiveModel = Job::find_by_id(id)
+            .one(db)
+            .await?
+            .ok_or(DbErr::Custom("Cannot find job.".to_owned()))
+            .map(Into::into)?;
+
+        job::ActiveModel {
+            id: job.id,
+            conversation_id: job.conversation_id,
+            stages: Set(form_data.stages.to_owned()),
+            stage: Set(form_data.stage),
+            status: Set(form_data.status),
+            progress: Set(form_data.progress),
+            attempts: Set(form_data.attempts),
+            error: Set(form_data.error.to_owned()),
+            version_id: job.version_id,
+            overrides: job.overrides,
+            created_at: job.created_at,
+            updated_at: Set(Utc::now().to_string()),
+        }
+        .update(db)
+        .await
+    }
+
+    /// Puts jobs that were interrupted mid-run (e.g. by the app quitting) back on the queue.
+    pub async fn requeue_running_jobs(db: &DbConn) -> Result<UpdateResult, DbErr> {
+        Job::update_many()
+            .col_expr(
+                job::Column::Status,
+                Expr::value(JobStatus::Queued.to_value()),
+            )
+            .filter(job::Column::Status.eq(JobStatus::Running))
+            .exec(db)
+            .await
+    }
+
+    pub async fn upsert_setting(db: &DbConn, key: &str, value: String) -> Result<(), DbErr> {
+        Setting::insert(setting::ActiveModel {
+            key: Set(key.to_owned()),
+            value: Set(value),
+        })
+        .on_conflict(
+            sea_query::OnConflict::column(setting::Column::Key)
+                .update_column(setting::Column::Value)
+                .to_owned(),
+        )
+        .exec(db)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn create_glossary_term(
+        db: &DbConn,
+        form_data: glossary_term::Model,
+    ) -> Result<glossary_term::ActiveModel, DbErr> {
+        glossary_term::ActiveModel {
+            term: Set(form_data.term.to_owned()),
+            misheard_as: Set(form_data.misheard_as.to_owned()),
+            ..Default::default()
+        }
+        .save(db)
+        .await
+    }
+
+    pub async fn update_glossary_term_by_id(
+        db: &DbConn,
+        id: i32,
+        form_data: glossary_term::Model,
+    ) -> Result<glossary_term::Model, DbErr> {
+        let term: glossary_term::ActiveModel = GlossaryTerm::find_by_id(id)
+            .one(db)
+            .await?
+            .ok_or(DbErr::Custom("Cannot find glossary term.".to_owned()))
+            .map(Into::into)?;
+
+        glossary_term::ActiveModel {
+            id: term.id,
+            term: Set(form_data.term.to_owned()),
+            misheard_as: Set(form_data.misheard_as.to_owned()),
+            created_at: term.created_at,
+            updated_at: Set(Utc::now().to_string()),
+        }
+        .update(db)
+        .await
+    }
+
+    pub async fn delete_glossary_term(db: &DbConn, id: i32) -> Result<DeleteResult, DbErr> {
+        GlossaryTerm::delete_by_id(id).exec(db).await
+    }
+
+    /// Saves a prompt as the next version of its key.
+    pub async fn create_prompt_version(
+        db: &DbConn,
+        form_data: prompt::Model,
+    ) -> Result<prompt::Model, DbErr> {
+        let txn = db.begin().await?;
+
+        let latest = Prompt::find()
+            .filter(prompt::Column::Key.eq(form_data.key.as_str()))
+            .order_by_desc(prompt::Column::Version)
+            .one(&txn)
+            .await?;
+        let prompt = prompt::ActiveModel {
+            key: Set(form_data.key.to_owned()),
+            version: Set(latest.map_or(1, |latest| latest.version + 1)),
+            template: Set(form_data.template.to_owned()),
+            variables: Set(form_data.variables.to_owned()),
+            model: Set(form_data.model.to_owned()),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await?;
+
+        txn.commit().await?;
+
+        Ok(prompt)
+    }
+
+    pub async fn create_custom_prompt(
+        db: &DbConn,
+        form_data: custom_prompt::Model,
+    ) -> Result<custom_prompt::ActiveModel, DbErr> {
+        custom_prompt::ActiveModel {
+            name: Set(form_data.name.to_owned()),
+            prompt: Set(form_data.prompt.to_owned()),
+            ..Default::default()
+        }
+        .save(db)
+        .await
+    }
+
+    pub async fn update_custom_prompt_by_id(
+        db: &DbConn,
+        id: i32,
+        form_data: custom_prompt::Model,
+    ) -> Result<custom_prompt::Model, DbErr> {
+        let prompt: custom_prompt::ActiveModel = CustomPrompt::find_by_id(id)
+            .one(db)
+            .await?
+            .ok_or(DbErr::Custom("Cannot find custom prompt.".to_owned()))
+            .map(Into::into)?;
+
+        custom_prompt::ActiveModel {
+            id: prompt.id,
+            name: Set(form_data.name.to_owned()),
+            prompt: Set(form_data.prompt.to_owned()),
+            created_at: prompt.created_at,
+            updated_at: Set(Utc::now().to_string()),
+        }
+        .update(db)
+        .await
+    }
+
+    pub async fn delete_custom_prompt(db: &DbConn, id: i32) -> Result<DeleteResult, DbErr> {
+        CustomPrompt::delete_by_id(id).exec(db).await
+    }
+
+    /// Saves an artifact under its name, replacing the conversation's artifact of that name.
+    pub async fn save_artifact(
+        db: &DbConn,
+        form_data: artifact::Model,
+    ) -> Result<artifact::Model, DbErr> {
+        let existing = Artifact::find()
+            .filter(artifact::Column::ConversationId.eq(form_data.conversation_id))
+            .filter(artifact::Column::Name.eq(form_data.name.as_str()))
+            .one(db)
+            .await?;
+
+        match existing {
+            Some(existing) => {
+                artifact::ActiveModel {
+                    prompt: Set(form_data.prompt.to_owned()),
+                    content: Set(form_data.content.to_owned()),
+                    model: Set(form_data.model.to_owned()),
+                    updated_at: Set(Utc::now().to_string()),
+                    ..existing.into()
+                }
+                .update(db)
+                .await
+            }
+            None => {
+                artifact::ActiveModel {
+                    conversation_id: Set(form_data.conversation_id),
+                    name: Set(form_data.name.to_owned()),
+                    prompt: Set(form_data.prompt.to_owned()),
+                    content: Set(form_data.content.to_owned()),
+                    model: Set(form_data.model.to_owned()),
+                    ..Default::default()
+                }
+                .insert(db)
+                .await
+            }
+        }
+    }
+
+    pub async fn delete_artifact(db: &DbConn, id: i32) -> Result<DeleteResult, DbErr> {
+        Artifact::delete_by_id(id).exec(db).await
+    }
+
+    pub async fn create_transcript_edit(
+        db: &DbConn,
+        form_data: transcript_edit::Model,
+    ) -> Result<transcript_edit::ActiveModel, DbErr> {
+        transcript_edit::ActiveModel {
+            conversation_id: Set(form_data.conversation_id),
+            kind: Set(form_data.kind),
+            segment_t0: Set(form_data.segment_t0),
+            segment_t1: Set(form_data.segment_t1),
+            text: Set(form_data.text.to_owned()),
+            speaker: Set(form_data.speaker.to_owned()),
+            split_offset: Set(form_data.split_offset),
+            ..Default::default()
+        }
+        .save(db)
+        .await
+    }
+
+    pub async fn delete_transcript_edit(db: &DbConn, id: i32) -> Result<DeleteResult, DbErr> {
+        TranscriptEdit::delete_by_id(id).exec(db).await
+    }
+
+    pub async fn create_redactions(
+        db: &DbConn,
+        redactions: Vec<redaction::Model>,
+    ) -> Result<(), DbErr> {
+        if redactions.is_empty() {
+            return Ok(());
+        }
+
+        Redaction::insert_many(
+            redactions
+                .into_iter()
+                .map(|form_data| redaction::ActiveModel {
+                    conversation_id: Set(form_data.conversation_id),
+                    kind: Set(form_data.kind),
+                    placeholder: Set(form_data.placeholder),
+                    original: Set(form_data.original),
+                    ..Default::default()
+                }),
+        )
+        .exec(db)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Records spans masked in a conversation's audio. The audio stays masked for good, so rows
+    /// are only ever added, skipping spans that are already recorded.
+    pub async fn create_audio_redactions(
+        db: &DbConn,
+        audio_redactions: Vec<audio_redaction::Model>,
+    ) -> Result<(), DbErr> {
+        if audio_redactions.is_empty() {
+            return Ok(());
+        }
+
+        let txn = db.begin().await?;
+
+        let conversation_ids: Vec<i32> = audio_redactions
+            .iter()
+            .map(|form_data| form_data.conversation_id)
+            .collect();
+        let mut recorded: Vec<(i32, i64, i64, RedactionKind)> = AudioRedaction::find()
+            .filter(audio_redaction::Column::ConversationId.is_in(conversation_ids))
+            .all(&txn)
+            .await?
+            .into_iter()
+            .map(|row| (row.conversation_id, row.t0, row.t1, row.kind))
+            .collect();
+
+        let mut new_rows: Vec<audio_redaction::ActiveModel> = Vec::new();
+        for form_data in audio_redactions {
+            let key = (
+                form_data.conversation_id,
+                form_data.t0,
+                form_data.t1,
+                form_data.kind,
+            );
+            if recorded.contains(&key) {
+                continue;
+            }
+            recorded.push(key);
+            new_rows.push(audio_redaction::ActiveModel {
+                conversation_id: Set(form_data.conversation_id),
+                t0: Set(form_data.t0),
+                t1: Set(form_data.t1),
+                method: Set(form_data.method),
+                kind: Set(form_data.kind),
+                placeholder: Set(form_data.placeholder),
+                ..Default::default()
+            });
+        }
+        if !new_rows.is_empty() {
+            AudioRedaction::insert_many(new_rows).exec(&txn).await?;
+        }
+
+        txn.commit().await
+    }
+
+    pub async fn update_transcript_edit_text(
+        db: &DbConn,
+        id: i32,
+        text: String,
+    ) -> Result<transcript_edit::Model, DbErr> {
+        let edit: transcript_edit::ActiveModel = TranscriptEdit::find_by_id(id)
+            .one(db)
+            .await?
+            .ok_or(DbErr::Custom("Cannot find transcript edit.".to_owned()))
+            .map(Into::into)?;
+
+        transcript_edit::ActiveModel {
+            text: Set(Some(text)),
+            ..edit
+        }
+        .update(db)
+        .await
+    }
+
+    pub async fn create_output_version(
+        db: &DbConn,
+        form_data: output_version::Model,
+    ) -> Result<output_version::ActiveModel, DbErr> {
+        output_version::ActiveModel {
+            conversation_id: Set(form_data.conversation_id),
+            job_id: Set(form_data.job_id),
+            stages: Set(form_data.stages.to_owned()),
+            overrides: Set(form_data.overrides.to_owned()),
+            is_current: Set(form_data.is_current),
+            ..Default::default()
+        }
+        .save(db)
+        .await
+    }
+
+    pub async fn set_output_version_job(
+        db: &DbConn,
+        id: i32,
+        job_id: i32,
+    ) -> Result<output_version::Model, DbErr> {
+        let version: output_version::ActiveModel = OutputVersion::find_by_id(id)
+            .one(db)
+            .await?
+            .ok_or(DbErr::Custom("Cannot find output version.".to_owned()))
+            .map(Into::into)?;
+
+        output_version::ActiveModel {
+            job_id: Set(Some(job_id)),
+            ..version
+        }
+        .update(db)
+        .await
+    }
+
+    /// Marks one version as current and clears the flag on the conversation's others.
+    pub async fn set_current_output_version(
+        db: &DbConn,
+        conversation_id: i32,
+        id: i32,
+    ) -> Result<output_version::Model, DbErr> {
+        let txn = db.begin().await?;
+
+        OutputVersion::update_many()
+            .col_expr(output_version::Column::IsCurrent, Expr::value(false))
+            .filter(output_version::Column::ConversationId.eq(conversation_id))
+            .exec(&txn)
+            .await?;
+
+        let version: output_version::ActiveModel = OutputVersion::find_by_id(id)
+            .filter(output_version::Column::ConversationId.eq(conversation_id))
+            .one(&txn)
+            .await?
+            .ok_or(DbErr::Custom("Cannot find output version.".to_owned()))
+            .map(Into::into)?;
+
+        let version = output_version::ActiveModel {
+            is_current: Set(true),
+            ..version
+        }
+        .update(&txn)
+        .await?;
+
+        txn.commit().await?;
+
+        Ok(version)
+    }
+
+    pub async fn create_summary_template(
+        db: &DbConn,
+        form_data: summary_template::Model,
+    ) -> Result<summary_template::ActiveModel, DbErr> {
+        summary_template::ActiveModel {
+            name: Set(form_data.name.to_owned()),
+            prompt: Set(form_data.prompt.to_owned()),
+            sections: Set(form_data.sections.to_owned()),
+            json_schema: Set(form_data.json_schema.to_owned()),
+            title_keywords: Set(form_data.title_keywords.to_owned()),
+            transcript_keywords: Set(form_data.transcript_keywords.to_owned()),
+            ..Default::default()
+        }
+        .save(db)
+        .await
+    }
+
+    pub async fn update_summary_template_by_id(
+        db: &DbConn,
+        id: i32,
+        form_data: summary_template::Model,
+    ) -> Result<summary_template::Model, DbErr> {
+        let template: summary_template::ActiveModel = SummaryTemplate::find_by_id(id)
+            .one(db)
+            .await?
+            .ok_or(DbErr::Custom("Cannot find summary template.".to_owned()))
+            .map(Into::into)?;
+
+        summary_template::ActiveModel {
+            id: template.id,
+            name: Set(form_data.name.to_owned()),
+            prompt: Set(form_data.prompt.to_owned()),
+            sections: Set(form_data.sections.to_owned()),
+            json_schema: Set(form_data.json_schema.to_owned()),
+            title_keywords: Set(form_data.title_keywords.to_owned()),
+            transcript_keywords: Set(form_data.transcript_keywords.to_owned()),
+            created_at: template.created_at,
+            updated_at: Set(Utc::now().to_string()),
+        }
+        .update(db)
+        .await
+    }
+
+    /// Conversations that used the template go back to the auto-selection rules.
+    pub async fn delete_summary_template(db: &DbConn, id: i32) -> Result<DeleteResult, DbErr> {
+        let txn = db.begin().await?;
+
+        Conversation::update_many()
+            .col_expr(
+                conversation::Column::SummaryTemplateId,
+                Expr::value(Option::<i32>::None),
+            )
+            .filter(conversation::Column::SummaryTemplateId.eq(id))
+            .exec(&txn)
+            .await?;
+
+        let result = SummaryTemplate::delete_by_id(id).exec(&txn).await?;
+
+        txn.commit().await?;
+
+        Ok(result)
+    }
+
+    /// Replaces a conversation's open action items with freshly extracted ones. Finished items
+    /// stay, and new items with the title of a finished one are dropped.
+    ///
+    /// Items the user edited are theirs: extracted again under the same title they keep the
+    /// user's assignee and due date, and they aren't removed when the model no longer finds them.
+    pub async fn replace_open_action_items(
+        db: &DbConn,
+        conversation_id: i32,
+        action_items: Vec<action_item::Model>,
+    ) -> Result<(), DbErr> {
+        let txn = db.begin().await?;
+
+        let existing = ActionItem::find()
+            .filter(action_item::Column::ConversationId.eq(conversation_id))
+            .all(&txn)
+            .await?;
+        let mut kept: Vec<i32> = Vec::new();
+        let mut new_items: Vec<action_item::ActiveModel> = Vec::new();
+        for form_data in action_items {
+            let title = form_data.title.to_lowercase();
+            match existing
+                .iter()
+                .find(|item| item.title.to_lowercase() == title)
+            {
+                Some(item) if item.status == ActionItemStatus::Done => {}
+                Some(item) => {
+                    kept.push(item.id);
+                    // Edits set `updated_at`, untouched items follow the new extraction.
+                    if item.updated_at == item.created_at {
+                        action_item::ActiveModel {
+                            assignee: Set(form_data.assignee),
+                            due_date: Set(form_data.due_date),
+                            source_t0: Set(form_data.source_t0),
+                            ..item.clone().into()
+                        }
+                        .update(&txn)
+                        .await?;
+                    }
+                }
+                None => new_items.push(action_item::ActiveModel {
+                    conversation_id: Set(conversation_id),
+                    title: Set(form_data.title),
+                    assignee: Set(form_data.assignee),
+                    due_date: Set(form_data.due_date),
+                    status: Set(ActionItemStatus::Open),
+                    source_t0: Set(form_data.source_t0),
+                    ..Default::default()
+                }),
+            }
+        }
+
+        ActionItem::delete_many()
+            .filter(action_item::Column::ConversationId.eq(conversation_id))
+            .filter(action_item::Column::Status.eq(ActionItemStatus::Open))
+            .filter(action_item::Column::Id.is_not_in(kept))
+            .filter(
+                Expr::col(action_item::Column::UpdatedAt).equals(action_item::Column::CreatedAt),
+            )
+            .exec(&txn)
+            .await?;
+        if !new_items.is_empty() {
+            ActionItem::insert_many(new_items).exec(&txn).await?;
+        }
+
+        txn.commit().await
+    }
+
+    /// Replaces a conversation's decisions, open questions, risks and topics.
+    pub async fn replace_insights(
+        db: &DbConn,
+        conversation_id: i32,
+        insights: Vec<insight::Model>,
+    ) -> Result<(), DbErr> {
+        let txn = db.begin().await?;
+
+        Insight::delete_many()
+            .filter(insight::Column::ConversationId.eq(conversation_id))
+            .exec(&txn)
+            .await?;
+
+        let insights: Vec<insight::ActiveModel> = insights
+            .into_iter()
+            .map(|form_data| insight::ActiveModel {
+                conversation_id: Set(conversation_id),
+                kind: Set(form_data.kind),
+                content: Set(form_data.content),
+                source_t0: Set(form_data.source_t0),
+                ..Default::default()
+            })
+            .collect();
+        if !insights.is_empty() {
+            Insight::insert_many(insights).exec(&txn).await?;
+        }
+
+        txn.commit().await
+    }
+
+    pub async fn update_action_item_by_id(
+        db: &DbConn,
+        id: i32,
+        form_data: action_item::Model,
+    ) -> Result<action_item::Model, DbErr> {
+        let item: action_item::ActiveModel = ActionItem::find_by_id(id)
+            .one(db)
+            .await?
+            .ok_or(DbErr::Custom("Cannot find action item.".to_owned()))
+            .map(Into::into)?;
+
+        action_item::ActiveModel {
+            title: Set(form_data.title.to_owned()),
+            assignee: Set(form_data.assignee.to_owned()),
+            due_date: Set(form_data.due_date.to_owned()),
+            status: Set(form_data.status),
+            updated_at: Set(Utc::now().to_string()),
+            ..item
+        }
+        .update(db)
+        .await
+    }
+
+    pub async fn set_action_item_status(
+        db: &DbConn,
+        id: i32,
+        status: ActionItemStatus,
+    ) -> Result<action_item::Model, DbErr> {
+        let item: action_item::ActiveModel = ActionItem::find_by_id(id)
+            .one(db)
+            .await?
+            .ok_or(DbErr::Custom("Cannot find action item.".to_owned()))
+            .map(Into::into)?;
+
+        action_item::ActiveModel {
+            status: Set(status),
+            updated_at: Set(Utc::now().to_string()),
+            ..item
+        }
+        .update(db)
+        .await
+    }
+
+    pub async fn create_chat_message(
+        db: &DbConn,
+        form_data: chat_message::Model,
+    ) -> Result<chat_message::Model, DbErr> {
+        chat_message::ActiveModel {
+            conversation_id: Set(form_data.conversation_id),
+            role: Set(form_data.role),
+            content: Set(form_data.content.to_owned()),
+            ..Default::default()
+        }
+        .insert(db)
+        .await
+    }
+
+    pub async fn delete_chat_messages(
+        db: &DbConn,
+        conversation_id: i32,
+    ) -> Result<DeleteResult, DbErr> {
+        ChatMessage::delete_many()
+            .filter(chat_message::Column::ConversationId.eq(conversation_id))
+            .exec(db)
+            .await
+    }
+
+    /// Swaps a conversation's search index for `embeddings`.
+    pub async fn replace_embeddings(
+        db: &DbConn,
+        conversation_id: i32,
+        embeddings: Vec<embedding::Model>,
+    ) -> Result<(), DbErr> {
+        let txn = db.begin().await?;
+
+        Embedding::delete_many()
+            .filter(embedding::Column::ConversationId.eq(conversation_id))
+            .exec(&txn)
+            .await?;
+
+        let embeddings: Vec<embedding::ActiveModel> = embeddings
+            .into_iter()
+            .map(|form_data| embedding::ActiveModel {
+                conversation_id: Set(conversation_id),
+                source: Set(form_data.source),
+                t0: Set(form_data.t0),
+                content: Set(form_data.content),
+                model: Set(form_data.model),
+                vector: Set(form_data.vector),
+                ..Default::default()
+            })
+            .collect();
+        if !embeddings.is_empty() {
+            Embedding::insert_many(embeddings).exec(&txn).await?;
+        }
+
+        txn.commit().await
+    }
 }
@@ -1,4 +1,16 @@
-use ::entity::{conversation, conversation::Entity as Conversation};
+use ::entity::{
+    action_item, action_item::ActionItemStatus, action_item::Entity as ActionItem, artifact,
+    artifact::Entity as Artifact, audio_redaction, audio_redaction::Entity as AudioRedaction,
+    chat_message, chat_message::Entity as ChatMessage, conversation,
+    conversation::Entity as Conversation, custom_prompt, custom_prompt::Entity as CustomPrompt,
+    embedding, embedding::EmbeddingSource, embedding::Entity as Embedding, glossary_term,
+    glossary_term::Entity as GlossaryTerm, insight, insight::Entity as Insight,
+    insight::InsightKind, job, job::Entity as Job, job::JobStatus, output_version,
+    output_version::Entity as OutputVersion, prompt, prompt::Entity as Prompt, redaction,
+    redaction::Entity as Redaction, setting, setting::Entity as Setting, summary_template,
+    summary_template::Entity as SummaryTemplate, transcript_edit,
+    transcript_edit::Entity as TranscriptEdit,
+};
 use sea_orm::*;
 
 pub struct Query;
@@ -12,6 +24,13 @@ impl Query {
     }
 
     /// If ok, returns (post models, num pages).
+    pub async fn find_conversations(db: &DbConn) -> Result<Vec<conversation::Model>, DbErr> {
+        Conversation::find()
+            .order_by_asc(conversation::Column::Id)
+            .all(db)
+            .await
+    }
+
     pub async fn find_conversations_in_page(
         db: &DbConn,
         page: u64,
@@ -26,4 +45,273 @@ impl Query {
         // Fetch paginated posts
         paginator.fetch_page(page - 1).await.map(|p| (p, num_pages))
     }
+
+    pub async fn find_job_by_id(db: &DbConn, id: i32) -> Result<Option<job::Model>, DbErr> {
+        Job::find_by_id(id).one(db).await
+    }
+
+    /// Oldest job that is still waiting to run.
+    pub async fn find_next_queued_job(db: &DbConn) -> Result<Option<job::Model>, DbErr> {
+        Job::find()
+            .filter(job::Column::Status.eq(JobStatus::Queued))
+            .order_by_asc(job::Column::Id)
+            .one(db)
+            .await
+    }
+
+    /// Newest first, optionally limited to a single conversation.
+    pub async fn find_jobs(
+        db: &DbConn,
+        conversation_id: Option<i32>,
+    ) -> Result<Vec<job::Model>, DbErr> {
+        let mut query = Job::find().order_by_desc(job::Column::Id);
+        if let Some(conversation_id) = conversation_id {
+            query = query.filter(job::Column::ConversationId.eq(conversation_id));
+        }
+        query.all(db).await
+    }
+
+    pub async fn find_setting(db: &DbConn, key: &str) -> Result<Option<setting::Model>, DbErr> {
+        Setting::find_by_id(key.to_owned()).one(db).await
+    }
+
+    pub async fn find_glossary_terms(db: &DbConn) -> Result<Vec<glossary_term::Model>, DbErr> {
+        GlossaryTerm::find()
+            .order_by_asc(glossary_term::Column::Term)
+            .all(db)
+            .await
+    }
+
+    /// The latest version of each prompt.
+    pub async fn find_latest_prompts(db: &DbConn) -> Result<Vec<prompt::Model>, DbErr> {
+        let mut prompts = Prompt::find()
+            .order_by_asc(prompt::Column::Key)
+            .order_by_desc(prompt::Column::Version)
+            .all(db)
+            .await?;
+        prompts.dedup_by(|later, first| later.key == first.key);
+
+        Ok(prompts)
+    }
+
+    pub async fn find_latest_prompt(
+        db: &DbConn,
+        key: &str,
+    ) -> Result<Option<prompt::Model>, DbErr> {
+        Prompt::find()
+            .filter(prompt::Column::Key.eq(key))
+            .order_by_desc(prompt::Column::Version)
+            .one(db)
+            .await
+    }
+
+    /// Every version of a prompt, newest first.
+    pub async fn find_prompt_versions(db: &DbConn, key: &str) -> Result<Vec<prompt::Model>, DbErr> {
+        Prompt::find()
+            .filter(prompt::Column::Key.eq(key))
+            .order_by_desc(prompt::Column::Version)
+            .all(db)
+            .await
+    }
+
+    pub async fn find_custom_prompts(db: &DbConn) -> Result<Vec<custom_prompt::Model>, DbErr> {
+        CustomPrompt::find()
+            .order_by_asc(custom_prompt::Column::Name)
+            .all(db)
+            .await
+    }
+
+    pub async fn find_custom_prompt_by_id(
+        db: &DbConn,
+        id: i32,
+    ) -> Result<Option<custom_prompt::Model>, DbErr> {
+        CustomPrompt::find_by_id(id).one(db).await
+    }
+
+    pub async fn find_artifacts(
+        db: &DbConn,
+        conversation_id: i32,
+    ) -> Result<Vec<artifact::Model>, DbErr> {
+        Artifact::find()
+            .filter(artifact::Column::ConversationId.eq(conversation_id))
+            .order_by_asc(artifact::Column::Id)
+            .all(db)
+            .await
+    }
+
+    pub async fn find_artifact_by_id(
+        db: &DbConn,
+        id: i32,
+    ) -> Result<Option<artifact::Model>, DbErr> {
+        Artifact::find_by_id(id).one(db).await
+    }
+
+    /// Oldest first, the order they are replayed in.
+    pub async fn find_transcript_edits(
+        db: &DbConn,
+        conversation_id: i32,
+    ) -> Result<Vec<transcript_edit::Model>, DbErr> {
+        TranscriptEdit::find()
+            .filter(transcript_edit::Column::ConversationId.eq(conversation_id))
+            .order_by_asc(transcript_edit::Column::Id)
+            .all(db)
+            .await
+    }
+
+    pub async fn find_output_version_by_id(
+        db: &DbConn,
+        id: i32,
+    ) -> Result<Option<output_version::Model>, DbErr> {
+        OutputVersion::find_by_id(id).one(db).await
+    }
+
+    /// Oldest first.
+    pub async fn find_output_versions(
+        db: &DbConn,
+        conversation_id: i32,
+    ) -> Result<Vec<output_version::Model>, DbErr> {
+        OutputVersion::find()
+            .filter(output_version::Column::ConversationId.eq(conversation_id))
+            .order_by_asc(output_version::Column::Id)
+            .all(db)
+            .await
+    }
+
+    pub async fn find_redactions(
+        db: &DbConn,
+        conversation_id: i32,
+    ) -> Result<Vec<redaction::Model>, DbErr> {
+        Redaction::find()
+            .filter(redaction::Column::ConversationId.eq(conversation_id))
+            .order_by_asc(redaction::Column::Id)
+            .all(db)
+            .await
+    }
+
+    pub async fn find_audio_redactions(
+        db: &DbConn,
+        conversation_id: i32,
+    ) -> Result<Vec<audio_redaction::Model>, DbErr> {
+        AudioRedaction::find()
+            .filter(audio_redaction::Column::ConversationId.eq(conversation_id))
+            .order_by_asc(audio_redaction::Column::Id)
+            .all(db)
+            .await
+    }
+
+    /// Oldest first, the order the auto-selection rules are tried in.
+    pub async fn find_summary_templates(
+        db: &DbConn,
+    ) -> Result<Vec<summary_template::Model>, DbErr> {
+        SummaryTemplate::find()
+            .order_by_asc(summary_template::Column::Id)
+            .all(db)
+            .await
+    }
+
+    pub async fn find_summary_template_by_id(
+        db: &DbConn,
+        id: i32,
+    ) -> Result<Option<summary_template::Model>, DbErr> {
+        SummaryTemplate::find_by_id(id).one(db).await
+    }
+
+    /// Newest conversations first, items in the order they came up. Filters left `None` match
+    /// everything.
+    pub async fn find_action_items(
+        db: &DbConn,
+        conversation_id: Option<i32>,
+        status: Option<ActionItemStatus>,
+        assignee: Option<String>,
+    ) -> Result<Vec<action_item::Model>, DbErr> {
+        let mut query = ActionItem::find();
+        if let Some(conversation_id) = conversation_id {
+            query = query.filter(action_item::Column::ConversationId.eq(conversation_id));
+        }
+        if let Some(status) = status {
+            query = query.filter(action_item::Column::Status.eq(status));
+        }
+        if let Some(assignee) = assignee {
+            query = query.filter(action_item::Column::Assignee.eq(assignee));
+        }
+
+        query
+            .order_by_desc(action_item::Column::ConversationId)
+            .order_by_asc(action_item::Column::SourceT0)
+            .order_by_asc(action_item::Column::Id)
+            .all(db)
+            .await
+    }
+
+    /// Decisions, open questions, risks and topics across meetings, or of one conversation.
+    /// Dates are compared with the conversation's `created_at`, so `2024-06-01` works as a bound.
+    pub async fn find_insights(
+        db: &DbConn,
+        conversation_id: Option<i32>,
+        kind: Option<InsightKind>,
+        created_after: Option<String>,
+        created_before: Option<String>,
+    ) -> Result<Vec<insight::Model>, DbErr> {
+        let mut query = Insight::find().inner_join(Conversation);
+        if let Some(conversation_id) = conversation_id {
+            query = query.filter(insight::Column::ConversationId.eq(conversation_id));
+        }
+        if let Some(kind) = kind {
+            query = query.filter(insight::Column::Kind.eq(kind));
+        }
+        if let Some(created_after) = created_after {
+            query = query.filter(conversation::Column::CreatedAt.gte(created_after));
+        }
+        if let Some(created_before) = created_before {
+            query = query.filter(conversation::Column::CreatedAt.lt(created_before));
+        }
+
+        query
+            .order_by_desc(insight::Column::ConversationId)
+            .order_by_asc(insight::Column::SourceT0)
+            .order_by_asc(insight::Column::Id)
+            .all(db)
+            .await
+    }
+
+    /// A conversation's chat, oldest message first.
+    pub async fn find_chat_messages(
+        db: &DbConn,
+        conversation_id: i32,
+    ) -> Result<Vec<chat_message::Model>, DbErr> {
+        ChatMessage::find()
+            .filter(chat_message::Column::ConversationId.eq(conversation_id))
+            .order_by_asc(chat_message::Column::Id)
+            .all(db)
+            .await
+    }
+
+    /// Embeddings made with `model`, with their conversations. Dates are compared with the
+    /// conversation's `created_at`, so `2024-06-01` works as a bound.
+    pub async fn find_embeddings(
+        db: &DbConn,
+        model: &str,
+        conversation_ids: Option<Vec<i32>>,
+        created_after: Option<String>,
+        created_before: Option<String>,
+        source: Option<EmbeddingSource>,
+    ) -> Result<Vec<(embedding::Model, Option<conversation::Model>)>, DbErr> {
+        let mut query = Embedding::find()
+            .find_also_related(Conversation)
+            .filter(embedding::Column::Model.eq(model));
+        if let Some(conversation_ids) = conversation_ids {
+            query = query.filter(embedding::Column::ConversationId.is_in(conversation_ids));
+        }
+        if let Some(created_after) = created_after {
+            query = query.filter(conversation::Column::CreatedAt.gte(created_after));
+        }
+        if let Some(created_before) = created_before {
+            query = query.filter(conversation::Column::CreatedAt.lt(created_before));
+        }
+        if let Some(source) = source {
+            query = query.filter(embedding::Column::Source.eq(source));
+        }
+
+        query.all(db).await
+    }
 }
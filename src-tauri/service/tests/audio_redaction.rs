@@ -0,0 +1,81 @@
+use entity::{
+    audio_redaction::{self, AudioRedactionMethod},
+    conversation,
+    redaction::RedactionKind,
+};
+use migration::{Migrator, MigratorTrait};
+use sea_orm::Database;
+use service::{Mutation, Query};
+
+fn masked(conversation_id: i32, t0: i64, kind: RedactionKind) -> audio_redaction::Model {
+    audio_redaction::Model {
+        id: 0,
+        conversation_id,
+        t0,
+        t1: t0 + 1000,
+        method: AudioRedactionMethod::Tone,
+        kind,
+        placeholder: "[REDACTED]".to_owned(),
+        created_at: String::new(),
+    }
+}
+
+#[tokio::test]
+async fn keeps_rows_of_masked_audio_when_transcribed_again() {
+    let db = Database::connect("sqlite::memory:").await.unwrap();
+    Migrator::up(&db, None).await.unwrap();
+    let conversation_id = Mutation::create_conversation(
+        &db,
+        conversation::Model {
+            id: 0,
+            title: "Weekly sync".to_owned(),
+            tags: None,
+            participants: None,
+            created_at: String::new(),
+            updated_at: String::new(),
+            summary_template_id: None,
+            title_edited: false,
+        },
+    )
+    .await
+    .unwrap()
+    .id
+    .unwrap();
+
+    // First transcription masks an email and a phone number.
+    Mutation::create_audio_redactions(
+        &db,
+        vec![
+            masked(conversation_id, 1000, RedactionKind::Email),
+            masked(conversation_id, 5000, RedactionKind::PhoneNumber),
+        ],
+    )
+    .await
+    .unwrap();
+    // The second can't hear them anymore, and finds the phone number again in one more place.
+    Mutation::create_audio_redactions(
+        &db,
+        vec![
+            masked(conversation_id, 5000, RedactionKind::PhoneNumber),
+            masked(conversation_id, 9000, RedactionKind::PhoneNumber),
+        ],
+    )
+    .await
+    .unwrap();
+
+    let mut rows: Vec<(i64, RedactionKind)> = Query::find_audio_redactions(&db, conversation_id)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|row| (row.t0, row.kind))
+        .collect();
+    rows.sort_by_key(|(t0, _)| *t0);
+    assert_eq!(
+        rows,
+        [
+            (1000, RedactionKind::Email),
+            (5000, RedactionKind::PhoneNumber),
+            (9000, RedactionKind::PhoneNumber),
+        ]
+    );
+}
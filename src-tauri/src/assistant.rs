@@ -0,0 +1,153 @@
+use regex::Regex;
+use serde::Serialize;
+use service::sea_orm::DatabaseConnection;
+
+use crate::glossary::estimate_tokens;
+use crate::llm::{CompletionRequest, GenerationKind, LlmProvider, TokenSink};
+use crate::search::{rank_passages, RankedPassage, SearchFilters};
+use crate::utils::{format_timestamp, parse_timestamp};
+
+/// Passages an answer is drawn from, unless the filters ask for another number.
+const ANSWER_PASSAGES: usize = 12;
+
+const ANSWER_SYSTEM: &str = "You answer questions about past meetings from passages of their \
+transcripts and summaries. Each passage is headed with where it's from, like [#12 @ 04:31] for \
+conversation 12 at 4:31, or [#12] for the summary of conversation 12. Cite every passage your \
+answer relies on with its heading exactly as written. If the passages don't answer the question, \
+say so instead of guessing.";
+
+#[derive(Serialize)]
+pub struct CrossMeetingAnswer {
+    pub answer: String,
+    /// The passages the answer cites, in the order they're first cited.
+    pub citations: Vec<Citation>,
+}
+
+#[derive(Clone, PartialEq, Serialize)]
+pub struct Citation {
+    pub conversation_id: i32,
+    pub conversation_title: String,
+    /// Where in the recording, in milliseconds. Unset for a summary.
+    pub t0: Option<i64>,
+}
+
+/// Answers a question from the passages of all indexed conversations matching `filters`,
+/// citing the conversation and timestamp of each passage used. `embedder` finds the passages,
+/// `provider` writes the answer.
+pub async fn answer_across_conversations(
+    db: &DatabaseConnection,
+    embedder: &dyn LlmProvider,
+    provider: &dyn LlmProvider,
+    question: &str,
+    filters: &SearchFilters,
+    context_tokens: usize,
+    on_token: TokenSink<'_>,
+) -> Result<CrossMeetingAnswer, String> {
+    let filters = SearchFilters {
+        limit: Some(filters.limit.unwrap_or(ANSWER_PASSAGES)),
+        ..filters.clone()
+    };
+    let ranked = rank_passages(db, embedder, question, &filters).await?;
+    if ranked.is_empty() {
+        return Err("No indexed conversation matches the filters".to_string());
+    }
+
+    // Best passages first until the budget is spent, then read in the order they were said.
+    let budget = context_tokens / 2;
+    let mut used = 0;
+    let mut passages: Vec<RankedPassage> = Vec::new();
+    for passage in ranked {
+        let tokens = estimate_tokens(&passage.embedding.content);
+        if used + tokens > budget {
+            continue;
+        }
+        used += tokens;
+        passages.push(passage);
+    }
+    passages.sort_by(|a, b| {
+        (
+            &a.conversation.created_at,
+            a.conversation.id,
+            a.embedding.t0,
+        )
+            .cmp(&(
+                &b.conversation.created_at,
+                b.conversation.id,
+                b.embedding.t0,
+            ))
+    });
+
+    let excerpts: Vec<String> = passages
+        .iter()
+        .map(|passage| {
+            format!(
+                "{} {} ({})\n{}",
+                heading(passage),
+                passage.conversation.title,
+                passage
+                    .conversation
+                    .created_at
+                    .get(..10)
+                    .unwrap_or_default(),
+                passage.embedding.content
+            )
+        })
+        .collect();
+    let request = CompletionRequest {
+        system: Some(ANSWER_SYSTEM.to_string()),
+        ..CompletionRequest::new(format!(
+            "Passages:\n\n{}\n\nQuestion: {}",
+            excerpts.join("\n\n"),
+            question.trim()
+        ))
+    }
+    .kind(GenerationKind::Answer);
+
+    let answer = provider
+        .complete_streaming(&request, on_token)
+        .await
+        .map_err(|e| format!("Failed to answer: {}", e))?;
+
+    Ok(CrossMeetingAnswer {
+        citations: citations(&answer, &passages),
+        answer,
+    })
+}
+
+fn heading(passage: &RankedPassage) -> String {
+    match passage.embedding.t0 {
+        Some(t0) => format!("[#{} @ {}]", passage.conversation.id, format_timestamp(t0)),
+        None => format!("[#{}]", passage.conversation.id),
+    }
+}
+
+/// The cited conversations and timestamps, keeping only conversations the answer was given.
+fn citations(answer: &str, passages: &[RankedPassage]) -> Vec<Citation> {
+    let pattern = Regex::new(r"#(\d+)(?:\s*@\s*(\d+:\d{2}(?::\d{2})?))?").unwrap();
+
+    let mut citations: Vec<Citation> = Vec::new();
+    for capture in pattern.captures_iter(answer) {
+        let Some(conversation_id) = capture[1].parse::<i32>().ok() else {
+            continue;
+        };
+        let Some(passage) = passages
+            .iter()
+            .find(|passage| passage.conversation.id == conversation_id)
+        else {
+            continue;
+        };
+
+        let citation = Citation {
+            conversation_id,
+            conversation_title: passage.conversation.title.clone(),
+            t0: capture
+                .get(2)
+                .and_then(|timestamp| parse_timestamp(timestamp.as_str())),
+        };
+        if !citations.contains(&citation) {
+            citations.push(citation);
+        }
+    }
+
+    citations
+}
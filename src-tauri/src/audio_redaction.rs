@@ -0,0 +1,164 @@
+use std::f32::consts::PI;
+use std::fs::{read_to_string, rename};
+use std::path::Path;
+
+use entity::audio_redaction::{self, AudioRedactionMethod};
+use hound::{SampleFormat, WavReader, WavWriter};
+use log::info;
+
+use crate::redaction::RedactedSpan;
+
+/// Audio masked either side of a span, word timestamps are only roughly aligned with speech.
+const SPAN_PADDING_MS: i64 = 150;
+const TONE_FREQUENCY: f32 = 1000.0;
+/// Roughly -20 dBFS.
+const TONE_AMPLITUDE: f32 = 0.1;
+
+/// Masks redacted spans in the mixed recording and in both tracks, including the chunks they
+/// were concatenated from, and returns each span as masked for the caller to record.
+pub async fn redact_recording_audio(
+    recording_dir: &Path,
+    conversation_id: i32,
+    spans: Vec<RedactedSpan>,
+    method: Option<AudioRedactionMethod>,
+) -> Result<Vec<audio_redaction::Model>, String> {
+    let Some(method) = method else {
+        return Ok(Vec::new());
+    };
+    if spans.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let ranges: Vec<(i64, i64)> = spans
+        .iter()
+        .map(|span| {
+            (
+                (span.t0 - SPAN_PADDING_MS).max(0),
+                span.t1 + SPAN_PADDING_MS,
+            )
+        })
+        .collect();
+
+    let recording_dir = recording_dir.to_path_buf();
+    let file_ranges = ranges.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        redact_recording_files(&recording_dir, &file_ranges, method)
+    })
+    .await
+    .map_err(|e| e.to_string())??;
+    info!(
+        "Masked {} spans in the audio of conversation {}",
+        spans.len(),
+        conversation_id
+    );
+
+    Ok(spans
+        .into_iter()
+        .zip(ranges)
+        .map(|(span, (t0, t1))| audio_redaction::Model {
+            id: 0,
+            conversation_id,
+            t0,
+            t1,
+            method,
+            kind: span.kind,
+            placeholder: span.placeholder,
+            created_at: String::new(),
+        })
+        .collect())
+}
+
+fn redact_recording_files(
+    recording_dir: &Path,
+    ranges: &[(i64, i64)],
+    method: AudioRedactionMethod,
+) -> Result<(), String> {
+    let mixed = recording_dir.join("combined.wav");
+    if mixed.exists() {
+        redact_wav(&mixed, ranges, 0, method)?;
+    }
+
+    for track in ["input", "output"] {
+        let track_dir = recording_dir.join(track);
+        let combined = track_dir.join("combined.wav");
+        if combined.exists() {
+            redact_wav(&combined, ranges, 0, method)?;
+        }
+
+        // The chunks are laid end to end on the same timeline as the combined track.
+        let Ok(segment_list) = read_to_string(track_dir.join("segment_list.txt")) else {
+            continue;
+        };
+        let mut offset_ms = 0;
+        for chunk in segment_list
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty())
+        {
+            let chunk_path = track_dir.join(chunk);
+            if chunk_path.exists() {
+                offset_ms += redact_wav(&chunk_path, ranges, offset_ms, method)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Replaces the samples in `ranges`, given in milliseconds on the recording's timeline, of a
+/// 16-bit wav file starting `offset_ms` into the recording. Returns the file's duration.
+fn redact_wav(
+    path: &Path,
+    ranges: &[(i64, i64)],
+    offset_ms: i64,
+    method: AudioRedactionMethod,
+) -> Result<i64, String> {
+    let mut reader =
+        WavReader::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let spec = reader.spec();
+    let sample_rate = spec.sample_rate as i64;
+    let channels = spec.channels as usize;
+    let duration_ms = reader.duration() as i64 * 1000 / sample_rate;
+
+    let to_frame = |ms: i64| ((ms - offset_ms).clamp(0, duration_ms) * sample_rate / 1000) as usize;
+    let frame_ranges: Vec<(usize, usize)> = ranges
+        .iter()
+        .map(|(t0, t1)| (to_frame(*t0), to_frame(*t1)))
+        .filter(|(start, end)| start < end)
+        .collect();
+    if frame_ranges.is_empty() {
+        return Ok(duration_ms);
+    }
+
+    if spec.sample_format != SampleFormat::Int || spec.bits_per_sample != 16 {
+        return Err(format!("{} isn't 16-bit PCM", path.display()));
+    }
+
+    // Written next to the original and moved over it, so a crash can't truncate the recording.
+    // Samples are streamed through, long recordings are never fully in memory.
+    let redacted_path = path.with_extension("redacting.wav");
+    let mut writer = WavWriter::create(&redacted_path, spec)
+        .map_err(|e| format!("Failed to create {}: {}", redacted_path.display(), e))?;
+    for (index, sample) in reader.samples::<i16>().enumerate() {
+        let sample = sample.map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let frame = index / channels;
+        let masked = frame_ranges
+            .iter()
+            .any(|(start, end)| (*start..*end).contains(&frame));
+        let value = match method {
+            _ if !masked => sample,
+            AudioRedactionMethod::Silence => 0,
+            AudioRedactionMethod::Tone => {
+                let seconds = frame as f32 / spec.sample_rate as f32;
+                (TONE_AMPLITUDE * i16::MAX as f32 * (2.0 * PI * TONE_FREQUENCY * seconds).sin())
+                    as i16
+            }
+        };
+        writer.write_sample(value).map_err(|e| e.to_string())?;
+    }
+    writer.finalize().map_err(|e| e.to_string())?;
+    rename(&redacted_path, path)
+        .map_err(|e| format!("Failed to replace {}: {}", path.display(), e))?;
+
+    Ok(duration_ms)
+}
@@ -0,0 +1,143 @@
+use std::collections::{HashMap, HashSet};
+
+use entity::chat_message::ChatRole;
+use serde::Deserialize;
+
+use crate::glossary::estimate_tokens;
+use crate::llm::{CompletionRequest, GenerationKind};
+use crate::summarize::transcript_pieces;
+use crate::transcribe::TranscriptionJSON;
+
+/// Longest stretch of a speaker turn that's retrieved as one excerpt.
+const EXCERPT_TOKENS: usize = 200;
+/// Earlier messages sent along, so follow-up questions make sense.
+const HISTORY_MESSAGES: usize = 6;
+
+const CHAT_SYSTEM: &str = "You answer questions about a meeting from excerpts of its transcript. \
+Each excerpt starts with the [mm:ss] timestamp it was said at. Cite the timestamps of the excerpts \
+your answer relies on, like [12:34]. If the excerpts don't answer the question, say so instead of \
+guessing.";
+
+/// Too common to tell excerpts apart.
+const STOPWORDS: [&str; 24] = [
+    "the", "and", "for", "are", "was", "were", "what", "when", "where", "who", "why", "how", "did",
+    "does", "about", "that", "this", "with", "have", "has", "say", "said", "you", "they",
+];
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct ChatMessage {
+    pub role: ChatRole,
+    pub content: String,
+}
+
+/// The excerpts of a transcript most relevant to the latest questions, in transcript order and
+/// within `budget` tokens. A transcript that fits is returned whole.
+pub fn retrieve_excerpts(
+    transcription: &TranscriptionJSON,
+    messages: &[ChatMessage],
+    budget: usize,
+) -> Vec<String> {
+    let pieces: Vec<String> = transcript_pieces(transcription, EXCERPT_TOKENS)
+        .into_iter()
+        .filter(|piece| !piece.trim().is_empty())
+        .collect();
+    if estimate_tokens(&pieces.join("\n")) <= budget {
+        return pieces;
+    }
+
+    // The question before the latest one keeps follow-ups like "and when is it due?" on topic.
+    let query: Vec<&str> = messages
+        .iter()
+        .rev()
+        .filter(|message| message.role == ChatRole::User)
+        .take(2)
+        .map(|message| message.content.as_str())
+        .collect();
+    let query: HashSet<String> = terms(&query.join(" ")).into_iter().collect();
+
+    let counts: Vec<HashMap<String, usize>> = pieces
+        .iter()
+        .map(|piece| {
+            let mut counts = HashMap::new();
+            for term in terms(piece) {
+                *counts.entry(term).or_insert(0) += 1;
+            }
+            counts
+        })
+        .collect();
+    // Terms said all through the meeting count for less than ones said in a few places.
+    let weights: HashMap<&String, f64> = query
+        .iter()
+        .map(|term| {
+            let found_in = counts
+                .iter()
+                .filter(|counts| counts.contains_key(term))
+                .count();
+            (
+                term,
+                (1.0 + pieces.len() as f64 / found_in.max(1) as f64).ln(),
+            )
+        })
+        .collect();
+    let score = |counts: &HashMap<String, usize>| -> f64 {
+        weights
+            .iter()
+            .filter_map(|(term, weight)| {
+                let count = *counts.get(*term)? as f64;
+                Some(weight * count / (count + 1.0))
+            })
+            .sum()
+    };
+
+    let mut ranked: Vec<(usize, f64)> = counts
+        .iter()
+        .enumerate()
+        .map(|(i, counts)| (i, score(counts)))
+        .collect();
+    // Pieces that match nothing keep transcript order, so a general question gets the start.
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    let mut picked = Vec::new();
+    let mut used = 0;
+    for (i, _) in ranked {
+        let tokens = estimate_tokens(&pieces[i]);
+        if used + tokens > budget {
+            continue;
+        }
+        used += tokens;
+        picked.push(i);
+    }
+    picked.sort();
+
+    picked.into_iter().map(|i| pieces[i].clone()).collect()
+}
+
+/// Asks for the answer to the last message, with the earlier ones as the chat so far.
+pub fn chat_request(excerpts: &[String], messages: &[ChatMessage]) -> CompletionRequest {
+    let history: Vec<String> = messages
+        .iter()
+        .skip(messages.len().saturating_sub(HISTORY_MESSAGES + 1))
+        .map(|message| match message.role {
+            ChatRole::User => format!("User: {}", message.content.trim()),
+            ChatRole::Assistant => format!("Assistant: {}", message.content.trim()),
+        })
+        .collect();
+
+    CompletionRequest {
+        system: Some(CHAT_SYSTEM.to_string()),
+        ..CompletionRequest::new(format!(
+            "Transcript excerpts:\n{}\n\n{}\nAssistant:",
+            excerpts.join("\n"),
+            history.join("\n")
+        ))
+    }
+    .kind(GenerationKind::Chat)
+}
+
+/// Lowercased words worth matching on.
+fn terms(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .map(str::to_lowercase)
+        .filter(|term| term.chars().count() >= 3 && !STOPWORDS.contains(&term.as_str()))
+        .collect()
+}
@@ -0,0 +1,53 @@
+use entity::action_item::{self, ActionItemStatus};
+use service::{Mutation, Query};
+
+use crate::AppState;
+
+/// Action items across meetings, or of one conversation. Unset filters match everything.
+#[tauri::command]
+pub async fn get_action_items(
+    state: tauri::State<'_, AppState>,
+    conversation_id: Option<i32>,
+    status: Option<ActionItemStatus>,
+    assignee: Option<String>,
+) -> Result<Vec<action_item::Model>, String> {
+    Query::find_action_items(&state.db, conversation_id, status, assignee)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn complete_action_item(
+    state: tauri::State<'_, AppState>,
+    action_item_id: i32,
+) -> Result<action_item::Model, String> {
+    Mutation::set_action_item_status(&state.db, action_item_id, ActionItemStatus::Done)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn reopen_action_item(
+    state: tauri::State<'_, AppState>,
+    action_item_id: i32,
+) -> Result<action_item::Model, String> {
+    Mutation::set_action_item_status(&state.db, action_item_id, ActionItemStatus::Open)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Renames, assigns or reschedules an item.
+#[tauri::command]
+pub async fn update_action_item(
+    state: tauri::State<'_, AppState>,
+    action_item_id: i32,
+    form: action_item::Model,
+) -> Result<action_item::Model, String> {
+    if form.title.trim().is_empty() {
+        return Err("Action item title can't be empty".to_string());
+    }
+
+    Mutation::update_action_item_by_id(&state.db, action_item_id, form)
+        .await
+        .map_err(|e| e.to_string())
+}
@@ -0,0 +1,174 @@
+use std::fs::read_to_string;
+use std::sync::Arc;
+
+use entity::artifact;
+use service::{Mutation, Query};
+
+use crate::{
+    generation::emit_generation,
+    jobs::JobQueue,
+    llm::{CompletionRequest, GenerationKind},
+    prompts::{
+        check_variables, conversation_variables, render_custom_prompt, CONVERSATION_VARIABLES,
+    },
+    redaction::Redactor,
+    settings::Settings,
+    summarize::SummaryJSON,
+    transcript_edits::load_edited_transcription,
+    AppState,
+};
+
+#[tauri::command]
+pub async fn get_artifacts(
+    state: tauri::State<'_, AppState>,
+    conversation_id: i32,
+) -> Result<Vec<artifact::Model>, String> {
+    Query::find_artifacts(&state.db, conversation_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Runs a prompt against a conversation and saves the answer as an artifact named `name`,
+/// replacing the conversation's artifact of that name. The prompt is either `prompt` or the saved
+/// custom prompt `custom_prompt_id`, whose name is used when `name` isn't given. The answer
+/// streams as `generation` events of kind `artifact`.
+#[tauri::command]
+pub async fn run_prompt(
+    handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    job_queue: tauri::State<'_, Arc<JobQueue>>,
+    conversation_id: i32,
+    name: Option<String>,
+    prompt: Option<String>,
+    custom_prompt_id: Option<i32>,
+) -> Result<artifact::Model, String> {
+    let (name, prompt) = match (prompt, custom_prompt_id) {
+        (Some(prompt), None) => (name.unwrap_or_default(), prompt),
+        (None, Some(custom_prompt_id)) => {
+            let custom_prompt = Query::find_custom_prompt_by_id(&state.db, custom_prompt_id)
+                .await
+                .map_err(|e| e.to_string())?
+                .ok_or(format!("Custom prompt {} not found", custom_prompt_id))?;
+            (name.unwrap_or(custom_prompt.name), custom_prompt.prompt)
+        }
+        _ => return Err("Give either a prompt or a saved prompt to run".to_string()),
+    };
+
+    write_artifact(&handle, &state, &job_queue, conversation_id, name, prompt).await
+}
+
+/// Runs an artifact's prompt again, against the conversation as it is now.
+#[tauri::command]
+pub async fn rerun_artifact(
+    handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    job_queue: tauri::State<'_, Arc<JobQueue>>,
+    artifact_id: i32,
+) -> Result<artifact::Model, String> {
+    let artifact = Query::find_artifact_by_id(&state.db, artifact_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or(format!("Artifact {} not found", artifact_id))?;
+
+    write_artifact(
+        &handle,
+        &state,
+        &job_queue,
+        artifact.conversation_id,
+        artifact.name,
+        artifact.prompt,
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn delete_artifact(
+    state: tauri::State<'_, AppState>,
+    artifact_id: i32,
+) -> Result<u64, String> {
+    let result = Mutation::delete_artifact(&state.db, artifact_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(result.rows_affected)
+}
+
+async fn write_artifact(
+    handle: &tauri::AppHandle,
+    state: &AppState,
+    job_queue: &JobQueue,
+    conversation_id: i32,
+    name: String,
+    prompt: String,
+) -> Result<artifact::Model, String> {
+    if name.trim().is_empty() {
+        return Err("Artifact name can't be empty".to_string());
+    }
+    if prompt.trim().is_empty() {
+        return Err("Prompt can't be empty".to_string());
+    }
+    check_variables(&prompt, &CONVERSATION_VARIABLES)?;
+
+    let conversation = Query::find_conversation_by_id(&state.db, conversation_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or(format!("Conversation {} not found", conversation_id))?;
+    let recording_dir = job_queue.recording_dir(conversation_id);
+    let transcription = load_edited_transcription(
+        &state.db,
+        recording_dir.join("transcription.json"),
+        conversation_id,
+    )
+    .await?;
+    // Not every conversation has been summarized yet.
+    let summary: Option<SummaryJSON> = read_to_string(recording_dir.join("summary.json"))
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok());
+    let llm = Settings::load(&state.db).await?.llm;
+
+    let variables = conversation_variables(&conversation, &transcription, summary.as_ref());
+    let text = render_custom_prompt(&prompt, &variables, llm.context_tokens / 2)?;
+    // The model sees what the summaries see, with sensitive values masked.
+    let mut redactor = Redactor::load(&state.db, job_queue.data_dir(), conversation_id).await?;
+    let request = CompletionRequest::new(redactor.redact(&text)).kind(GenerationKind::Artifact);
+
+    let provider = state.llm_providers.get(&llm.backend, llm.context_tokens);
+    let content = provider
+        .complete_streaming(&request, &|token| {
+            emit_generation(
+                handle,
+                Some(conversation_id),
+                GenerationKind::Artifact,
+                token,
+                false,
+            )
+        })
+        .await
+        .map_err(|e| format!("Failed to run prompt: {}", e))?;
+    emit_generation(
+        handle,
+        Some(conversation_id),
+        GenerationKind::Artifact,
+        "",
+        true,
+    );
+
+    let content = redactor.redact(content.trim());
+    redactor.save().await?;
+
+    Mutation::save_artifact(
+        &state.db,
+        artifact::Model {
+            id: 0,
+            conversation_id,
+            name: name.trim().to_string(),
+            prompt,
+            content,
+            model: provider.name(),
+            created_at: String::new(),
+            updated_at: String::new(),
+        },
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
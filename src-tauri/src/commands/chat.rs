@@ -0,0 +1,126 @@
+use std::sync::Arc;
+
+use entity::chat_message::{self, ChatRole};
+use service::{Mutation, Query};
+
+use crate::{
+    chat::{chat_request, retrieve_excerpts, ChatMessage},
+    generation::emit_generation,
+    jobs::JobQueue,
+    llm::GenerationKind,
+    redaction::Redactor,
+    settings::Settings,
+    transcript_edits::load_edited_transcription,
+    AppState,
+};
+
+/// Answers the last of `messages` from the conversation's transcript, citing timestamps. The
+/// answer streams as `generation` events of kind `chat`; the question and the answer are saved
+/// to the conversation's chat.
+#[tauri::command]
+pub async fn chat_with_conversation(
+    handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    job_queue: tauri::State<'_, Arc<JobQueue>>,
+    conversation_id: i32,
+    messages: Vec<ChatMessage>,
+) -> Result<chat_message::Model, String> {
+    match messages.last() {
+        Some(message) if message.role == ChatRole::User && !message.content.trim().is_empty() => {}
+        _ => return Err("The last message has to be a question".to_string()),
+    }
+
+    let transcription_path = job_queue
+        .recording_dir(conversation_id)
+        .join("transcription.json");
+    let transcription =
+        load_edited_transcription(&state.db, transcription_path, conversation_id).await?;
+    let llm = Settings::load(&state.db).await?.llm;
+
+    // The model sees what the summaries see, with sensitive values masked.
+    let mut redactor = Redactor::load(&state.db, job_queue.data_dir(), conversation_id).await?;
+    let messages: Vec<ChatMessage> = messages
+        .iter()
+        .map(|message| ChatMessage {
+            role: message.role,
+            content: redactor.redact(&message.content),
+        })
+        .collect();
+    let excerpts: Vec<String> =
+        retrieve_excerpts(&transcription, &messages, llm.context_tokens / 2)
+            .iter()
+            .map(|excerpt| redactor.redact(excerpt))
+            .collect();
+
+    let question = messages.last().map(|message| message.content.clone());
+    Mutation::create_chat_message(
+        &state.db,
+        chat_message::Model {
+            id: 0,
+            conversation_id,
+            role: ChatRole::User,
+            content: question.unwrap_or_default(),
+            created_at: String::new(),
+        },
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let provider = state.llm_providers.get(&llm.backend, llm.context_tokens);
+    let answer = provider
+        .complete_streaming(&chat_request(&excerpts, &messages), &|token| {
+            emit_generation(
+                &handle,
+                Some(conversation_id),
+                GenerationKind::Chat,
+                token,
+                false,
+            )
+        })
+        .await
+        .map_err(|e| format!("Failed to answer: {}", e))?;
+    emit_generation(
+        &handle,
+        Some(conversation_id),
+        GenerationKind::Chat,
+        "",
+        true,
+    );
+
+    let answer = redactor.redact(answer.trim());
+    redactor.save().await?;
+
+    Mutation::create_chat_message(
+        &state.db,
+        chat_message::Model {
+            id: 0,
+            conversation_id,
+            role: ChatRole::Assistant,
+            content: answer,
+            created_at: String::new(),
+        },
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_chat_history(
+    state: tauri::State<'_, AppState>,
+    conversation_id: i32,
+) -> Result<Vec<chat_message::Model>, String> {
+    Query::find_chat_messages(&state.db, conversation_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn clear_chat_history(
+    state: tauri::State<'_, AppState>,
+    conversation_id: i32,
+) -> Result<(), String> {
+    Mutation::delete_chat_messages(&state.db, conversation_id)
+        .await
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
@@ -5,7 +5,14 @@ use entity::conversation::{self, Model as ConversationModel};
 use log::info;
 use service::{sea_orm::TryIntoModel, Mutation, Query};
 
-use crate::{recorder::RecordingState, summarize::SummaryJSON, AppState};
+use crate::{
+    generation::{PartialAnswer, PartialGeneration, StreamingProvider, PARTIAL_GENERATION_FILE},
+    jobs::JobQueue,
+    recorder::RecordingState,
+    settings::Settings,
+    summarize::SummaryJSON,
+    AppState,
+};
 
 #[tauri::command]
 pub async fn get_conversation(
@@ -77,6 +84,111 @@ pub async fn delete_conversation(
     Ok(result.rows_affected)
 }
 
+/// Renames a conversation. Generated titles won't replace the new one.
+#[tauri::command]
+pub async fn rename_conversation(
+    state: tauri::State<'_, AppState>,
+    conversation_id: i32,
+    title: String,
+) -> Result<conversation::Model, String> {
+    let title = title.trim().to_string();
+    if title.is_empty() {
+        return Err("Title can't be empty".to_string());
+    }
+
+    let conversation = Query::find_conversation_by_id(&state.db, conversation_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or(format!("Conversation {} not found", conversation_id))?;
+
+    Mutation::update_conversation_by_id(
+        &state.db,
+        conversation_id,
+        conversation::Model {
+            title,
+            title_edited: true,
+            ..conversation
+        },
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Files a conversation under `tags`, which cross-meeting search and questions can filter by.
+#[tauri::command]
+pub async fn set_conversation_tags(
+    state: tauri::State<'_, AppState>,
+    conversation_id: i32,
+    tags: Vec<String>,
+) -> Result<conversation::Model, String> {
+    let mut unique: Vec<String> = Vec::new();
+    for tag in tags.iter().map(|tag| tag.trim()) {
+        if tag.contains(',') {
+            return Err(format!("Tag {} can't contain a comma", tag));
+        }
+        if !tag.is_empty() && !unique.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            unique.push(tag.to_string());
+        }
+    }
+
+    let tags = (!unique.is_empty()).then(|| unique.join(", "));
+    Mutation::set_conversation_tags(&state.db, conversation_id, tags)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Titles a conversation from its current summary, replacing a title the user wrote.
+#[tauri::command]
+pub async fn regenerate_title(
+    handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    job_queue: tauri::State<'_, Arc<JobQueue>>,
+    conversation_id: i32,
+) -> Result<conversation::Model, String> {
+    let recording_dir = job_queue.recording_dir(conversation_id);
+    let path = recording_dir.join("summary.json");
+    let content = read_to_string(&path)
+        .map_err(|err| format!("Failed to read file {}: {}", path.display(), err))?;
+    let summary: SummaryJSON = serde_json::from_str(&content)
+        .map_err(|err| format!("Failed to parse JSON in file {}: {}", path.display(), err))?;
+
+    let llm = Settings::load(&state.db).await?.llm;
+    let provider = state.llm_providers.get(&llm.backend, llm.context_tokens);
+    let streaming = StreamingProvider::load(
+        provider.as_ref(),
+        handle,
+        conversation_id,
+        recording_dir.join(PARTIAL_GENERATION_FILE),
+    );
+
+    let conversation = job_queue
+        .retitle(conversation_id, &summary, &streaming, true)
+        .await?;
+    streaming.finish();
+
+    Ok(conversation)
+}
+
+/// What the summary stage got through before it was interrupted, the unfinished answer last.
+/// Empty when nothing is being or was left generating.
+#[tauri::command]
+pub async fn get_partial_generation(
+    job_queue: tauri::State<'_, Arc<JobQueue>>,
+    conversation_id: i32,
+    version_id: Option<i32>,
+) -> Result<Vec<PartialAnswer>, String> {
+    let output_dir = match version_id {
+        Some(version_id) => job_queue.version_dir(conversation_id, version_id),
+        None => job_queue.recording_dir(conversation_id),
+    };
+
+    Ok(
+        PartialGeneration::load(&output_dir.join(PARTIAL_GENERATION_FILE))
+            .map(|partial| partial.answers())
+            .unwrap_or_default(),
+    )
+}
+
 #[tauri::command]
 pub async fn get_summary_for_converstation(
     state: tauri::State<'_, Arc<tauri::async_runtime::Mutex<RecordingState>>>,
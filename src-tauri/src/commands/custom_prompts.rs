@@ -0,0 +1,66 @@
+use entity::custom_prompt;
+use service::{sea_orm::TryIntoModel, Mutation, Query};
+
+use crate::{
+    prompts::{check_variables, CONVERSATION_VARIABLES},
+    AppState,
+};
+
+#[tauri::command]
+pub async fn get_custom_prompts(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<custom_prompt::Model>, String> {
+    Query::find_custom_prompts(&state.db)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn create_custom_prompt(
+    state: tauri::State<'_, AppState>,
+    form: custom_prompt::Model,
+) -> Result<custom_prompt::Model, String> {
+    validate(&form)?;
+
+    Mutation::create_custom_prompt(&state.db, form)
+        .await
+        .map_err(|e| e.to_string())?
+        .try_into_model()
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn update_custom_prompt(
+    state: tauri::State<'_, AppState>,
+    custom_prompt_id: i32,
+    form: custom_prompt::Model,
+) -> Result<custom_prompt::Model, String> {
+    validate(&form)?;
+
+    Mutation::update_custom_prompt_by_id(&state.db, custom_prompt_id, form)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_custom_prompt(
+    state: tauri::State<'_, AppState>,
+    custom_prompt_id: i32,
+) -> Result<u64, String> {
+    let result = Mutation::delete_custom_prompt(&state.db, custom_prompt_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(result.rows_affected)
+}
+
+fn validate(form: &custom_prompt::Model) -> Result<(), String> {
+    if form.name.trim().is_empty() {
+        return Err("Prompt name can't be empty".to_string());
+    }
+    if form.prompt.trim().is_empty() {
+        return Err("Prompt can't be empty".to_string());
+    }
+
+    check_variables(&form.prompt, &CONVERSATION_VARIABLES)
+}
@@ -0,0 +1,56 @@
+use entity::glossary_term;
+use service::{sea_orm::TryIntoModel, Mutation, Query};
+
+use crate::AppState;
+
+#[tauri::command]
+pub async fn get_glossary_terms(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<glossary_term::Model>, String> {
+    Query::find_glossary_terms(&state.db)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn create_glossary_term(
+    state: tauri::State<'_, AppState>,
+    form: glossary_term::Model,
+) -> Result<glossary_term::Model, String> {
+    if form.term.trim().is_empty() {
+        return Err("Glossary term can't be empty".to_string());
+    }
+
+    Mutation::create_glossary_term(&state.db, form)
+        .await
+        .map_err(|e| e.to_string())?
+        .try_into_model()
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn update_glossary_term(
+    state: tauri::State<'_, AppState>,
+    glossary_term_id: i32,
+    form: glossary_term::Model,
+) -> Result<glossary_term::Model, String> {
+    if form.term.trim().is_empty() {
+        return Err("Glossary term can't be empty".to_string());
+    }
+
+    Mutation::update_glossary_term_by_id(&state.db, glossary_term_id, form)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_glossary_term(
+    state: tauri::State<'_, AppState>,
+    glossary_term_id: i32,
+) -> Result<u64, String> {
+    let result = Mutation::delete_glossary_term(&state.db, glossary_term_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(result.rows_affected)
+}
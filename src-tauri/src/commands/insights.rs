@@ -0,0 +1,26 @@
+use entity::insight::{self, InsightKind};
+use service::Query;
+
+use crate::AppState;
+
+/// Decisions, open questions, risks and topics across meetings, or of one conversation. Dates
+/// bound when the conversations were created, e.g. `created_after: "2024-06-17"` for this week's
+/// meetings. Unset filters match everything.
+#[tauri::command]
+pub async fn get_insights(
+    state: tauri::State<'_, AppState>,
+    conversation_id: Option<i32>,
+    kind: Option<InsightKind>,
+    created_after: Option<String>,
+    created_before: Option<String>,
+) -> Result<Vec<insight::Model>, String> {
+    Query::find_insights(
+        &state.db,
+        conversation_id,
+        kind,
+        created_after,
+        created_before,
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
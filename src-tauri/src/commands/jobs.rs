@@ -0,0 +1,33 @@
+use std::sync::Arc;
+
+use entity::job::{self, JobStage};
+use service::Query;
+
+use crate::{jobs::JobQueue, AppState};
+
+#[tauri::command]
+pub async fn get_jobs(
+    state: tauri::State<'_, AppState>,
+    conversation_id: Option<i32>,
+) -> Result<Vec<job::Model>, String> {
+    Query::find_jobs(&state.db, conversation_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn cancel_job(
+    job_queue: tauri::State<'_, Arc<JobQueue>>,
+    job_id: i32,
+) -> Result<job::Model, String> {
+    job_queue.cancel(job_id).await
+}
+
+#[tauri::command]
+pub async fn retry_job(
+    job_queue: tauri::State<'_, Arc<JobQueue>>,
+    job_id: i32,
+    from_stage: Option<JobStage>,
+) -> Result<job::Model, String> {
+    job_queue.retry(job_id, from_stage).await
+}
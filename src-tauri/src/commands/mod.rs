@@ -1,4 +1,18 @@
+pub mod action_items;
+pub mod artifacts;
+pub mod chat;
 pub mod conversation;
+pub mod custom_prompts;
 pub mod devices;
+pub mod glossary;
+pub mod insights;
+pub mod jobs;
+pub mod prompts;
 pub mod recording;
+pub mod redaction;
+pub mod search;
+pub mod settings;
+pub mod summary_template;
+pub mod transcript;
+pub mod versions;
 pub mod window;
@@ -0,0 +1,120 @@
+use entity::prompt;
+use service::{Mutation, Query};
+
+use crate::{
+    prompts::{default_prompt, validate_pack, validate_prompt, PackedPrompt, PromptPack},
+    AppState,
+};
+
+/// The latest version of each prompt the pipeline is built from.
+#[tauri::command]
+pub async fn get_prompts(state: tauri::State<'_, AppState>) -> Result<Vec<prompt::Model>, String> {
+    Query::find_latest_prompts(&state.db)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Every version of a prompt, newest first.
+#[tauri::command]
+pub async fn get_prompt_versions(
+    state: tauri::State<'_, AppState>,
+    key: String,
+) -> Result<Vec<prompt::Model>, String> {
+    Query::find_prompt_versions(&state.db, &key)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Saves an edited prompt as its next version, used from the next run on.
+#[tauri::command]
+pub async fn update_prompt(
+    state: tauri::State<'_, AppState>,
+    form: prompt::Model,
+) -> Result<prompt::Model, String> {
+    save_version(&state, form.key, form.template, form.model).await
+}
+
+/// Puts a prompt back the way it ships, as a new version so earlier edits stay in its history.
+#[tauri::command]
+pub async fn reset_prompt(
+    state: tauri::State<'_, AppState>,
+    key: String,
+) -> Result<prompt::Model, String> {
+    let default = default_prompt(&key)?;
+
+    save_version(&state, key, default.template.to_string(), None).await
+}
+
+/// The latest prompts as a JSON prompt pack.
+#[tauri::command]
+pub async fn export_prompt_pack(state: tauri::State<'_, AppState>) -> Result<String, String> {
+    let prompts = Query::find_latest_prompts(&state.db)
+        .await
+        .map_err(|e| e.to_string())?;
+    let pack = PromptPack {
+        prompts: prompts
+            .into_iter()
+            .map(|prompt| PackedPrompt {
+                key: prompt.key,
+                template: prompt.template,
+                model: prompt.model,
+            })
+            .collect(),
+    };
+
+    serde_json::to_string_pretty(&pack).map_err(|e| e.to_string())
+}
+
+/// Saves the prompts of a JSON prompt pack as new versions, skipping ones that are unchanged.
+/// Nothing is saved unless every prompt in the pack is valid.
+#[tauri::command]
+pub async fn import_prompt_pack(
+    state: tauri::State<'_, AppState>,
+    pack: String,
+) -> Result<Vec<prompt::Model>, String> {
+    let pack: PromptPack =
+        serde_json::from_str(&pack).map_err(|e| format!("Invalid prompt pack: {}", e))?;
+    validate_pack(&pack)?;
+
+    let mut imported = Vec::new();
+    for prompt in pack.prompts {
+        let latest = Query::find_latest_prompt(&state.db, &prompt.key)
+            .await
+            .map_err(|e| e.to_string())?;
+        let unchanged = latest.is_some_and(|latest| {
+            latest.template == prompt.template && latest.model == prompt.model
+        });
+        if !unchanged {
+            imported.push(save_version(&state, prompt.key, prompt.template, prompt.model).await?);
+        }
+    }
+
+    Ok(imported)
+}
+
+async fn save_version(
+    state: &AppState,
+    key: String,
+    template: String,
+    model: Option<String>,
+) -> Result<prompt::Model, String> {
+    let model = model
+        .map(|model| model.trim().to_string())
+        .filter(|model| !model.is_empty());
+    let default = validate_prompt(&key, &template, model.as_deref())?;
+
+    Mutation::create_prompt_version(
+        &state.db,
+        prompt::Model {
+            id: 0,
+            key,
+            version: 0,
+            template,
+            variables: default.variables.join(", "),
+            model,
+            created_at: String::new(),
+        },
+    )
+    .await
+    .map_err(|e| e.to_string())
+}
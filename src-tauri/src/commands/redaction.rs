@@ -0,0 +1,158 @@
+use std::fs::read_dir;
+use std::sync::Arc;
+
+use entity::{audio_redaction, job::JobStage, redaction::RedactionKind};
+use service::{Mutation, Query};
+
+use crate::{
+    audio_redaction::redact_recording_audio,
+    jobs::JobQueue,
+    redaction::{reveal_redactions, RedactedSpan, Redactor, RevealedRedaction},
+    transcribe::{
+        full_text_from_segments, load_transcription, write_transcription, TranscriptionJSON,
+    },
+    transcript_edits::load_edited_transcription,
+    AppState,
+};
+
+/// Lists what was masked in a conversation, with the originals of reversible redactions.
+#[tauri::command]
+pub async fn get_redactions(
+    state: tauri::State<'_, AppState>,
+    job_queue: tauri::State<'_, Arc<JobQueue>>,
+    conversation_id: i32,
+) -> Result<Vec<RevealedRedaction>, String> {
+    reveal_redactions(&state.db, job_queue.data_dir(), conversation_id).await
+}
+
+#[tauri::command]
+pub async fn get_audio_redactions(
+    state: tauri::State<'_, AppState>,
+    conversation_id: i32,
+) -> Result<Vec<audio_redaction::Model>, String> {
+    Query::find_audio_redactions(&state.db, conversation_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Redacts text the user selected, by character offsets into a segment of the edited transcript.
+/// The text is masked in that stretch of every version's transcript and in typed edits, and the
+/// audio it was said in is masked too.
+#[tauri::command]
+pub async fn redact_transcript_span(
+    state: tauri::State<'_, AppState>,
+    job_queue: tauri::State<'_, Arc<JobQueue>>,
+    conversation_id: i32,
+    segment: usize,
+    start: usize,
+    end: usize,
+) -> Result<TranscriptionJSON, String> {
+    let recording_dir = job_queue.recording_dir(conversation_id);
+    let transcription_path = recording_dir.join("transcription.json");
+    let edited =
+        load_edited_transcription(&state.db, transcription_path.clone(), conversation_id).await?;
+    let target = edited
+        .segments
+        .get(segment)
+        .ok_or(format!("No segment {}", segment))?;
+    let selected: String = target
+        .text
+        .chars()
+        .skip(start)
+        .take(end.saturating_sub(start))
+        .collect();
+    let literal = selected.trim();
+    if literal.is_empty() {
+        return Err("Nothing selected to redact".to_string());
+    }
+
+    let mut redactor = Redactor::load(&state.db, job_queue.data_dir(), conversation_id).await?;
+    let placeholder = redactor.manual_placeholder(literal);
+
+    let mut paths = vec![transcription_path.clone()];
+    if let Ok(entries) = read_dir(recording_dir.join("versions")) {
+        paths.extend(
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path().join("transcription.json"))
+                .filter(|path| path.exists()),
+        );
+    }
+
+    let mut spans = Vec::new();
+    let mut transcriptions = Vec::new();
+    for path in paths {
+        let mut transcription = load_transcription(path.clone()).await?;
+        for raw in transcription
+            .segments
+            .iter_mut()
+            .filter(|raw| raw.t0 < target.t1 && target.t0 < raw.t1)
+        {
+            redactor.redact_literal(raw, literal);
+        }
+        transcription.full_text = full_text_from_segments(&transcription.segments);
+
+        // Versions share the recording, the current transcript's timing is enough.
+        let path_spans = redactor.take_spans();
+        if path == transcription_path {
+            spans = path_spans;
+        }
+        transcriptions.push((path, transcription));
+    }
+
+    // Typed in by the user, so there's no timing beyond the position in the segment.
+    if spans.is_empty() {
+        let length = target.text.chars().count().max(1) as i64;
+        let duration = target.t1 - target.t0;
+        spans.push(RedactedSpan {
+            t0: target.t0 + duration * start as i64 / length,
+            t1: target.t0 + duration * end as i64 / length,
+            kind: RedactionKind::Manual,
+            placeholder: placeholder.clone(),
+        });
+    }
+
+    redactor.save().await?;
+    for (path, transcription) in &transcriptions {
+        write_transcription(path, transcription)?;
+    }
+
+    let edits = Query::find_transcript_edits(&state.db, conversation_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    for edit in edits {
+        let Some(text) = edit.text.filter(|text| text.contains(literal)) else {
+            continue;
+        };
+        if edit.segment_t0 < target.t1 && target.t0 < edit.segment_t1 {
+            Mutation::update_transcript_edit_text(
+                &state.db,
+                edit.id,
+                text.replace(literal, &placeholder),
+            )
+            .await
+            .map_err(|e| e.to_string())?;
+        }
+    }
+
+    let audio_redactions = redact_recording_audio(
+        &recording_dir,
+        conversation_id,
+        spans,
+        redactor.audio_method(),
+    )
+    .await?;
+    Mutation::create_audio_redactions(&state.db, audio_redactions)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    // The search index holds passages as they were, drop them until they're embedded again.
+    Mutation::replace_embeddings(&state.db, conversation_id, Vec::new())
+        .await
+        .map_err(|e| e.to_string())?;
+    job_queue
+        .enqueue(conversation_id, &[JobStage::Index])
+        .await?;
+
+    load_edited_transcription(&state.db, transcription_path, conversation_id).await
+}
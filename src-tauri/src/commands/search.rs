@@ -0,0 +1,96 @@
+use std::sync::Arc;
+
+use entity::job::JobStage;
+use service::Query;
+
+use crate::{
+    assistant::{answer_across_conversations, CrossMeetingAnswer},
+    generation::emit_generation,
+    jobs::JobQueue,
+    llm::GenerationKind,
+    search::{semantic_search as search, SearchFilters, SearchResults},
+    settings::Settings,
+    AppState,
+};
+
+/// Conversations and passages closest in meaning to `query`, across all indexed meetings.
+#[tauri::command]
+pub async fn semantic_search(
+    state: tauri::State<'_, AppState>,
+    query: String,
+    filters: Option<SearchFilters>,
+) -> Result<SearchResults, String> {
+    if query.trim().is_empty() {
+        return Err("Search query can't be empty".to_string());
+    }
+
+    let llm = Settings::load(&state.db).await?.llm;
+    let provider = state
+        .llm_providers
+        .get(&llm.embedding_backend()?, llm.context_tokens);
+
+    search(
+        &state.db,
+        provider.as_ref(),
+        &query,
+        &filters.unwrap_or_default(),
+    )
+    .await
+}
+
+/// Answers a question from everything said in the meetings matching `filters`, citing the
+/// conversations and timestamps it drew on. The answer streams as `generation` events of kind
+/// `answer`.
+#[tauri::command]
+pub async fn ask_across_conversations(
+    handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>,
+    question: String,
+    filters: Option<SearchFilters>,
+) -> Result<CrossMeetingAnswer, String> {
+    if question.trim().is_empty() {
+        return Err("Question can't be empty".to_string());
+    }
+
+    let llm = Settings::load(&state.db).await?.llm;
+    let embedder = state
+        .llm_providers
+        .get(&llm.embedding_backend()?, llm.context_tokens);
+    let provider = state.llm_providers.get(&llm.backend, llm.context_tokens);
+
+    let answer = answer_across_conversations(
+        &state.db,
+        embedder.as_ref(),
+        provider.as_ref(),
+        &question,
+        &filters.unwrap_or_default(),
+        llm.context_tokens,
+        &|token| emit_generation(&handle, None, GenerationKind::Answer, token, false),
+    )
+    .await?;
+    emit_generation(&handle, None, GenerationKind::Answer, "", true);
+
+    Ok(answer)
+}
+
+/// Queues every conversation for indexing, e.g. after the embedding model was changed.
+/// Returns how many were queued.
+#[tauri::command]
+pub async fn rebuild_search_index(
+    state: tauri::State<'_, AppState>,
+    job_queue: tauri::State<'_, Arc<JobQueue>>,
+) -> Result<usize, String> {
+    // Index jobs skip backends that can't embed, say so rather than queue them for nothing.
+    Settings::load(&state.db).await?.llm.embedding_backend()?;
+
+    let conversations = Query::find_conversations(&state.db)
+        .await
+        .map_err(|e| e.to_string())?;
+    for conversation in &conversations {
+        job_queue
+            .enqueue(conversation.id, &[JobStage::Index])
+            .await?;
+    }
+
+    Ok(conversations.len())
+}
@@ -0,0 +1,16 @@
+use crate::{settings::Settings, AppState};
+
+#[tauri::command]
+pub async fn get_settings(state: tauri::State<'_, AppState>) -> Result<Settings, String> {
+    Settings::load(&state.db).await
+}
+
+#[tauri::command]
+pub async fn update_settings(
+    state: tauri::State<'_, AppState>,
+    settings: Settings,
+) -> Result<Settings, String> {
+    settings.save(&state.db).await?;
+
+    Ok(settings)
+}
@@ -0,0 +1,99 @@
+use entity::{conversation, summary_template};
+use service::{sea_orm::TryIntoModel, Mutation, Query};
+
+use crate::summary_template::{parse_sections, schema_for_sections};
+use crate::AppState;
+
+#[tauri::command]
+pub async fn get_summary_templates(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<summary_template::Model>, String> {
+    Query::find_summary_templates(&state.db)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn create_summary_template(
+    state: tauri::State<'_, AppState>,
+    form: summary_template::Model,
+) -> Result<summary_template::Model, String> {
+    let form = validate_template(form)?;
+
+    Mutation::create_summary_template(&state.db, form)
+        .await
+        .map_err(|e| e.to_string())?
+        .try_into_model()
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn update_summary_template(
+    state: tauri::State<'_, AppState>,
+    summary_template_id: i32,
+    form: summary_template::Model,
+) -> Result<summary_template::Model, String> {
+    let form = validate_template(form)?;
+
+    Mutation::update_summary_template_by_id(&state.db, summary_template_id, form)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_summary_template(
+    state: tauri::State<'_, AppState>,
+    summary_template_id: i32,
+) -> Result<u64, String> {
+    let result = Mutation::delete_summary_template(&state.db, summary_template_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(result.rows_affected)
+}
+
+/// Picks the template the conversation's next summary is written with, `None` to go back to the
+/// auto-selection rules.
+#[tauri::command]
+pub async fn set_conversation_summary_template(
+    state: tauri::State<'_, AppState>,
+    conversation_id: i32,
+    summary_template_id: Option<i32>,
+) -> Result<conversation::Model, String> {
+    if let Some(summary_template_id) = summary_template_id {
+        Query::find_summary_template_by_id(&state.db, summary_template_id)
+            .await
+            .map_err(|e| e.to_string())?
+            .ok_or(format!(
+                "Summary template {} not found",
+                summary_template_id
+            ))?;
+    }
+
+    Mutation::set_conversation_summary_template(&state.db, conversation_id, summary_template_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Checks the sections and schema, deriving the schema from the sections when it's left empty.
+fn validate_template(mut form: summary_template::Model) -> Result<summary_template::Model, String> {
+    if form.name.trim().is_empty() {
+        return Err("Summary template name can't be empty".to_string());
+    }
+    if form.prompt.trim().is_empty() {
+        return Err("Summary template prompt can't be empty".to_string());
+    }
+
+    let sections = parse_sections(&form.sections)?;
+    if form.json_schema.trim().is_empty() {
+        form.json_schema = schema_for_sections(&sections).to_string();
+    } else {
+        let schema: serde_json::Value = serde_json::from_str(&form.json_schema)
+            .map_err(|e| format!("Invalid JSON schema: {}", e))?;
+        if !schema.is_object() {
+            return Err("The JSON schema must be an object".to_string());
+        }
+    }
+
+    Ok(form)
+}
@@ -0,0 +1,203 @@
+use std::sync::Arc;
+
+use entity::job::JobStage;
+use entity::transcript_edit::{self, TranscriptEditKind};
+use service::{Mutation, Query};
+
+use crate::{
+    jobs::JobQueue,
+    redaction::Redactor,
+    transcribe::{full_text_from_segments, TranscriptionJSON},
+    transcript_edits::{apply_edit, load_edited_transcription},
+    AppState,
+};
+
+fn new_edit(conversation_id: i32, kind: TranscriptEditKind) -> transcript_edit::Model {
+    transcript_edit::Model {
+        id: 0,
+        conversation_id,
+        kind,
+        segment_t0: 0,
+        segment_t1: 0,
+        text: None,
+        speaker: None,
+        split_offset: None,
+        created_at: String::new(),
+    }
+}
+
+/// Anchors an edit to a segment of the transcript as the user sees it, checks it applies and
+/// stores it.
+async fn record_edit(
+    state: &AppState,
+    job_queue: &JobQueue,
+    segment: usize,
+    edit: transcript_edit::Model,
+) -> Result<TranscriptionJSON, String> {
+    let conversation_id = edit.conversation_id;
+    let transcription_path = job_queue
+        .recording_dir(conversation_id)
+        .join("transcription.json");
+    let mut transcription =
+        load_edited_transcription(&state.db, transcription_path, conversation_id).await?;
+
+    let target = transcription
+        .segments
+        .get(segment)
+        .ok_or(format!("No segment {}", segment))?;
+    // Typed text is masked the same way as the machine transcript.
+    let mut redactor = Redactor::load(&state.db, job_queue.data_dir(), conversation_id).await?;
+    let edit = transcript_edit::Model {
+        segment_t0: target.t0,
+        segment_t1: target.t1,
+        text: edit.text.as_deref().map(|text| redactor.redact(text)),
+        ..edit
+    };
+
+    apply_edit(&mut transcription.segments, &edit)?;
+    transcription.full_text = full_text_from_segments(&transcription.segments);
+
+    redactor.save().await?;
+    Mutation::create_transcript_edit(&state.db, edit)
+        .await
+        .map_err(|e| e.to_string())?;
+    // Search passages and participants follow the edited transcript.
+    job_queue
+        .enqueue(conversation_id, &[JobStage::Index])
+        .await?;
+
+    Ok(transcription)
+}
+
+#[tauri::command]
+pub async fn get_transcript_edits(
+    state: tauri::State<'_, AppState>,
+    conversation_id: i32,
+) -> Result<Vec<transcript_edit::Model>, String> {
+    Query::find_transcript_edits(&state.db, conversation_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn edit_transcript_segment_text(
+    state: tauri::State<'_, AppState>,
+    job_queue: tauri::State<'_, Arc<JobQueue>>,
+    conversation_id: i32,
+    segment: usize,
+    text: String,
+) -> Result<TranscriptionJSON, String> {
+    record_edit(
+        &state,
+        &job_queue,
+        segment,
+        transcript_edit::Model {
+            text: Some(text),
+            ..new_edit(conversation_id, TranscriptEditKind::EditText)
+        },
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn set_transcript_segment_speaker(
+    state: tauri::State<'_, AppState>,
+    job_queue: tauri::State<'_, Arc<JobQueue>>,
+    conversation_id: i32,
+    segment: usize,
+    speaker: Option<String>,
+) -> Result<TranscriptionJSON, String> {
+    record_edit(
+        &state,
+        &job_queue,
+        segment,
+        transcript_edit::Model {
+            speaker,
+            ..new_edit(conversation_id, TranscriptEditKind::SetSpeaker)
+        },
+    )
+    .await
+}
+
+/// Splits a segment in two at a character offset into its text.
+#[tauri::command]
+pub async fn split_transcript_segment(
+    state: tauri::State<'_, AppState>,
+    job_queue: tauri::State<'_, Arc<JobQueue>>,
+    conversation_id: i32,
+    segment: usize,
+    offset: i32,
+) -> Result<TranscriptionJSON, String> {
+    record_edit(
+        &state,
+        &job_queue,
+        segment,
+        transcript_edit::Model {
+            split_offset: Some(offset),
+            ..new_edit(conversation_id, TranscriptEditKind::Split)
+        },
+    )
+    .await
+}
+
+/// Merges a segment with the one after it.
+#[tauri::command]
+pub async fn merge_transcript_segments(
+    state: tauri::State<'_, AppState>,
+    job_queue: tauri::State<'_, Arc<JobQueue>>,
+    conversation_id: i32,
+    segment: usize,
+) -> Result<TranscriptionJSON, String> {
+    record_edit(
+        &state,
+        &job_queue,
+        segment,
+        new_edit(conversation_id, TranscriptEditKind::Merge),
+    )
+    .await
+}
+
+#[tauri::command]
+pub async fn delete_transcript_segment(
+    state: tauri::State<'_, AppState>,
+    job_queue: tauri::State<'_, Arc<JobQueue>>,
+    conversation_id: i32,
+    segment: usize,
+) -> Result<TranscriptionJSON, String> {
+    record_edit(
+        &state,
+        &job_queue,
+        segment,
+        new_edit(conversation_id, TranscriptEditKind::Delete),
+    )
+    .await
+}
+
+/// Drops the most recent edit.
+#[tauri::command]
+pub async fn undo_transcript_edit(
+    state: tauri::State<'_, AppState>,
+    job_queue: tauri::State<'_, Arc<JobQueue>>,
+    conversation_id: i32,
+) -> Result<TranscriptionJSON, String> {
+    let edits = Query::find_transcript_edits(&state.db, conversation_id)
+        .await
+        .map_err(|e| e.to_string())?;
+    let last = edits.last().ok_or("Nothing to undo")?;
+
+    Mutation::delete_transcript_edit(&state.db, last.id)
+        .await
+        .map_err(|e| e.to_string())?;
+    job_queue
+        .enqueue(conversation_id, &[JobStage::Index])
+        .await?;
+
+    load_edited_transcription(
+        &state.db,
+        job_queue
+            .recording_dir(conversation_id)
+            .join("transcription.json"),
+        conversation_id,
+    )
+    .await
+}
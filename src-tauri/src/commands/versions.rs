@@ -0,0 +1,99 @@
+use std::fs::read_to_string;
+use std::sync::Arc;
+
+use entity::{job::JobStage, output_version};
+use serde::Serialize;
+use service::Query;
+
+use crate::{
+    jobs::{JobQueue, ProcessingOverrides},
+    summarize::SummaryJSON,
+    transcribe::TranscriptionJSON,
+    transcript_edits::load_edited_transcription,
+    AppState,
+};
+
+/// A version with the outputs it produced, so versions can be compared side by side.
+#[derive(Serialize)]
+pub struct OutputVersionDetails {
+    pub version: output_version::Model,
+    pub transcription: Option<TranscriptionJSON>,
+    pub summary: Option<SummaryJSON>,
+}
+
+#[tauri::command]
+pub async fn reprocess_conversation(
+    job_queue: tauri::State<'_, Arc<JobQueue>>,
+    conversation_id: i32,
+    stages: Vec<JobStage>,
+    overrides: Option<ProcessingOverrides>,
+) -> Result<output_version::Model, String> {
+    job_queue
+        .reprocess(conversation_id, &stages, overrides.unwrap_or_default())
+        .await
+}
+
+#[tauri::command]
+pub async fn get_output_versions(
+    state: tauri::State<'_, AppState>,
+    conversation_id: i32,
+) -> Result<Vec<output_version::Model>, String> {
+    Query::find_output_versions(&state.db, conversation_id)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_output_version(
+    state: tauri::State<'_, AppState>,
+    job_queue: tauri::State<'_, Arc<JobQueue>>,
+    version_id: i32,
+) -> Result<OutputVersionDetails, String> {
+    let version = Query::find_output_version_by_id(&state.db, version_id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or(format!("Version {} not found", version_id))?;
+    let version_dir = job_queue.version_dir(version.conversation_id, version.id);
+
+    let transcription_path = version_dir.join("transcription.json");
+    let transcription = if transcription_path.exists() {
+        Some(
+            load_edited_transcription(&state.db, transcription_path, version.conversation_id)
+                .await?,
+        )
+    } else {
+        None
+    };
+
+    let summary_path = version_dir.join("summary.json");
+    let summary = if summary_path.exists() {
+        let content = read_to_string(&summary_path)
+            .map_err(|err| format!("Failed to read file {}: {}", summary_path.display(), err))?;
+        Some(serde_json::from_str(&content).map_err(|err| {
+            format!(
+                "Failed to parse JSON in file {}: {}",
+                summary_path.display(),
+                err
+            )
+        })?)
+    } else {
+        None
+    };
+
+    Ok(OutputVersionDetails {
+        version,
+        transcription,
+        summary,
+    })
+}
+
+#[tauri::command]
+pub async fn set_current_output_version(
+    job_queue: tauri::State<'_, Arc<JobQueue>>,
+    conversation_id: i32,
+    version_id: i32,
+) -> Result<output_version::Model, String> {
+    job_queue
+        .set_current_version(conversation_id, version_id)
+        .await
+}
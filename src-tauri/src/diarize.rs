@@ -0,0 +1,280 @@
+use std::path::Path;
+
+use log::{info, warn};
+use ort::session::Session;
+use ort::value::Tensor;
+use rustfft::{num_complex::Complex, FftPlanner};
+
+use crate::transcribe::{TranscriptSegment, WavSamples};
+
+/// Where the speaker embedding model is installed, relative to the app data dir. It isn't
+/// bundled, diarization is skipped until it's there.
+pub const SPEAKER_MODEL_DIR: &str = "models";
+/// Any WeSpeaker style model works: it takes `[batch, frames, 80]` log mel filterbank features
+/// and returns one embedding per batch.
+pub const SPEAKER_MODEL_FILE: &str = "speaker-embedding.onnx";
+
+const SAMPLE_RATE: usize = 16000;
+const FRAME_LENGTH: usize = 400;
+const FRAME_SHIFT: usize = 160;
+const FFT_SIZE: usize = 512;
+const MEL_BINS: usize = 80;
+
+/// Segments shorter than this don't carry enough voice to embed reliably.
+const MIN_SEGMENT_MS: i64 = 1000;
+/// Cosine similarity above which two embeddings are taken to be the same speaker.
+const SAME_SPEAKER_THRESHOLD: f32 = 0.5;
+
+pub struct SpeakerEmbedder {
+    session: Session,
+}
+
+impl SpeakerEmbedder {
+    pub fn new(model_path: &Path) -> Result<Self, String> {
+        let session = Session::builder()
+            .and_then(|builder| builder.with_intra_threads(2))
+            .and_then(|builder| builder.commit_from_file(model_path))
+            .map_err(|e| format!("failed to load speaker model: {}", e))?;
+
+        Ok(SpeakerEmbedder { session })
+    }
+
+    /// L2 normalised embedding of 16kHz mono audio.
+    pub fn embed(&self, samples: &[f32]) -> Result<Vec<f32>, String> {
+        let (frames, features) = fbank(samples);
+        if frames == 0 {
+            return Err("not enough audio to embed".to_string());
+        }
+
+        let input = Tensor::from_array(([1usize, frames, MEL_BINS], features))
+            .map_err(|e| e.to_string())?;
+        let inputs = ort::inputs![input].map_err(|e| e.to_string())?;
+        let outputs = self.session.run(inputs).map_err(|e| e.to_string())?;
+        let (_, embedding) = outputs[0]
+            .try_extract_raw_tensor::<f32>()
+            .map_err(|e| e.to_string())?;
+
+        Ok(normalize(embedding.to_vec()))
+    }
+}
+
+/// Labels each segment with a stable `Speaker N` id by clustering voice embeddings.
+///
+/// Ids are numbered in order of first appearance. Segments that are too short to embed take
+/// the speaker of the closest preceding segment.
+pub fn diarize(
+    embedder: &SpeakerEmbedder,
+    audio: &mut WavSamples,
+    segments: &mut [TranscriptSegment],
+) {
+    let embeddings: Vec<Option<Vec<f32>>> = segments
+        .iter()
+        .map(|segment| {
+            if segment.t1 - segment.t0 < MIN_SEGMENT_MS {
+                return None;
+            }
+            match audio
+                .read_ms(segment.t0, segment.t1)
+                .and_then(|samples| embedder.embed(&samples))
+            {
+                Ok(embedding) => Some(embedding),
+                Err(e) => {
+                    warn!("Skipping segment at {}ms: {}", segment.t0, e);
+                    None
+                }
+            }
+        })
+        .collect();
+
+    let clusters = cluster(&embeddings);
+    info!(
+        "Found {} speakers in {} segments",
+        clusters.iter().flatten().max().map_or(0, |max| max + 1),
+        segments.len()
+    );
+
+    let mut previous: Option<usize> = None;
+    let mut pending: Vec<usize> = Vec::new();
+    for (index, cluster) in clusters.iter().enumerate() {
+        match cluster.or(previous) {
+            Some(speaker) => {
+                segments[index].speaker = Some(speaker_label(speaker));
+                // Short segments at the very start take the first speaker we find.
+                for pending_index in pending.drain(..) {
+                    segments[pending_index].speaker = Some(speaker_label(speaker));
+                }
+                previous = Some(speaker);
+            }
+            None => pending.push(index),
+        }
+    }
+}
+
+fn speaker_label(cluster: usize) -> String {
+    format!("Speaker {}", cluster + 1)
+}
+
+/// Online centroid clustering followed by a reassignment pass, numbered by first appearance.
+fn cluster(embeddings: &[Option<Vec<f32>>]) -> Vec<Option<usize>> {
+    let mut centroids: Vec<Vec<f32>> = Vec::new();
+    let mut sums: Vec<Vec<f32>> = Vec::new();
+
+    for embedding in embeddings.iter().flatten() {
+        match closest(&centroids, embedding) {
+            Some((index, similarity)) if similarity >= SAME_SPEAKER_THRESHOLD => {
+                for (sum, value) in sums[index].iter_mut().zip(embedding) {
+                    *sum += value;
+                }
+                centroids[index] = normalize(sums[index].clone());
+            }
+            _ => {
+                centroids.push(embedding.clone());
+                sums.push(embedding.clone());
+            }
+        }
+    }
+
+    // Early segments were assigned against immature centroids, so assign everything again.
+    let assignments: Vec<Option<usize>> = embeddings
+        .iter()
+        .map(|embedding| {
+            embedding
+                .as_ref()
+                .and_then(|embedding| closest(&centroids, embedding))
+                .map(|(index, _)| index)
+        })
+        .collect();
+
+    let mut order: Vec<usize> = Vec::new();
+    assignments
+        .into_iter()
+        .map(|assignment| {
+            assignment.map(|index| match order.iter().position(|seen| *seen == index) {
+                Some(position) => position,
+                None => {
+                    order.push(index);
+                    order.len() - 1
+                }
+            })
+        })
+        .collect()
+}
+
+fn closest(centroids: &[Vec<f32>], embedding: &[f32]) -> Option<(usize, f32)> {
+    centroids
+        .iter()
+        .map(|centroid| dot(centroid, embedding))
+        .enumerate()
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+fn normalize(mut vector: Vec<f32>) -> Vec<f32> {
+    let norm = dot(&vector, &vector).sqrt();
+    if norm > 0.0 {
+        for value in vector.iter_mut() {
+            *value /= norm;
+        }
+    }
+    vector
+}
+
+/// Kaldi style log mel filterbank with per-utterance mean normalisation.
+///
+/// Returns the number of frames and a row-major `[frames, MEL_BINS]` buffer.
+fn fbank(samples: &[f32]) -> (usize, Vec<f32>) {
+    if samples.len() < FRAME_LENGTH {
+        return (0, Vec::new());
+    }
+    let frames = 1 + (samples.len() - FRAME_LENGTH) / FRAME_SHIFT;
+
+    let window: Vec<f32> = (0..FRAME_LENGTH)
+        .map(|i| {
+            let hann = 0.5
+                - 0.5 * (2.0 * std::f32::consts::PI * i as f32 / (FRAME_LENGTH - 1) as f32).cos();
+            hann.powf(0.85)
+        })
+        .collect();
+    let filters = mel_filters();
+    let fft = FftPlanner::<f32>::new().plan_fft_forward(FFT_SIZE);
+
+    let mut features = vec![0.0f32; frames * MEL_BINS];
+    let mut buffer = vec![Complex::new(0.0f32, 0.0f32); FFT_SIZE];
+    for frame in 0..frames {
+        // Models are trained on 16 bit integer scale audio.
+        let mut frame_samples: Vec<f32> = samples
+            [frame * FRAME_SHIFT..frame * FRAME_SHIFT + FRAME_LENGTH]
+            .iter()
+            .map(|sample| sample * 32768.0)
+            .collect();
+
+        let mean = frame_samples.iter().sum::<f32>() / FRAME_LENGTH as f32;
+        for sample in frame_samples.iter_mut() {
+            *sample -= mean;
+        }
+        for i in (1..FRAME_LENGTH).rev() {
+            frame_samples[i] -= 0.97 * frame_samples[i - 1];
+        }
+        frame_samples[0] -= 0.97 * frame_samples[0];
+
+        for (i, value) in buffer.iter_mut().enumerate() {
+            *value = if i < FRAME_LENGTH {
+                Complex::new(frame_samples[i] * window[i], 0.0)
+            } else {
+                Complex::new(0.0, 0.0)
+            };
+        }
+        fft.process(&mut buffer);
+
+        let power: Vec<f32> = buffer[..FFT_SIZE / 2]
+            .iter()
+            .map(|value| value.norm_sqr())
+            .collect();
+        for (bin, filter) in filters.iter().enumerate() {
+            let energy: f32 = filter.iter().zip(&power).map(|(w, p)| w * p).sum();
+            features[frame * MEL_BINS + bin] = energy.max(f32::EPSILON).ln();
+        }
+    }
+
+    for bin in 0..MEL_BINS {
+        let mean = (0..frames)
+            .map(|frame| features[frame * MEL_BINS + bin])
+            .sum::<f32>()
+            / frames as f32;
+        for frame in 0..frames {
+            features[frame * MEL_BINS + bin] -= mean;
+        }
+    }
+
+    (frames, features)
+}
+
+fn mel_filters() -> Vec<Vec<f32>> {
+    let mel = |hz: f32| 1127.0 * (1.0 + hz / 700.0).ln();
+    let low = mel(20.0);
+    let high = mel(SAMPLE_RATE as f32 / 2.0);
+    let delta = (high - low) / (MEL_BINS + 1) as f32;
+
+    (0..MEL_BINS)
+        .map(|bin| {
+            let left = low + bin as f32 * delta;
+            let center = left + delta;
+            let right = center + delta;
+            (0..FFT_SIZE / 2)
+                .map(|i| {
+                    let m = mel(i as f32 * SAMPLE_RATE as f32 / FFT_SIZE as f32);
+                    if m <= left || m >= right {
+                        0.0
+                    } else if m <= center {
+                        (m - left) / (center - left)
+                    } else {
+                        (right - m) / (right - center)
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
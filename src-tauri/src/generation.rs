@@ -0,0 +1,212 @@
+use std::fs::{read_to_string, remove_file, write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use log::warn;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+
+use crate::llm::{CompletionRequest, GenerationKind, LlmProvider, TokenSink};
+
+pub const GENERATION_EVENT: &str = "generation";
+/// Written next to the outputs while they're generated.
+pub const PARTIAL_GENERATION_FILE: &str = "generation.partial.json";
+
+/// How often the answer being generated is written to disk.
+const SAVE_INTERVAL: Duration = Duration::from_millis(500);
+
+#[derive(Clone, Serialize)]
+pub struct GenerationEvent {
+    /// Unset for answers drawn from several conversations.
+    pub conversation_id: Option<i32>,
+    pub kind: GenerationKind,
+    pub token: String,
+    /// Set on the last event of an answer, which carries no token.
+    pub done: bool,
+}
+
+pub fn emit_generation(
+    handle: &AppHandle,
+    conversation_id: Option<i32>,
+    kind: GenerationKind,
+    token: &str,
+    done: bool,
+) {
+    let event = GenerationEvent {
+        conversation_id,
+        kind,
+        token: token.to_string(),
+        done,
+    };
+    if let Err(e) = handle.emit(GENERATION_EVENT, event) {
+        warn!("Failed to emit generation event: {}", e);
+    }
+}
+
+/// What an unfinished generation got through, so it can be resumed or shown as it is.
+#[derive(Default, Serialize, Deserialize)]
+pub struct PartialGeneration {
+    /// Finished answers in the order they were generated, reused when the generation resumes.
+    #[serde(default)]
+    pub completed: Vec<CompletedAnswer>,
+    /// The answer that was being generated.
+    pub in_progress: Option<PartialAnswer>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CompletedAnswer {
+    /// `CompletionRequest::cache_key` of the request.
+    pub key: String,
+    #[serde(flatten)]
+    pub answer: PartialAnswer,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PartialAnswer {
+    pub kind: GenerationKind,
+    pub text: String,
+}
+
+impl PartialGeneration {
+    pub fn load(path: &Path) -> Option<PartialGeneration> {
+        read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+    }
+
+    /// Everything generated so far, the unfinished answer last.
+    pub fn answers(&self) -> Vec<PartialAnswer> {
+        self.completed
+            .iter()
+            .map(|completed| completed.answer.clone())
+            .chain(self.in_progress.clone())
+            .collect()
+    }
+}
+
+/// Streams a conversation's answers to the frontend as `generation` events and keeps what's
+/// been generated on disk until `finish` is called.
+pub struct StreamingProvider<'a> {
+    provider: &'a dyn LlmProvider,
+    handle: AppHandle,
+    conversation_id: i32,
+    path: PathBuf,
+    partial: Mutex<PartialGeneration>,
+    last_saved: Mutex<Instant>,
+}
+
+impl<'a> StreamingProvider<'a> {
+    /// Picks up the answers of an interrupted generation saved at `path`.
+    pub fn load(
+        provider: &'a dyn LlmProvider,
+        handle: AppHandle,
+        conversation_id: i32,
+        path: PathBuf,
+    ) -> Self {
+        let partial = PartialGeneration::load(&path).unwrap_or_default();
+
+        StreamingProvider {
+            provider,
+            handle,
+            conversation_id,
+            path,
+            partial: Mutex::new(partial),
+            last_saved: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Drops the saved progress once everything it was for has been written.
+    pub fn finish(self) {
+        if self.path.exists() {
+            if let Err(e) = remove_file(&self.path) {
+                warn!("Failed to remove {}: {}", self.path.display(), e);
+            }
+        }
+    }
+
+    fn emit(&self, kind: GenerationKind, token: &str, done: bool) {
+        emit_generation(&self.handle, Some(self.conversation_id), kind, token, done);
+    }
+
+    fn save(&self) {
+        let partial = self.partial.lock().unwrap();
+        let result = serde_json::to_string(&*partial)
+            .map_err(|e| e.to_string())
+            .and_then(|json| write(&self.path, json).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            warn!("Failed to write {}: {}", self.path.display(), e);
+        }
+        *self.last_saved.lock().unwrap() = Instant::now();
+    }
+}
+
+#[async_trait]
+impl LlmProvider for StreamingProvider<'_> {
+    fn name(&self) -> String {
+        self.provider.name()
+    }
+
+    async fn complete(&self, request: &CompletionRequest) -> Result<String, String> {
+        self.complete_streaming(request, &|_| {}).await
+    }
+
+    async fn complete_streaming(
+        &self,
+        request: &CompletionRequest,
+        on_token: TokenSink<'_>,
+    ) -> Result<String, String> {
+        let key = request.cache_key(&self.provider.name());
+
+        let completed = self
+            .partial
+            .lock()
+            .unwrap()
+            .completed
+            .iter()
+            .find(|completed| completed.key == key)
+            .map(|completed| completed.answer.text.clone());
+        if let Some(completion) = completed {
+            self.emit(request.kind, &completion, false);
+            self.emit(request.kind, "", true);
+            on_token(&completion);
+            return Ok(completion);
+        }
+
+        self.partial.lock().unwrap().in_progress = Some(PartialAnswer {
+            kind: request.kind,
+            text: String::new(),
+        });
+        let completion = self
+            .provider
+            .complete_streaming(request, &|token| {
+                self.emit(request.kind, token, false);
+                on_token(token);
+
+                if let Some(answer) = self.partial.lock().unwrap().in_progress.as_mut() {
+                    answer.text.push_str(token);
+                }
+                if self.last_saved.lock().unwrap().elapsed() >= SAVE_INTERVAL {
+                    self.save();
+                }
+            })
+            .await?;
+        self.emit(request.kind, "", true);
+
+        {
+            let mut partial = self.partial.lock().unwrap();
+            partial.in_progress = None;
+            partial.completed.push(CompletedAnswer {
+                key,
+                answer: PartialAnswer {
+                    kind: request.kind,
+                    text: completion.clone(),
+                },
+            });
+        }
+        self.save();
+
+        Ok(completion)
+    }
+}
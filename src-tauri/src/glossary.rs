@@ -0,0 +1,108 @@
+use entity::glossary_term;
+use log::warn;
+use regex::{NoExpand, Regex};
+
+use crate::transcribe::TranscriptionJSON;
+
+/// Prompt budget of the Whisper models we know of, `n_text_ctx / 2`.
+pub const WHISPER_PROMPT_TOKENS: usize = 224;
+
+/// Rough token count for backends whose tokenizer we can't call. Names and acronyms tokenize
+/// poorly, so this errs on the high side.
+pub fn estimate_tokens(text: &str) -> usize {
+    (text.len() + 2) / 3
+}
+
+/// Builds the initial prompt from the user's prompt followed by as many glossary terms as fit.
+///
+/// Whisper only conditions on the last `budget` prompt tokens, so anything past that would
+/// silently push the start of the prompt out.
+pub fn build_initial_prompt(
+    base: &str,
+    glossary: &[glossary_term::Model],
+    budget: usize,
+    count_tokens: impl Fn(&str) -> usize,
+) -> String {
+    let base = base.trim();
+    if count_tokens(base) > budget {
+        warn!("Initial prompt is longer than {} tokens", budget);
+        return base.to_string();
+    }
+
+    let mut prompt = base.to_string();
+    let mut terms: Vec<&str> = Vec::new();
+    for term in glossary {
+        terms.push(term.term.trim());
+        let candidate = compose_prompt(base, &terms);
+        if count_tokens(&candidate) > budget {
+            warn!(
+                "Glossary only partly fits in the initial prompt, used {} of {} terms",
+                terms.len() - 1,
+                glossary.len()
+            );
+            break;
+        }
+        prompt = candidate;
+    }
+
+    prompt
+}
+
+fn compose_prompt(base: &str, terms: &[&str]) -> String {
+    match (base.is_empty(), terms.is_empty()) {
+        (_, true) => base.to_string(),
+        (true, false) => format!("{}.", terms.join(", ")),
+        (false, false) => format!("{} {}.", base, terms.join(", ")),
+    }
+}
+
+/// Replaces known mis-hearings of glossary terms with the correct spelling.
+pub fn substitute_glossary_terms(
+    transcription: &mut TranscriptionJSON,
+    glossary: &[glossary_term::Model],
+) {
+    let substitutions: Vec<(Regex, &str)> = glossary
+        .iter()
+        .filter_map(|term| {
+            let variants: Vec<String> = term
+                .misheard_list()
+                .iter()
+                .map(|variant| regex::escape(variant))
+                .collect();
+            if variants.is_empty() {
+                return None;
+            }
+
+            let pattern = format!(r"(?i)\b(?:{})\b", variants.join("|"));
+            match Regex::new(&pattern) {
+                Ok(regex) => Some((regex, term.term.as_str())),
+                Err(e) => {
+                    warn!("Skipping glossary term {}: {}", term.term, e);
+                    None
+                }
+            }
+        })
+        .collect();
+
+    if substitutions.is_empty() {
+        return;
+    }
+
+    let substitute = |text: &mut String| {
+        for (regex, term) in &substitutions {
+            if regex.is_match(text) {
+                *text = regex.replace_all(text, NoExpand(term)).into_owned();
+            }
+        }
+    };
+
+    for text in transcription.full_text.iter_mut() {
+        substitute(text);
+    }
+    for segment in transcription.segments.iter_mut() {
+        substitute(&mut segment.text);
+        for word in segment.words.iter_mut() {
+            substitute(&mut word.text);
+        }
+    }
+}
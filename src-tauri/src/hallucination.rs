@@ -0,0 +1,304 @@
+use std::io::Write;
+
+use flate2::{write::ZlibEncoder, Compression};
+use log::info;
+use serde::{Deserialize, Serialize};
+
+use crate::settings::HallucinationFilterSettings;
+use crate::transcribe::TranscriptSegment;
+
+/// Phrases Whisper is known to produce over silence and music, picked up from subtitled videos.
+pub const DEFAULT_BLACKLIST: [&str; 7] = [
+    "thank you for watching",
+    "thanks for watching",
+    "thank you for watching and see you next time",
+    "please subscribe",
+    "like and subscribe",
+    "subtitles by the amara org community",
+    "transcription by castingwords",
+];
+
+/// Short text compresses poorly whatever it says, so its ratio tells us nothing.
+const MIN_TEXT_LENGTH_FOR_RATIO: usize = 24;
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DropReason {
+    NoText,
+    NoSpeech { probability: f32 },
+    CompressionRatio { ratio: f32 },
+    RepeatedNgram { ngram: String, repeats: usize },
+    RepeatedSegment { repeats: usize },
+    Blacklisted { phrase: String },
+}
+
+/// A segment removed by the filter, kept in `transcription.json` so it can be restored.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct DroppedSegment {
+    #[serde(flatten)]
+    pub segment: TranscriptSegment,
+    pub reason: DropReason,
+}
+
+/// Splits Whisper output into the segments to keep and the ones that look hallucinated.
+pub fn filter_hallucinations(
+    segments: Vec<TranscriptSegment>,
+    settings: &HallucinationFilterSettings,
+) -> (Vec<TranscriptSegment>, Vec<DroppedSegment>) {
+    let blacklist: Vec<String> = DEFAULT_BLACKLIST
+        .iter()
+        .map(|phrase| phrase.to_string())
+        .chain(settings.blacklist.iter().map(|phrase| normalize(phrase)))
+        .filter(|phrase| !phrase.is_empty())
+        .collect();
+
+    let mut kept: Vec<TranscriptSegment> = Vec::new();
+    let mut dropped: Vec<DroppedSegment> = Vec::new();
+    let mut previous_text = String::new();
+    let mut previous_repeats = 0;
+    // Every word is "repeated" at least once, so a limit of 0 turns the repeat checks off.
+    let max_repeats = match settings.max_repeats {
+        0 => usize::MAX,
+        max_repeats => max_repeats,
+    };
+
+    for segment in segments {
+        let text = normalize(&segment.text);
+        if !text.is_empty() && text == previous_text {
+            previous_repeats += 1;
+        } else if !text.is_empty() {
+            previous_text = text.clone();
+            previous_repeats = 1;
+        }
+
+        // Only the remote backends report `no_speech_prob`, whisper-rs doesn't expose it for
+        // local transcription, so local segments are never dropped for it.
+        let reason = if text.is_empty() {
+            // Nothing but punctuation or symbols, e.g. `...` or `♪` over silence.
+            Some(DropReason::NoText)
+        } else if segment
+            .no_speech_prob
+            .is_some_and(|probability| probability > settings.no_speech_threshold)
+        {
+            Some(DropReason::NoSpeech {
+                probability: segment.no_speech_prob.unwrap_or_default(),
+            })
+        } else if let Some(phrase) = blacklist.iter().find(|phrase| **phrase == text) {
+            Some(DropReason::Blacklisted {
+                phrase: phrase.clone(),
+            })
+        } else if previous_repeats > max_repeats {
+            Some(DropReason::RepeatedSegment {
+                repeats: previous_repeats,
+            })
+        } else if let Some((ngram, repeats)) = repeated_ngram(&text, max_repeats) {
+            Some(DropReason::RepeatedNgram { ngram, repeats })
+        } else {
+            compression_ratio(&text)
+                .filter(|ratio| *ratio > settings.compression_ratio_threshold)
+                .map(|ratio| DropReason::CompressionRatio { ratio })
+        };
+
+        match reason {
+            Some(reason) => {
+                info!(
+                    "Dropped segment [{} - {}] ({}): {}",
+                    segment.t0,
+                    segment.t1,
+                    reason.describe(),
+                    segment.text.trim()
+                );
+                dropped.push(DroppedSegment { segment, reason });
+            }
+            None => kept.push(segment),
+        }
+    }
+
+    (kept, dropped)
+}
+
+impl DropReason {
+    fn describe(&self) -> String {
+        match self {
+            DropReason::NoText => "no text".to_string(),
+            DropReason::NoSpeech { probability } => format!("no speech p={:.2}", probability),
+            DropReason::CompressionRatio { ratio } => format!("compression ratio {:.2}", ratio),
+            DropReason::RepeatedNgram { ngram, repeats } => {
+                format!("\"{}\" repeated {} times", ngram, repeats)
+            }
+            DropReason::RepeatedSegment { repeats } => {
+                format!("segment repeated {} times", repeats)
+            }
+            DropReason::Blacklisted { phrase } => format!("blacklisted \"{}\"", phrase),
+        }
+    }
+}
+
+/// Lowercase words without punctuation, so "Thank you for watching!" matches the blacklist.
+fn normalize(text: &str) -> String {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric() && c != '\'')
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Finds an n-gram of up to four words repeated back to back more than `max_repeats` times.
+fn repeated_ngram(text: &str, max_repeats: usize) -> Option<(String, usize)> {
+    let words: Vec<&str> = text.split(' ').collect();
+
+    for n in 1..=4 {
+        let mut start = 0;
+        while start + n <= words.len() {
+            let ngram = &words[start..start + n];
+            let mut repeats = 1;
+            while start + (repeats + 1) * n <= words.len()
+                && &words[start + repeats * n..start + (repeats + 1) * n] == ngram
+            {
+                repeats += 1;
+            }
+            if repeats > max_repeats {
+                return Some((ngram.join(" "), repeats));
+            }
+            start += 1;
+        }
+    }
+
+    None
+}
+
+/// Ratio of raw to zlib compressed size, as used by Whisper to spot looping output.
+fn compression_ratio(text: &str) -> Option<f32> {
+    if text.len() < MIN_TEXT_LENGTH_FOR_RATIO {
+        return None;
+    }
+
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(text.as_bytes()).ok()?;
+    let compressed = encoder.finish().ok()?;
+
+    Some(text.len() as f32 / compressed.len() as f32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(t0: i64, text: &str) -> TranscriptSegment {
+        TranscriptSegment {
+            t0,
+            t1: t0 + 1000,
+            text: text.to_string(),
+            speaker_turn_next: false,
+            speaker: None,
+            words: Vec::new(),
+            no_speech_prob: None,
+        }
+    }
+
+    fn filter(
+        texts: &[&str],
+        settings: &HallucinationFilterSettings,
+    ) -> (Vec<String>, Vec<String>) {
+        let segments = texts
+            .iter()
+            .enumerate()
+            .map(|(i, text)| segment(i as i64 * 1000, text))
+            .collect();
+        let (kept, dropped) = filter_hallucinations(segments, settings);
+
+        (
+            kept.into_iter().map(|segment| segment.text).collect(),
+            dropped
+                .into_iter()
+                .map(|dropped| dropped.reason.describe())
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn drops_blacklisted_segments() {
+        let settings = HallucinationFilterSettings {
+            blacklist: vec!["Bye, everyone".to_string()],
+            ..Default::default()
+        };
+
+        let (kept, dropped) = filter(
+            &[
+                "Thanks for watching!",
+                "You",
+                "bye everyone.",
+                "Thanks for watching the demo.",
+            ],
+            &settings,
+        );
+        assert_eq!(kept, ["You", "Thanks for watching the demo."]);
+        assert_eq!(
+            dropped,
+            [
+                "blacklisted \"thanks for watching\"",
+                "blacklisted \"bye everyone\""
+            ]
+        );
+    }
+
+    #[test]
+    fn drops_repeated_ngrams() {
+        let settings = HallucinationFilterSettings::default();
+
+        let (kept, dropped) = filter(
+            &[
+                "No, no, no, no.",
+                "I mean it, I mean it, I mean it, I mean it, I mean it.",
+            ],
+            &settings,
+        );
+        assert_eq!(kept, ["No, no, no, no."]);
+        assert_eq!(dropped, ["\"i mean it\" repeated 5 times"]);
+    }
+
+    #[test]
+    fn drops_text_that_compresses_too_well() {
+        let settings = HallucinationFilterSettings::default();
+        let looping = "we will look at the budget and then we will look at the budget again and \
+                       then we will look at the budget again and then we will look at the budget";
+
+        assert!(compression_ratio("Too short to tell.").is_none());
+        let (kept, dropped) = filter(
+            &[looping, "We'll look at the budget once the numbers are in."],
+            &settings,
+        );
+        assert_eq!(kept, ["We'll look at the budget once the numbers are in."]);
+        assert!(dropped[0].starts_with("compression ratio"), "{:?}", dropped);
+    }
+
+    #[test]
+    fn drops_segments_repeated_back_to_back() {
+        let settings = HallucinationFilterSettings {
+            max_repeats: 2,
+            ..Default::default()
+        };
+
+        let (kept, dropped) = filter(
+            &["Okay.", "Okay.", "Okay!", "okay", "Right.", "Okay."],
+            &settings,
+        );
+        assert_eq!(kept, ["Okay.", "Okay.", "Right.", "Okay."]);
+        assert_eq!(
+            dropped,
+            ["segment repeated 3 times", "segment repeated 4 times"]
+        );
+    }
+
+    #[test]
+    fn keeps_repeats_when_the_limit_is_zero() {
+        let settings = HallucinationFilterSettings {
+            max_repeats: 0,
+            ..Default::default()
+        };
+
+        let (kept, dropped) = filter(&["Yes.", "Yes.", "Go, go, go."], &settings);
+        assert_eq!(kept, ["Yes.", "Yes.", "Go, go, go."]);
+        assert!(dropped.is_empty());
+    }
+}
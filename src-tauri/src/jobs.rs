@@ -0,0 +1,894 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::{copy, create_dir_all, read_to_string};
+use std::path::{Path, PathBuf};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use std::time::Duration;
+
+use entity::job::{self, JobStage, JobStatus};
+use entity::{conversation, output_version};
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use service::sea_orm::{DatabaseConnection, TryIntoModel};
+use service::{Mutation, Query};
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Notify;
+
+use crate::audio_redaction::redact_recording_audio;
+use crate::generation::{StreamingProvider, PARTIAL_GENERATION_FILE};
+use crate::llm::{LlmProvider, LlmProviders};
+use crate::prompts::PromptLibrary;
+use crate::recorder::{combine_segments, concat_segments};
+use crate::redaction::Redactor;
+use crate::search::index_conversation;
+use crate::settings::{LlmSettings, Settings, TranscriptionBackend};
+use crate::summarize::{generate_title, summarize_and_write, SummaryJSON, SummaryOptions};
+use crate::summary_template::{select_template, SummaryTemplate};
+use crate::transcribe::{transcribe_wav_file_and_write, TranscriptionOptions};
+use crate::transcriber::create_transcriber;
+use crate::transcript_edits::load_edited_transcription;
+
+/// Stages run for a freshly stopped recording.
+pub const RECORDING_STAGES: [JobStage; 5] = [
+    JobStage::Concat,
+    JobStage::Mix,
+    JobStage::Transcribe,
+    JobStage::Summarize,
+    JobStage::Index,
+];
+
+/// Stages that can be rerun on a conversation whose audio is already mixed.
+pub const REPROCESS_STAGES: [JobStage; 2] = [JobStage::Transcribe, JobStage::Summarize];
+
+/// Files a job produces, kept per output version.
+const OUTPUT_FILES: [&str; 2] = ["transcription.json", "summary.json"];
+
+/// How many times a single stage is attempted before the job is marked as failed.
+const MAX_STAGE_ATTEMPTS: i32 = 3;
+
+pub const JOB_PROGRESS_EVENT: &str = "job-progress";
+
+#[derive(Clone, Serialize)]
+pub struct JobProgressEvent {
+    pub job_id: i32,
+    pub conversation_id: i32,
+    pub stage: JobStage,
+    pub status: JobStatus,
+    /// Progress of the current stage, 0-100.
+    pub progress: i32,
+    pub error: Option<String>,
+}
+
+/// Settings replaced for a single reprocessing run, everything unset comes from the settings.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProcessingOverrides {
+    pub transcription_backend: Option<TranscriptionBackend>,
+    pub language: Option<String>,
+    pub summary_model: Option<String>,
+    /// Template to summarize with instead of the conversation's.
+    pub summary_template_id: Option<i32>,
+    /// Replaces the template's instruction.
+    pub summary_prompt: Option<String>,
+}
+
+impl ProcessingOverrides {
+    fn from_job(job: &job::Model) -> Result<ProcessingOverrides, String> {
+        match &job.overrides {
+            Some(overrides) => serde_json::from_str(overrides)
+                .map_err(|e| format!("Failed to parse job overrides: {}", e)),
+            None => Ok(ProcessingOverrides::default()),
+        }
+    }
+
+    fn apply_to_transcription(&self, options: &mut TranscriptionOptions) {
+        if let Some(backend) = &self.transcription_backend {
+            options.backend = backend.clone();
+        }
+        if let Some(language) = &self.language {
+            options.language = Some(language.clone());
+        }
+    }
+
+    fn summary_options(
+        &self,
+        llm: &LlmSettings,
+        mut template: SummaryTemplate,
+        mut prompts: PromptLibrary,
+    ) -> SummaryOptions {
+        if let Some(prompt) = &self.summary_prompt {
+            template.prompt = prompt.clone();
+        }
+        // A version made to compare models runs everything on the model it was asked for.
+        if self.summary_model.is_some() {
+            prompts = prompts.without_models();
+        }
+
+        SummaryOptions {
+            template,
+            context_tokens: llm.context_tokens,
+            prompts,
+        }
+    }
+}
+
+/// Persistent queue of post-recording processing jobs.
+///
+/// Jobs live in the `job` table so they survive restarts. A single worker runs them one at a
+/// time, stage by stage, which keeps Whisper and Ollama from competing with each other and
+/// leaves the recording state free while a conversation is being processed.
+pub struct JobQueue {
+    db: DatabaseConnection,
+    data_dir: PathBuf,
+    llm_providers: Arc<LlmProviders>,
+    notify: Notify,
+    cancel_flags: std::sync::Mutex<HashMap<i32, Arc<AtomicBool>>>,
+}
+
+impl JobQueue {
+    pub fn new(
+        db: DatabaseConnection,
+        data_dir: PathBuf,
+        llm_providers: Arc<LlmProviders>,
+    ) -> Self {
+        JobQueue {
+            db,
+            data_dir,
+            llm_providers,
+            notify: Notify::new(),
+            cancel_flags: std::sync::Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn data_dir(&self) -> &Path {
+        &self.data_dir
+    }
+
+    pub fn recording_dir(&self, conversation_id: i32) -> PathBuf {
+        self.data_dir
+            .join("chunks/audio")
+            .join(conversation_id.to_string())
+    }
+
+    pub fn version_dir(&self, conversation_id: i32, version_id: i32) -> PathBuf {
+        self.recording_dir(conversation_id)
+            .join("versions")
+            .join(version_id.to_string())
+    }
+
+    /// Where a job writes its outputs, the recording directory unless it produces a new version.
+    fn output_dir(&self, job: &job::Model) -> PathBuf {
+        match job.version_id {
+            Some(version_id) => self.version_dir(job.conversation_id, version_id),
+            None => self.recording_dir(job.conversation_id),
+        }
+    }
+
+    pub async fn enqueue(
+        &self,
+        conversation_id: i32,
+        stages: &[JobStage],
+    ) -> Result<job::Model, String> {
+        self.enqueue_job(conversation_id, stages, None, None).await
+    }
+
+    async fn enqueue_job(
+        &self,
+        conversation_id: i32,
+        stages: &[JobStage],
+        version_id: Option<i32>,
+        overrides: Option<String>,
+    ) -> Result<job::Model, String> {
+        let first_stage = *stages.first().ok_or("A job needs at least one stage")?;
+
+        let job = Mutation::create_job(
+            &self.db,
+            job::Model {
+                id: 0,
+                conversation_id,
+                stages: JobStage::join_list(stages),
+                stage: first_stage,
+                status: JobStatus::Queued,
+                progress: 0,
+                attempts: 0,
+                error: None,
+                version_id,
+                overrides,
+                created_at: String::new(),
+                updated_at: String::new(),
+            },
+        )
+        .await
+        .map_err(|e| e.to_string())?
+        .try_into_model()
+        .map_err(|e| e.to_string())?;
+
+        info!("Queued job {} for conversation {}", job.id, conversation_id);
+        self.notify.notify_one();
+
+        Ok(job)
+    }
+
+    /// Reruns stages of an existing conversation into a new output version, leaving the current
+    /// outputs alone until the new version is made current.
+    pub async fn reprocess(
+        &self,
+        conversation_id: i32,
+        stages: &[JobStage],
+        overrides: ProcessingOverrides,
+    ) -> Result<output_version::Model, String> {
+        if stages.is_empty() || stages.iter().any(|stage| !REPROCESS_STAGES.contains(stage)) {
+            return Err("Only transcribe and summarize can be rerun".to_string());
+        }
+        if !self
+            .recording_dir(conversation_id)
+            .join("combined.wav")
+            .exists()
+        {
+            return Err(format!(
+                "Conversation {} has no recording to reprocess",
+                conversation_id
+            ));
+        }
+
+        self.snapshot_original_version(conversation_id).await?;
+
+        let overrides = serde_json::to_string(&overrides).map_err(|e| e.to_string())?;
+        let version = self
+            .create_version(
+                conversation_id,
+                output_version::Model {
+                    id: 0,
+                    conversation_id,
+                    job_id: None,
+                    stages: JobStage::join_list(stages),
+                    overrides: Some(overrides.clone()),
+                    is_current: false,
+                    created_at: String::new(),
+                },
+            )
+            .await?;
+
+        let job = self
+            .enqueue_job(conversation_id, stages, Some(version.id), Some(overrides))
+            .await?;
+
+        Mutation::set_output_version_job(&self.db, version.id, job.id)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Keeps the conversation's first outputs as a version of their own before anything
+    /// replaces them.
+    async fn snapshot_original_version(&self, conversation_id: i32) -> Result<(), String> {
+        let versions = Query::find_output_versions(&self.db, conversation_id)
+            .await
+            .map_err(|e| e.to_string())?;
+        if !versions.is_empty() {
+            return Ok(());
+        }
+
+        let version = self
+            .create_version(
+                conversation_id,
+                output_version::Model {
+                    id: 0,
+                    conversation_id,
+                    job_id: None,
+                    stages: String::new(),
+                    overrides: None,
+                    is_current: true,
+                    created_at: String::new(),
+                },
+            )
+            .await?;
+
+        copy_outputs(
+            &self.recording_dir(conversation_id),
+            &self.version_dir(conversation_id, version.id),
+        )
+    }
+
+    async fn create_version(
+        &self,
+        conversation_id: i32,
+        version: output_version::Model,
+    ) -> Result<output_version::Model, String> {
+        let version = Mutation::create_output_version(&self.db, version)
+            .await
+            .map_err(|e| e.to_string())?
+            .try_into_model()
+            .map_err(|e| e.to_string())?;
+
+        create_dir_all(self.version_dir(conversation_id, version.id)).map_err(|e| e.to_string())?;
+
+        Ok(version)
+    }
+
+    /// Makes a version's outputs the ones the rest of the app reads.
+    pub async fn set_current_version(
+        &self,
+        conversation_id: i32,
+        version_id: i32,
+    ) -> Result<output_version::Model, String> {
+        let version = Query::find_output_version_by_id(&self.db, version_id)
+            .await
+            .map_err(|e| e.to_string())?
+            .filter(|version| version.conversation_id == conversation_id)
+            .ok_or(format!("Version {} not found", version_id))?;
+
+        if let Some(job_id) = version.job_id {
+            let job = self.find(job_id).await?;
+            if job.status != JobStatus::Completed {
+                return Err(format!("Version {} hasn't finished processing", version_id));
+            }
+        }
+
+        let version_dir = self.version_dir(conversation_id, version_id);
+        copy_outputs(&version_dir, &self.recording_dir(conversation_id))?;
+
+        let summary_path = version_dir.join("summary.json");
+        if summary_path.exists() {
+            let content = read_to_string(&summary_path).map_err(|e| e.to_string())?;
+            let summary: SummaryJSON = serde_json::from_str(&content)
+                .map_err(|e| format!("Failed to parse {}: {}", summary_path.display(), e))?;
+            self.save_summary_items(conversation_id, &summary).await?;
+        }
+
+        let version = Mutation::set_current_output_version(&self.db, conversation_id, version_id)
+            .await
+            .map_err(|e| e.to_string())?;
+        self.enqueue(conversation_id, &[JobStage::Index]).await?;
+
+        Ok(version)
+    }
+
+    /// Tracks the action items and insights of the conversation's current summary, keeping
+    /// finished action items.
+    async fn save_summary_items(
+        &self,
+        conversation_id: i32,
+        summary: &SummaryJSON,
+    ) -> Result<(), String> {
+        Mutation::replace_open_action_items(
+            &self.db,
+            conversation_id,
+            summary
+                .action_items
+                .iter()
+                .map(|item| item.to_model(conversation_id))
+                .collect(),
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+
+        Mutation::replace_insights(
+            &self.db,
+            conversation_id,
+            summary.insights.to_models(conversation_id),
+        )
+        .await
+        .map_err(|e| e.to_string())
+    }
+
+    /// Tracks the action items and insights of conversations summarized before they were kept in
+    /// the database, read from their `summary.json`.
+    async fn backfill_summary_items(&self) -> Result<(), String> {
+        let tracked: HashSet<i32> = Query::find_action_items(&self.db, None, None, None)
+            .await
+            .map_err(|e| e.to_string())?
+            .into_iter()
+            .map(|item| item.conversation_id)
+            .collect();
+
+        for conversation in Query::find_conversations(&self.db)
+            .await
+            .map_err(|e| e.to_string())?
+        {
+            if tracked.contains(&conversation.id) {
+                continue;
+            }
+            let path = self.recording_dir(conversation.id).join("summary.json");
+            let Ok(content) = read_to_string(&path) else {
+                continue;
+            };
+            let summary: SummaryJSON = match serde_json::from_str(&content) {
+                Ok(summary) => summary,
+                Err(e) => {
+                    warn!("Failed to parse {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            if summary.action_items.is_empty() {
+                continue;
+            }
+
+            info!(
+                "Importing {} action items of conversation {}",
+                summary.action_items.len(),
+                conversation.id
+            );
+            self.save_summary_items(conversation.id, &summary).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Titles a conversation from its summary. Titles the user wrote are kept unless `force` is
+    /// set, in which case the conversation goes back to generated titles.
+    pub async fn retitle(
+        &self,
+        conversation_id: i32,
+        summary: &SummaryJSON,
+        provider: &dyn LlmProvider,
+        force: bool,
+    ) -> Result<conversation::Model, String> {
+        let conversation = self.find_conversation(conversation_id).await?;
+        if conversation.title_edited && !force {
+            return Ok(conversation);
+        }
+
+        let prompts = PromptLibrary::load(&self.db).await?;
+        let title = generate_title(provider, &summary.to_markdown(), &prompts).await?;
+
+        // The user may have renamed or tagged the conversation while the title was generated.
+        let conversation = self.find_conversation(conversation_id).await?;
+        if conversation.title_edited && !force {
+            return Ok(conversation);
+        }
+        info!("Titled conversation {}: {}", conversation_id, title);
+
+        Mutation::update_conversation_by_id(
+            &self.db,
+            conversation_id,
+            conversation::Model {
+                title,
+                title_edited: false,
+                ..conversation
+            },
+        )
+        .await
+        .map_err(|e| e.to_string())
+    }
+
+    /// Cancels a queued job right away, or asks a running one to stop at the next opportunity.
+    pub async fn cancel(&self, job_id: i32) -> Result<job::Model, String> {
+        let job = self.find(job_id).await?;
+
+        match job.status {
+            JobStatus::Queued => {
+                self.save(job::Model {
+                    status: JobStatus::Cancelled,
+                    ..job
+                })
+                .await
+            }
+            JobStatus::Running => {
+                if let Some(flag) = self.cancel_flags.lock().unwrap().get(&job_id) {
+                    flag.store(true, Ordering::SeqCst);
+                }
+                Ok(job)
+            }
+            _ => Err(format!("Job {} is not queued or running", job_id)),
+        }
+    }
+
+    /// Requeues a failed or cancelled job. It picks up at the stage it stopped at unless
+    /// `from_stage` says otherwise.
+    pub async fn retry(
+        &self,
+        job_id: i32,
+        from_stage: Option<JobStage>,
+    ) -> Result<job::Model, String> {
+        let job = self.find(job_id).await?;
+
+        if job.status != JobStatus::Failed && job.status != JobStatus::Cancelled {
+            return Err(format!("Job {} is not failed or cancelled", job_id));
+        }
+
+        let stage = from_stage.unwrap_or(job.stage);
+        if !job.stage_list().contains(&stage) {
+            return Err(format!("Job {} has no {:?} stage", job_id, stage));
+        }
+
+        let job = self
+            .save(job::Model {
+                stage,
+                status: JobStatus::Queued,
+                progress: 0,
+                attempts: 0,
+                error: None,
+                ..job
+            })
+            .await?;
+        self.notify.notify_one();
+
+        Ok(job)
+    }
+
+    /// Worker loop, spawned once at startup.
+    pub async fn run(self: Arc<Self>, handle: AppHandle) {
+        match Mutation::requeue_running_jobs(&self.db).await {
+            Ok(result) if result.rows_affected > 0 => {
+                info!("Requeued {} interrupted jobs", result.rows_affected)
+            }
+            Ok(_) => {}
+            Err(e) => error!("Failed to requeue interrupted jobs: {}", e),
+        }
+        if let Err(e) = self.backfill_summary_items().await {
+            error!(
+                "Failed to import action items from earlier summaries: {}",
+                e
+            );
+        }
+
+        loop {
+            match Query::find_next_queued_job(&self.db).await {
+                Ok(Some(job)) => self.process(&handle, job).await,
+                Ok(None) => self.notify.notified().await,
+                Err(e) => {
+                    error!("Failed to fetch next job: {}", e);
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                }
+            }
+        }
+    }
+
+    async fn process(self: &Arc<Self>, handle: &AppHandle, job: job::Model) {
+        let cancel_flag = Arc::new(AtomicBool::new(false));
+        self.cancel_flags
+            .lock()
+            .unwrap()
+            .insert(job.id, cancel_flag.clone());
+
+        let job_id = job.id;
+        // Each job runs in its own task, so a panic fails the job instead of stopping the worker.
+        let queue = self.clone();
+        let task_handle = handle.clone();
+        let result =
+            tokio::spawn(async move { queue.run_stages(&task_handle, job, cancel_flag).await })
+                .await;
+        match result {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => error!("Failed to update job {}: {}", job_id, e),
+            Err(e) => {
+                error!("Job {} panicked: {}", job_id, e);
+                if let Err(e) = self.fail_panicked(handle, job_id, e.to_string()).await {
+                    error!("Failed to update job {}: {}", job_id, e);
+                }
+            }
+        }
+
+        self.cancel_flags.lock().unwrap().remove(&job_id);
+    }
+
+    async fn fail_panicked(
+        &self,
+        handle: &AppHandle,
+        job_id: i32,
+        error: String,
+    ) -> Result<(), String> {
+        let job = self.find(job_id).await?;
+
+        self.finish(
+            handle,
+            job,
+            JobStatus::Failed,
+            Some(format!("Stopped unexpectedly: {}", error)),
+        )
+        .await
+    }
+
+    async fn run_stages(
+        &self,
+        handle: &AppHandle,
+        job: job::Model,
+        cancel_flag: Arc<AtomicBool>,
+    ) -> Result<(), String> {
+        let job_id = job.id;
+        let stages = job.stage_list();
+        let start = stages.iter().position(|s| *s == job.stage).unwrap_or(0);
+
+        let mut job = self
+            .save(job::Model {
+                status: JobStatus::Running,
+                ..job
+            })
+            .await?;
+        emit_progress(handle, &job);
+
+        for stage in stages.iter().skip(start) {
+            if job.stage != *stage {
+                job = self
+                    .save(job::Model {
+                        stage: *stage,
+                        progress: 0,
+                        attempts: 0,
+                        ..job
+                    })
+                    .await?;
+                emit_progress(handle, &job);
+            }
+
+            loop {
+                if cancel_flag.load(Ordering::SeqCst) {
+                    return self.finish(handle, job, JobStatus::Cancelled, None).await;
+                }
+
+                info!(
+                    "Running {:?} for job {} (attempt {})",
+                    stage,
+                    job_id,
+                    job.attempts + 1
+                );
+                match self.run_stage(handle, &job, cancel_flag.clone()).await {
+                    Ok(()) => break,
+                    Err(_) if cancel_flag.load(Ordering::SeqCst) => {
+                        return self.finish(handle, job, JobStatus::Cancelled, None).await;
+                    }
+                    Err(e) => {
+                        let attempts = job.attempts + 1;
+                        warn!("{:?} failed for job {}: {}", stage, job_id, e);
+                        job = self
+                            .save(job::Model {
+                                attempts,
+                                error: Some(e.clone()),
+                                ..job
+                            })
+                            .await?;
+
+                        if attempts >= MAX_STAGE_ATTEMPTS {
+                            return self.finish(handle, job, JobStatus::Failed, Some(e)).await;
+                        }
+                        tokio::time::sleep(Duration::from_secs(2u64.pow(attempts as u32))).await;
+                    }
+                }
+            }
+
+            job = self
+                .save(job::Model {
+                    progress: 100,
+                    error: None,
+                    ..job
+                })
+                .await?;
+            emit_progress(handle, &job);
+        }
+
+        self.finish(handle, job, JobStatus::Completed, None).await
+    }
+
+    async fn run_stage(
+        &self,
+        handle: &AppHandle,
+        job: &job::Model,
+        cancel_flag: Arc<AtomicBool>,
+    ) -> Result<(), String> {
+        let recording_dir = self.recording_dir(job.conversation_id);
+        let output_dir = self.output_dir(job);
+
+        match job.stage {
+            JobStage::Concat => {
+                concat_segments(&recording_dir.join("input")).await?;
+                concat_segments(&recording_dir.join("output")).await?;
+            }
+            JobStage::Mix => {
+                combine_segments(&recording_dir).await?;
+            }
+            JobStage::Transcribe => {
+                let mut options = TranscriptionOptions::load(&self.db).await?;
+                ProcessingOverrides::from_job(job)?.apply_to_transcription(&mut options);
+                let transcriber = create_transcriber(handle, &options.backend, &options.decoding);
+                let mut redactor =
+                    Redactor::load(&self.db, &self.data_dir, job.conversation_id).await?;
+                let progress_handle = handle.clone();
+                let progress_job = job.clone();
+                transcribe_wav_file_and_write(
+                    handle,
+                    transcriber.as_ref(),
+                    &recording_dir.join("combined.wav"),
+                    &output_dir.join("transcription.json"),
+                    &options,
+                    &mut redactor,
+                    Box::new(move |progress| {
+                        emit_progress(
+                            &progress_handle,
+                            &job::Model {
+                                progress,
+                                ..progress_job.clone()
+                            },
+                        )
+                    }),
+                    cancel_flag,
+                )
+                .await?;
+                let audio_redactions = redact_recording_audio(
+                    &recording_dir,
+                    job.conversation_id,
+                    redactor.take_spans(),
+                    redactor.audio_method(),
+                )
+                .await?;
+                // Spans masked by an earlier run aren't heard again, their rows are kept as is.
+                Mutation::create_audio_redactions(&self.db, audio_redactions)
+                    .await
+                    .map_err(|e| e.to_string())?;
+            }
+            JobStage::Summarize => {
+                // A summary-only version summarizes the current transcript.
+                let transcription_path = if output_dir.join("transcription.json").exists() {
+                    output_dir.join("transcription.json")
+                } else {
+                    recording_dir.join("transcription.json")
+                };
+                let transcription =
+                    load_edited_transcription(&self.db, transcription_path, job.conversation_id)
+                        .await?;
+                let mut redactor =
+                    Redactor::load(&self.db, &self.data_dir, job.conversation_id).await?;
+                let overrides = ProcessingOverrides::from_job(job)?;
+                let conversation = self.find_conversation(job.conversation_id).await?;
+                let template = select_template(
+                    &self.db,
+                    &conversation,
+                    &transcription.speaker_text(),
+                    overrides.summary_template_id,
+                )
+                .await?;
+                let llm = Settings::load(&self.db).await?.llm;
+                let prompts = PromptLibrary::load(&self.db).await?;
+                let backend = llm.backend.with_model(overrides.summary_model.clone());
+                let provider = self.llm_providers.get(&backend, llm.context_tokens);
+                // Kept until the stage is done, so a retry picks up the answers it already got.
+                let streaming = StreamingProvider::load(
+                    provider.as_ref(),
+                    handle.clone(),
+                    job.conversation_id,
+                    output_dir.join(PARTIAL_GENERATION_FILE),
+                );
+                // Shared by all versions, so reprocessing only resends the chunks that changed.
+                let summary = summarize_and_write(
+                    &streaming,
+                    &transcription,
+                    &output_dir.join("summary.json"),
+                    &recording_dir.join("summary_cache.json"),
+                    &overrides.summary_options(&llm, template, prompts),
+                    &mut redactor,
+                )
+                .await?;
+
+                // Versions are only compared, the conversation keeps the title, action items and
+                // insights of its current one.
+                if job.version_id.is_none() {
+                    self.save_summary_items(job.conversation_id, &summary)
+                        .await?;
+                    if let Err(e) = self
+                        .retitle(job.conversation_id, &summary, &streaming, false)
+                        .await
+                    {
+                        warn!(
+                            "Failed to title conversation {}: {}",
+                            job.conversation_id, e
+                        );
+                    }
+                }
+                streaming.finish();
+            }
+            JobStage::Index => {
+                // The index follows the conversation's current outputs.
+                if job.version_id.is_some() {
+                    return Ok(());
+                }
+
+                let transcription = load_edited_transcription(
+                    &self.db,
+                    recording_dir.join("transcription.json"),
+                    job.conversation_id,
+                )
+                .await?;
+                let summary_path = recording_dir.join("summary.json");
+                let summary: Option<SummaryJSON> = match read_to_string(&summary_path) {
+                    Ok(content) => Some(serde_json::from_str(&content).map_err(|e| {
+                        format!("Failed to parse {}: {}", summary_path.display(), e)
+                    })?),
+                    Err(_) => None,
+                };
+                // Search is an extra, a backend that can't embed shouldn't fail the job.
+                let llm = Settings::load(&self.db).await?.llm;
+                let backend = match llm.embedding_backend() {
+                    Ok(backend) => backend,
+                    Err(e) => {
+                        info!("Not indexing conversation {}: {}", job.conversation_id, e);
+                        return Ok(());
+                    }
+                };
+                let provider = self.llm_providers.get(&backend, llm.context_tokens);
+                if let Err(e) = index_conversation(
+                    &self.db,
+                    provider.as_ref(),
+                    job.conversation_id,
+                    &transcription,
+                    summary.as_ref(),
+                )
+                .await
+                {
+                    warn!(
+                        "Failed to index conversation {}, it won't show up in search: {}",
+                        job.conversation_id, e
+                    );
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn finish(
+        &self,
+        handle: &AppHandle,
+        job: job::Model,
+        status: JobStatus,
+        error: Option<String>,
+    ) -> Result<(), String> {
+        let job = self
+            .save(job::Model {
+                status,
+                error,
+                ..job
+            })
+            .await?;
+        info!("Job {} finished as {:?}", job.id, job.status);
+        emit_progress(handle, &job);
+
+        Ok(())
+    }
+
+    async fn find_conversation(&self, conversation_id: i32) -> Result<conversation::Model, String> {
+        Query::find_conversation_by_id(&self.db, conversation_id)
+            .await
+            .map_err(|e| e.to_string())?
+            .ok_or(format!("Conversation {} not found", conversation_id))
+    }
+
+    async fn find(&self, job_id: i32) -> Result<job::Model, String> {
+        Query::find_job_by_id(&self.db, job_id)
+            .await
+            .map_err(|e| e.to_string())?
+            .ok_or(format!("Job {} not found", job_id))
+    }
+
+    async fn save(&self, job: job::Model) -> Result<job::Model, String> {
+        Mutation::update_job_by_id(&self.db, job.id, job)
+            .await
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Copies whichever output files exist from one directory to another.
+fn copy_outputs(from: &Path, to: &Path) -> Result<(), String> {
+    for file in OUTPUT_FILES {
+        if from.join(file).exists() {
+            copy(from.join(file), to.join(file))
+                .map_err(|e| format!("Failed to copy {}: {}", file, e))?;
+        }
+    }
+
+    Ok(())
+}
+
+fn emit_progress(handle: &AppHandle, job: &job::Model) {
+    let event = JobProgressEvent {
+        job_id: job.id,
+        conversation_id: job.conversation_id,
+        stage: job.stage,
+        status: job.status,
+        progress: job.progress,
+        error: job.error.clone(),
+    };
+
+    if let Err(e) = handle.emit(JOB_PROGRESS_EVENT, event) {
+        warn!("Failed to emit job progress: {}", e);
+    }
+}
@@ -1,14 +1,31 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
+mod assistant;
 mod audio;
+mod audio_redaction;
+mod chat;
 mod commands;
 mod device_listener;
+mod diarize;
+mod generation;
+mod glossary;
+mod hallucination;
+mod jobs;
+mod llm;
 mod media;
+mod prompts;
 mod recorder;
+mod redaction;
+mod search;
+mod settings;
 mod summarize;
+mod summary_template;
 mod transcribe;
+mod transcriber;
+mod transcript_edits;
 mod utils;
+mod whisper_service;
 mod window;
 
 use audio::macos::aggregate_device::{
@@ -34,31 +51,71 @@ use tauri::Manager;
 use tauri::WindowEvent;
 use tauri_plugin_log::{Target, TargetKind};
 use tauri_plugin_positioner::WindowExt;
-use transcribe::{get_complete_transcription, get_real_time_transcription};
+use transcribe::{
+    get_complete_transcription, get_real_time_transcription, get_transcript_words,
+    restore_dropped_segment,
+};
 use uuid::Uuid;
 use window::setup_windows;
 
 use crate::device_listener::ActiveListener;
-use crate::recorder::{RecordingOptions, _start_recording, _stop_recording};
+use crate::recorder::{_start_recording, _stop_recording, RecordingOptions};
 use commands::{
+    action_items::{
+        complete_action_item, get_action_items, reopen_action_item, update_action_item,
+    },
+    artifacts::{delete_artifact, get_artifacts, rerun_artifact, run_prompt},
+    chat::{chat_with_conversation, clear_chat_history, get_chat_history},
     conversation::{
         create_conversation, delete_conversation, get_conversation, get_conversations,
-        get_summary_for_converstation, open_conversation,
+        get_partial_generation, get_summary_for_converstation, open_conversation, regenerate_title,
+        rename_conversation, set_conversation_tags,
+    },
+    custom_prompts::{
+        create_custom_prompt, delete_custom_prompt, get_custom_prompts, update_custom_prompt,
     },
     devices::{
         enumerate_audio_input_devices, enumerate_audio_output_devices, set_input_device_name,
         set_output_device_name,
     },
+    glossary::{
+        create_glossary_term, delete_glossary_term, get_glossary_terms, update_glossary_term,
+    },
+    insights::get_insights,
+    jobs::{cancel_job, get_jobs, retry_job},
+    prompts::{
+        export_prompt_pack, get_prompt_versions, get_prompts, import_prompt_pack, reset_prompt,
+        update_prompt,
+    },
     recording::is_recording,
+    redaction::{get_audio_redactions, get_redactions, redact_transcript_span},
+    search::{ask_across_conversations, rebuild_search_index, semantic_search},
+    settings::{get_settings, update_settings},
+    summary_template::{
+        create_summary_template, delete_summary_template, get_summary_templates,
+        set_conversation_summary_template, update_summary_template,
+    },
+    transcript::{
+        delete_transcript_segment, edit_transcript_segment_text, get_transcript_edits,
+        merge_transcript_segments, set_transcript_segment_speaker, split_transcript_segment,
+        undo_transcript_edit,
+    },
+    versions::{
+        get_output_version, get_output_versions, reprocess_conversation, set_current_output_version,
+    },
 };
+use jobs::JobQueue;
+use llm::LlmProviders;
 use media::set_target_output_device;
 use recorder::{delete_recording_data, start_recording, stop_recording, RecordingState};
+use whisper_service::{WhisperService, WHISPER_MODELS_DIR};
 
 use std::sync::{atomic::AtomicBool, Arc};
 
 #[derive(Clone)]
 struct AppState {
     db: DatabaseConnection,
+    llm_providers: Arc<LlmProviders>,
 }
 
 struct DeviceState {
@@ -147,7 +204,7 @@ pub fn run() {
                 recording_options: None,
                 shutdown_flag: Arc::new(AtomicBool::new(false)),
                 audio_uploading_finished: Arc::new(AtomicBool::new(false)),
-                data_dir: Some(data_directory),
+                data_dir: Some(data_directory.clone()),
                 conversation_id: None,
             }));
 
@@ -159,7 +216,25 @@ pub fn run() {
 
             async_runtime::block_on(Migrator::up(&db, None)).unwrap();
 
-            let state = AppState { db };
+            let whisper_models_dir = app
+                .path()
+                .resource_dir()
+                .expect("failed to get resource dir")
+                .join(WHISPER_MODELS_DIR);
+            let whisper_service = Arc::new(WhisperService::new(whisper_models_dir));
+            app.manage(whisper_service.clone());
+            tauri::async_runtime::spawn(whisper_service.run_idle_unloader(db.clone()));
+
+            let llm_providers = Arc::new(LlmProviders::default());
+            let job_queue = Arc::new(JobQueue::new(
+                db.clone(),
+                data_directory,
+                llm_providers.clone(),
+            ));
+            app.manage(job_queue.clone());
+            tauri::async_runtime::spawn(job_queue.run(app.handle().clone()));
+
+            let state = AppState { db, llm_providers };
             app.manage(state);
 
             let device_state = DeviceState {
@@ -199,6 +274,10 @@ pub fn run() {
                                 &app_state.db,
                                 entity::conversation::Model {
                                     title: "New Conversation".to_string(),
+                                    title_edited: false,
+                                    summary_template_id: None,
+                                    tags: None,
+                                    participants: None,
                                     id: 0,
                                     created_at: String::new(),
                                     updated_at: String::new(),
@@ -255,6 +334,15 @@ pub fn run() {
             stop_recording,
             get_real_time_transcription,
             get_complete_transcription,
+            get_transcript_words,
+            restore_dropped_segment,
+            get_transcript_edits,
+            edit_transcript_segment_text,
+            set_transcript_segment_speaker,
+            split_transcript_segment,
+            merge_transcript_segments,
+            delete_transcript_segment,
+            undo_transcript_edit,
             delete_recording_data,
             enumerate_audio_input_devices,
             enumerate_audio_output_devices,
@@ -266,8 +354,58 @@ pub fn run() {
             set_input_device_name,
             set_output_device_name,
             get_summary_for_converstation,
+            rename_conversation,
+            regenerate_title,
+            get_partial_generation,
             open_conversation,
             is_recording,
+            get_jobs,
+            cancel_job,
+            retry_job,
+            reprocess_conversation,
+            get_output_versions,
+            get_output_version,
+            set_current_output_version,
+            get_settings,
+            update_settings,
+            get_redactions,
+            get_audio_redactions,
+            redact_transcript_span,
+            get_glossary_terms,
+            create_glossary_term,
+            update_glossary_term,
+            delete_glossary_term,
+            get_summary_templates,
+            create_summary_template,
+            update_summary_template,
+            delete_summary_template,
+            set_conversation_summary_template,
+            get_action_items,
+            complete_action_item,
+            reopen_action_item,
+            update_action_item,
+            get_insights,
+            get_custom_prompts,
+            create_custom_prompt,
+            update_custom_prompt,
+            delete_custom_prompt,
+            get_artifacts,
+            run_prompt,
+            rerun_artifact,
+            delete_artifact,
+            get_prompts,
+            get_prompt_versions,
+            update_prompt,
+            reset_prompt,
+            export_prompt_pack,
+            import_prompt_pack,
+            chat_with_conversation,
+            get_chat_history,
+            clear_chat_history,
+            semantic_search,
+            rebuild_search_index,
+            ask_across_conversations,
+            set_conversation_tags,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,108 @@
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use llama_cpp::{standard_sampler::StandardSampler, LlamaModel, LlamaParams, SessionParams};
+use log::info;
+use tokio::sync::Mutex;
+
+use super::{CompletionRequest, LlmProvider, TokenSink};
+
+const MAX_COMPLETION_TOKENS: usize = 1024;
+
+/// A GGUF model run in-process with llama.cpp. The model is loaded on first use.
+///
+/// There's no grammar support, so JSON requests rely on the prompt asking for JSON.
+pub struct LlamaCppProvider {
+    model_path: PathBuf,
+    /// Tokens of context a session gets, `context_tokens` of the settings that prompts are
+    /// packed to.
+    context_size: u32,
+    model: Mutex<Option<LlamaModel>>,
+}
+
+impl LlamaCppProvider {
+    pub fn new(model_path: PathBuf, context_size: u32) -> Self {
+        LlamaCppProvider {
+            model_path,
+            context_size,
+            model: Mutex::new(None),
+        }
+    }
+
+    async fn model(&self) -> Result<LlamaModel, String> {
+        let mut model = self.model.lock().await;
+        if let Some(model) = model.as_ref() {
+            return Ok(model.clone());
+        }
+
+        let loaded =
+            LlamaModel::load_from_file_async(self.model_path.clone(), LlamaParams::default())
+                .await
+                .map_err(|e| format!("failed to load {}: {}", self.model_path.display(), e))?;
+        info!("Loaded llama.cpp model {}", self.model_path.display());
+        *model = Some(loaded.clone());
+
+        Ok(loaded)
+    }
+}
+
+#[async_trait]
+impl LlmProvider for LlamaCppProvider {
+    fn name(&self) -> String {
+        self.model_path.display().to_string()
+    }
+
+    async fn complete(&self, request: &CompletionRequest) -> Result<String, String> {
+        self.complete_streaming(request, &|_| {}).await
+    }
+
+    async fn complete_streaming(
+        &self,
+        request: &CompletionRequest,
+        on_token: TokenSink<'_>,
+    ) -> Result<String, String> {
+        let model = self.model().await?;
+        // Without a chat template the system message simply leads the prompt.
+        let prompt = match &request.system {
+            Some(system) => format!("{}\n\n{}", system, request.prompt),
+            None => request.prompt.clone(),
+        };
+
+        // Tokens come off the blocking session one by one and are handed over here.
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel::<String>();
+        let context_size = self.context_size;
+        let generation = tauri::async_runtime::spawn_blocking(move || {
+            let mut session = model
+                .create_session(SessionParams {
+                    n_ctx: context_size,
+                    ..Default::default()
+                })
+                .map_err(|e| format!("failed to create llama.cpp session: {}", e))?;
+            session
+                .advance_context(&prompt)
+                .map_err(|e| format!("failed to evaluate prompt: {}", e))?;
+
+            let tokens = session
+                .start_completing_with(StandardSampler::default(), MAX_COMPLETION_TOKENS)
+                .map_err(|e| format!("failed to start completion: {}", e))?
+                .into_strings();
+            for token in tokens {
+                // The receiver is gone once the caller stopped waiting.
+                if sender.send(token).is_err() {
+                    break;
+                }
+            }
+
+            Ok::<(), String>(())
+        });
+
+        let mut completion = String::new();
+        while let Some(token) = receiver.recv().await {
+            on_token(&token);
+            completion.push_str(&token);
+        }
+        generation.await.map_err(|e| e.to_string())??;
+
+        Ok(completion)
+    }
+}
@@ -0,0 +1,321 @@
+mod llama_cpp;
+mod ollama;
+mod openai;
+mod structured;
+
+use std::sync::{Arc, Mutex};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+pub use self::llama_cpp::LlamaCppProvider;
+pub use ollama::OllamaProvider;
+pub use openai::OpenAiProvider;
+pub use structured::{complete_json, complete_structured};
+
+use crate::settings::LlmBackend;
+
+pub struct CompletionRequest {
+    pub prompt: String,
+    /// Instructions kept apart from the prompt, for providers that take a system message.
+    pub system: Option<String>,
+    /// Ask for a single JSON object back.
+    pub json: bool,
+    pub kind: GenerationKind,
+    /// Model to answer with instead of the provider's own. Providers that load a single model
+    /// file ignore it.
+    pub model: Option<String>,
+}
+
+/// What a completion is for, so streamed tokens can be told apart.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GenerationKind {
+    #[default]
+    Summary,
+    ActionItems,
+    Insights,
+    Title,
+    Chat,
+    Answer,
+    Artifact,
+}
+
+/// Called with each piece of an answer as it's generated.
+pub type TokenSink<'a> = &'a (dyn Fn(&str) + Send + Sync);
+
+impl CompletionRequest {
+    pub fn new(prompt: String) -> Self {
+        CompletionRequest {
+            prompt,
+            system: None,
+            json: false,
+            kind: GenerationKind::default(),
+            model: None,
+        }
+    }
+
+    pub fn json(self) -> Self {
+        CompletionRequest { json: true, ..self }
+    }
+
+    pub fn kind(self, kind: GenerationKind) -> Self {
+        CompletionRequest { kind, ..self }
+    }
+
+    pub fn model(self, model: Option<String>) -> Self {
+        CompletionRequest { model, ..self }
+    }
+
+    /// Identifies the request to `model`, for remembering answers.
+    pub fn cache_key(&self, model: &str) -> String {
+        let model = match &self.model {
+            Some(requested) => format!("{} {}", model, requested),
+            None => model.to_string(),
+        };
+        format!(
+            "{}\n{}\n{}\n{}",
+            model,
+            self.json,
+            self.system.as_deref().unwrap_or_default(),
+            self.prompt
+        )
+    }
+}
+
+/// A language model the summaries, action items and titles are generated with.
+#[async_trait]
+pub trait LlmProvider: Send + Sync {
+    /// Identifies the model, answers cached under one name aren't reused for another.
+    fn name(&self) -> String;
+
+    async fn complete(&self, request: &CompletionRequest) -> Result<String, String>;
+
+    /// Like `complete`, handing each piece of the answer to `on_token` as it's generated.
+    /// Providers that can't stream hand over the whole answer at once.
+    async fn complete_streaming(
+        &self,
+        request: &CompletionRequest,
+        on_token: TokenSink<'_>,
+    ) -> Result<String, String> {
+        let completion = self.complete(request).await?;
+        on_token(&completion);
+        Ok(completion)
+    }
+
+    /// One embedding vector per text, for the search index.
+    async fn embed(&self, _texts: &[String]) -> Result<Vec<Vec<f32>>, String> {
+        Err(format!("{} can't embed text", self.name()))
+    }
+}
+
+/// Calls `on_line` with each line of a streamed response body as it arrives.
+async fn read_lines(
+    mut response: reqwest::Response,
+    mut on_line: impl FnMut(&str) -> Result<(), String>,
+) -> Result<(), String> {
+    let mut buffer: Vec<u8> = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|e| e.to_string())? {
+        buffer.extend_from_slice(&chunk);
+        while let Some(end) = buffer.iter().position(|byte| *byte == b'\n') {
+            let line: Vec<u8> = buffer.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            if !line.trim().is_empty() {
+                on_line(line.trim())?;
+            }
+        }
+    }
+
+    let rest = String::from_utf8_lossy(&buffer);
+    if !rest.trim().is_empty() {
+        on_line(rest.trim())?;
+    }
+
+    Ok(())
+}
+
+/// One provider per backend, kept for the life of the app so a llama.cpp model is loaded once
+/// rather than for every generation.
+#[derive(Default)]
+pub struct LlmProviders {
+    providers: Mutex<Vec<CachedProvider>>,
+}
+
+struct CachedProvider {
+    backend: LlmBackend,
+    context_tokens: usize,
+    provider: Arc<dyn LlmProvider>,
+}
+
+impl LlmProviders {
+    /// The provider for `backend`. `context_tokens` sizes the context of in-process models, the
+    /// servers have their own.
+    pub fn get(&self, backend: &LlmBackend, context_tokens: usize) -> Arc<dyn LlmProvider> {
+        let mut providers = self.providers.lock().unwrap();
+        if let Some(cached) = providers
+            .iter()
+            .find(|cached| cached.backend == *backend && cached.context_tokens == context_tokens)
+        {
+            return cached.provider.clone();
+        }
+
+        // Only the model file in use stays in memory.
+        if matches!(backend, LlmBackend::LlamaCpp { .. }) {
+            providers.retain(|cached| !matches!(cached.backend, LlmBackend::LlamaCpp { .. }));
+        }
+        let provider = create_llm_provider(backend, context_tokens);
+        providers.push(CachedProvider {
+            backend: backend.clone(),
+            context_tokens,
+            provider: provider.clone(),
+        });
+
+        provider
+    }
+}
+
+fn create_llm_provider(backend: &LlmBackend, context_tokens: usize) -> Arc<dyn LlmProvider> {
+    match backend {
+        LlmBackend::Ollama { host, port, model } => {
+            Arc::new(OllamaProvider::new(host.clone(), *port, model.clone()))
+        }
+        LlmBackend::OpenAi {
+            url,
+            model,
+            api_key,
+        } => Arc::new(OpenAiProvider::new(
+            url.clone(),
+            model.clone(),
+            api_key.clone(),
+        )),
+        LlmBackend::LlamaCpp { model_path } => Arc::new(LlamaCppProvider::new(
+            model_path.into(),
+            context_tokens as u32,
+        )),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod mock_server {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+    use tokio::task::JoinHandle;
+
+    pub struct MockRequest {
+        /// e.g. `POST /api/generate HTTP/1.1`
+        pub request_line: String,
+        pub headers: Vec<(String, String)>,
+        pub body: serde_json::Value,
+        /// The body as sent, for requests that aren't JSON.
+        pub raw_body: Vec<u8>,
+    }
+
+    impl MockRequest {
+        pub fn header(&self, name: &str) -> Option<&str> {
+            self.headers
+                .iter()
+                .find(|(header, _)| header.eq_ignore_ascii_case(name))
+                .map(|(_, value)| value.as_str())
+        }
+
+        /// Values of the multipart form field `name`, in the order they were sent.
+        pub fn form_fields(&self, name: &str) -> Vec<String> {
+            let body = String::from_utf8_lossy(&self.raw_body);
+            body.split(&format!("name=\"{}\"", name))
+                .skip(1)
+                .filter_map(|part| {
+                    let value = &part[part.find("\r\n\r\n")? + 4..];
+                    Some(value[..value.find("\r\n--")?].to_string())
+                })
+                .collect()
+        }
+    }
+
+    /// Answers a single request on a local port with `status` and `response_body`, and hands back what
+    /// was asked.
+    pub async fn serve_once(
+        status: u16,
+        response_body: &'static str,
+    ) -> (u16, JoinHandle<MockRequest>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let request = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+
+            let mut received = Vec::new();
+            let mut buffer = [0; 4096];
+            let header_end = loop {
+                let read = stream.read(&mut buffer).await.unwrap();
+                received.extend_from_slice(&buffer[..read]);
+                if let Some(end) = received.windows(4).position(|w| w == b"\r\n\r\n") {
+                    break end;
+                }
+            };
+
+            let head = String::from_utf8_lossy(&received[..header_end]).to_string();
+            let mut lines = head.lines();
+            let request_line = lines.next().unwrap_or_default().to_string();
+            let headers: Vec<(String, String)> = lines
+                .filter_map(|line| line.split_once(':'))
+                .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+                .collect();
+            let content_length = headers
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+                .and_then(|(_, value)| value.parse::<usize>().ok())
+                .unwrap_or(0);
+
+            while received.len() < header_end + 4 + content_length {
+                let read = stream.read(&mut buffer).await.unwrap();
+                received.extend_from_slice(&buffer[..read]);
+            }
+            let raw_body = received[header_end + 4..].to_vec();
+            let body = serde_json::from_slice(&raw_body).unwrap_or(serde_json::Value::Null);
+
+            let response = format!(
+                "HTTP/1.1 {} Mock\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                status,
+                response_body.len(),
+                response_body
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+            stream.shutdown().await.unwrap();
+
+            MockRequest {
+                request_line,
+                headers,
+                body,
+                raw_body,
+            }
+        });
+
+        (port, request)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ollama(model: &str) -> LlmBackend {
+        LlmBackend::Ollama {
+            host: "http://localhost".to_string(),
+            port: 11434,
+            model: model.to_string(),
+        }
+    }
+
+    #[test]
+    fn reuses_the_provider_of_a_backend() {
+        let providers = LlmProviders::default();
+
+        let first = providers.get(&ollama("mistral"), 8192);
+        let again = providers.get(&ollama("mistral"), 8192);
+        let other = providers.get(&ollama("llama3"), 8192);
+
+        assert!(Arc::ptr_eq(&first, &again));
+        assert!(!Arc::ptr_eq(&first, &other));
+        assert_eq!(other.name(), "ollama/llama3");
+    }
+}
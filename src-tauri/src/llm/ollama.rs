@@ -0,0 +1,270 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use super::{read_lines, CompletionRequest, LlmProvider, TokenSink};
+
+/// A local or remote Ollama server.
+pub struct OllamaProvider {
+    url: String,
+    model: String,
+    client: reqwest::Client,
+}
+
+#[derive(Serialize)]
+struct GenerateRequest<'a> {
+    model: &'a str,
+    prompt: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    format: Option<&'a str>,
+    stream: bool,
+}
+
+#[derive(Deserialize)]
+struct GenerateResponse {
+    response: String,
+}
+
+/// One line of a streamed answer.
+#[derive(Deserialize)]
+struct GenerateChunk {
+    #[serde(default)]
+    response: String,
+    error: Option<String>,
+}
+
+#[derive(Serialize)]
+struct EmbedRequest<'a> {
+    model: &'a str,
+    input: &'a [String],
+}
+
+#[derive(Deserialize)]
+struct EmbedResponse {
+    embeddings: Vec<Vec<f32>>,
+}
+
+impl OllamaProvider {
+    /// `host` includes the scheme, e.g. `http://localhost`.
+    pub fn new(host: String, port: u16, model: String) -> Self {
+        OllamaProvider {
+            url: format!("{}:{}", host.trim_end_matches('/'), port),
+            model,
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl LlmProvider for OllamaProvider {
+    fn name(&self) -> String {
+        format!("ollama/{}", self.model)
+    }
+
+    async fn complete(&self, request: &CompletionRequest) -> Result<String, String> {
+        let response = self
+            .client
+            .post(format!("{}/api/generate", self.url))
+            .json(&GenerateRequest {
+                model: request.model.as_deref().unwrap_or(&self.model),
+                prompt: &request.prompt,
+                system: request.system.as_deref(),
+                format: request.json.then_some("json"),
+                stream: false,
+            })
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| format!("Ollama request failed: {}", e))?
+            .json::<GenerateResponse>()
+            .await
+            .map_err(|e| format!("unexpected Ollama response: {}", e))?;
+
+        Ok(response.response)
+    }
+
+    async fn complete_streaming(
+        &self,
+        request: &CompletionRequest,
+        on_token: TokenSink<'_>,
+    ) -> Result<String, String> {
+        let response = self
+            .client
+            .post(format!("{}/api/generate", self.url))
+            .json(&GenerateRequest {
+                model: request.model.as_deref().unwrap_or(&self.model),
+                prompt: &request.prompt,
+                system: request.system.as_deref(),
+                format: request.json.then_some("json"),
+                stream: true,
+            })
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| format!("Ollama request failed: {}", e))?;
+
+        let mut completion = String::new();
+        read_lines(response, |line| {
+            let chunk: GenerateChunk = serde_json::from_str(line)
+                .map_err(|e| format!("unexpected Ollama response: {}", e))?;
+            if let Some(error) = chunk.error {
+                return Err(error);
+            }
+            on_token(&chunk.response);
+            completion.push_str(&chunk.response);
+            Ok(())
+        })
+        .await
+        .map_err(|e| format!("Ollama request failed: {}", e))?;
+
+        Ok(completion)
+    }
+
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, String> {
+        let response = self
+            .client
+            .post(format!("{}/api/embed", self.url))
+            .json(&EmbedRequest {
+                model: &self.model,
+                input: texts,
+            })
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| format!("Ollama request failed: {}", e))?
+            .json::<EmbedResponse>()
+            .await
+            .map_err(|e| format!("unexpected Ollama response: {}", e))?;
+
+        if response.embeddings.len() != texts.len() {
+            return Err(format!(
+                "Ollama returned {} embeddings for {} texts",
+                response.embeddings.len(),
+                texts.len()
+            ));
+        }
+
+        Ok(response.embeddings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::mock_server::serve_once;
+
+    #[tokio::test]
+    async fn generates_with_the_configured_model() {
+        let (port, request) =
+            serve_once(200, r#"{"response":"A short summary.","done":true}"#).await;
+        let provider =
+            OllamaProvider::new("http://127.0.0.1".to_string(), port, "mistral".to_string());
+
+        let completion = provider
+            .complete(&CompletionRequest::new("Summarize this".to_string()))
+            .await
+            .unwrap();
+
+        let request = request.await.unwrap();
+        assert_eq!(completion, "A short summary.");
+        assert_eq!(request.request_line, "POST /api/generate HTTP/1.1");
+        assert_eq!(request.body["model"], "mistral");
+        assert_eq!(request.body["prompt"], "Summarize this");
+        assert_eq!(request.body["stream"], false);
+        assert!(request.body.get("format").is_none());
+    }
+
+    #[tokio::test]
+    async fn generates_with_the_requested_model() {
+        let (port, request) = serve_once(200, r#"{"response":"Done."}"#).await;
+        let provider =
+            OllamaProvider::new("http://127.0.0.1".to_string(), port, "mistral".to_string());
+
+        provider
+            .complete(
+                &CompletionRequest::new("Summarize this".to_string())
+                    .model(Some("llama3".to_string())),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(request.await.unwrap().body["model"], "llama3");
+    }
+
+    #[tokio::test]
+    async fn asks_for_json_when_requested() {
+        let (port, request) = serve_once(200, r#"{"response":"{\"action_items\":[]}"}"#).await;
+        let provider =
+            OllamaProvider::new("http://127.0.0.1".to_string(), port, "llama3".to_string());
+
+        let completion = provider
+            .complete(&CompletionRequest::new("Extract".to_string()).json())
+            .await
+            .unwrap();
+
+        assert_eq!(completion, r#"{"action_items":[]}"#);
+        assert_eq!(request.await.unwrap().body["format"], "json");
+    }
+
+    #[tokio::test]
+    async fn reports_server_errors() {
+        let (port, _) = serve_once(404, r#"{"error":"model 'nope' not found"}"#).await;
+        let provider =
+            OllamaProvider::new("http://127.0.0.1".to_string(), port, "nope".to_string());
+
+        let error = provider
+            .complete(&CompletionRequest::new("Hi".to_string()))
+            .await
+            .unwrap_err();
+
+        assert!(error.contains("404"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn streams_tokens() {
+        let (port, request) = serve_once(
+            200,
+            "{\"response\":\"Q3 \",\"done\":false}\n{\"response\":\"planning\",\"done\":false}\n{\"response\":\"\",\"done\":true}\n",
+        )
+        .await;
+        let provider =
+            OllamaProvider::new("http://127.0.0.1".to_string(), port, "llama3".to_string());
+
+        let tokens = std::sync::Mutex::new(Vec::new());
+        let completion = provider
+            .complete_streaming(&CompletionRequest::new("Title".to_string()), &|token| {
+                tokens.lock().unwrap().push(token.to_string())
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(completion, "Q3 planning");
+        assert_eq!(tokens.into_inner().unwrap(), ["Q3 ", "planning", ""]);
+        assert_eq!(request.await.unwrap().body["stream"], true);
+    }
+
+    #[tokio::test]
+    async fn embeds_texts() {
+        let (port, request) = serve_once(
+            200,
+            r#"{"model":"nomic-embed-text","embeddings":[[0.1,0.2]]}"#,
+        )
+        .await;
+        let provider = OllamaProvider::new(
+            "http://127.0.0.1".to_string(),
+            port,
+            "nomic-embed-text".to_string(),
+        );
+
+        let embeddings = provider
+            .embed(&["Budget review".to_string()])
+            .await
+            .unwrap();
+
+        let request = request.await.unwrap();
+        assert_eq!(embeddings, vec![vec![0.1, 0.2]]);
+        assert_eq!(request.request_line, "POST /api/embed HTTP/1.1");
+        assert_eq!(request.body["input"][0], "Budget review");
+    }
+}
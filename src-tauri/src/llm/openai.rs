@@ -0,0 +1,316 @@
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use super::{read_lines, CompletionRequest, LlmProvider, TokenSink};
+
+/// Any server implementing OpenAI's `/v1/chat/completions`, e.g. llama.cpp's server, LM Studio
+/// or vLLM.
+pub struct OpenAiProvider {
+    url: String,
+    model: String,
+    api_key: Option<String>,
+    client: reqwest::Client,
+}
+
+#[derive(Serialize)]
+struct ChatMessage<'a> {
+    role: &'a str,
+    content: &'a str,
+}
+
+#[derive(Deserialize)]
+struct ChatCompletion {
+    choices: Vec<ChatChoice>,
+}
+
+#[derive(Deserialize)]
+struct ChatChoice {
+    message: ChatChoiceMessage,
+}
+
+#[derive(Deserialize)]
+struct ChatChoiceMessage {
+    content: String,
+}
+
+/// One server-sent event of a streamed answer.
+#[derive(Deserialize)]
+struct ChatCompletionChunk {
+    choices: Vec<ChatChunkChoice>,
+}
+
+#[derive(Deserialize)]
+struct ChatChunkChoice {
+    delta: ChatDelta,
+}
+
+#[derive(Deserialize)]
+struct ChatDelta {
+    content: Option<String>,
+}
+
+/// Answer of `/v1/embeddings`, in input order by `index`.
+#[derive(Deserialize)]
+struct EmbeddingList {
+    data: Vec<Embedding>,
+}
+
+#[derive(Deserialize)]
+struct Embedding {
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+impl OpenAiProvider {
+    pub fn new(url: String, model: String, api_key: Option<String>) -> Self {
+        OpenAiProvider {
+            url: url.trim_end_matches('/').to_string(),
+            model,
+            api_key,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    fn request(&self, request: &CompletionRequest, stream: bool) -> reqwest::RequestBuilder {
+        let mut messages = Vec::new();
+        if let Some(system) = &request.system {
+            messages.push(ChatMessage {
+                role: "system",
+                content: system,
+            });
+        }
+        messages.push(ChatMessage {
+            role: "user",
+            content: &request.prompt,
+        });
+
+        let mut body = json!({
+            "model": request.model.as_deref().unwrap_or(&self.model),
+            "messages": messages,
+            "stream": stream,
+        });
+        if request.json {
+            body["response_format"] = json!({ "type": "json_object" });
+        }
+
+        let http_request = self
+            .client
+            .post(format!("{}/v1/chat/completions", self.url))
+            .json(&body);
+        match &self.api_key {
+            Some(api_key) => http_request.bearer_auth(api_key),
+            None => http_request,
+        }
+    }
+}
+
+#[async_trait]
+impl LlmProvider for OpenAiProvider {
+    fn name(&self) -> String {
+        format!("{}/{}", self.url, self.model)
+    }
+
+    async fn complete(&self, request: &CompletionRequest) -> Result<String, String> {
+        let completion = self
+            .request(request, false)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| format!("chat completion request failed: {}", e))?
+            .json::<ChatCompletion>()
+            .await
+            .map_err(|e| format!("unexpected chat completion response: {}", e))?;
+
+        completion
+            .choices
+            .into_iter()
+            .next()
+            .map(|choice| choice.message.content)
+            .ok_or("chat completion has no choices".to_string())
+    }
+
+    async fn complete_streaming(
+        &self,
+        request: &CompletionRequest,
+        on_token: TokenSink<'_>,
+    ) -> Result<String, String> {
+        let response = self
+            .request(request, true)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| format!("chat completion request failed: {}", e))?;
+
+        let mut completion = None::<String>;
+        read_lines(response, |line| {
+            let Some(data) = line.strip_prefix("data:").map(str::trim) else {
+                return Ok(());
+            };
+            if data == "[DONE]" {
+                return Ok(());
+            }
+
+            let chunk: ChatCompletionChunk = serde_json::from_str(data)
+                .map_err(|e| format!("unexpected chat completion response: {}", e))?;
+            if let Some(choice) = chunk.choices.into_iter().next() {
+                let token = choice.delta.content.unwrap_or_default();
+                on_token(&token);
+                completion.get_or_insert_with(String::new).push_str(&token);
+            }
+            Ok(())
+        })
+        .await
+        .map_err(|e| format!("chat completion request failed: {}", e))?;
+
+        completion.ok_or("chat completion has no choices".to_string())
+    }
+
+    async fn embed(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, String> {
+        let http_request = self
+            .client
+            .post(format!("{}/v1/embeddings", self.url))
+            .json(&json!({ "model": self.model, "input": texts }));
+        let http_request = match &self.api_key {
+            Some(api_key) => http_request.bearer_auth(api_key),
+            None => http_request,
+        };
+
+        let mut list = http_request
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| format!("embeddings request failed: {}", e))?
+            .json::<EmbeddingList>()
+            .await
+            .map_err(|e| format!("unexpected embeddings response: {}", e))?;
+
+        if list.data.len() != texts.len() {
+            return Err(format!(
+                "got {} embeddings for {} texts",
+                list.data.len(),
+                texts.len()
+            ));
+        }
+        list.data.sort_by_key(|embedding| embedding.index);
+
+        Ok(list
+            .data
+            .into_iter()
+            .map(|embedding| embedding.embedding)
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::mock_server::serve_once;
+
+    const COMPLETION: &str =
+        r#"{"id":"1","choices":[{"index":0,"message":{"role":"assistant","content":"Done."}}]}"#;
+
+    #[tokio::test]
+    async fn sends_chat_messages() {
+        let (port, request) = serve_once(200, COMPLETION).await;
+        let provider = OpenAiProvider::new(
+            format!("http://127.0.0.1:{}/", port),
+            "qwen2.5".to_string(),
+            Some("secret".to_string()),
+        );
+
+        let completion = provider
+            .complete(&CompletionRequest {
+                system: Some("Be brief.".to_string()),
+                ..CompletionRequest::new("Summarize this".to_string())
+            })
+            .await
+            .unwrap();
+
+        let request = request.await.unwrap();
+        assert_eq!(completion, "Done.");
+        assert_eq!(request.request_line, "POST /v1/chat/completions HTTP/1.1");
+        assert_eq!(request.header("authorization"), Some("Bearer secret"));
+        assert_eq!(request.body["model"], "qwen2.5");
+        assert_eq!(request.body["messages"][0]["role"], "system");
+        assert_eq!(request.body["messages"][1]["content"], "Summarize this");
+        assert!(request.body.get("response_format").is_none());
+    }
+
+    #[tokio::test]
+    async fn asks_for_a_json_object_when_requested() {
+        let (port, request) = serve_once(200, COMPLETION).await;
+        let provider =
+            OpenAiProvider::new(format!("http://127.0.0.1:{}", port), "m".to_string(), None);
+
+        provider
+            .complete(&CompletionRequest::new("Extract".to_string()).json())
+            .await
+            .unwrap();
+
+        let request = request.await.unwrap();
+        assert_eq!(request.body["response_format"]["type"], "json_object");
+        assert_eq!(request.header("authorization"), None);
+    }
+
+    #[tokio::test]
+    async fn fails_without_choices() {
+        let (port, _) = serve_once(200, r#"{"choices":[]}"#).await;
+        let provider =
+            OpenAiProvider::new(format!("http://127.0.0.1:{}", port), "m".to_string(), None);
+
+        let error = provider
+            .complete(&CompletionRequest::new("Hi".to_string()))
+            .await
+            .unwrap_err();
+
+        assert_eq!(error, "chat completion has no choices");
+    }
+
+    #[tokio::test]
+    async fn streams_server_sent_events() {
+        let (port, request) = serve_once(
+            200,
+            "data: {\"choices\":[{\"delta\":{\"role\":\"assistant\"}}]}\n\ndata: {\"choices\":[{\"delta\":{\"content\":\"Do\"}}]}\n\ndata: {\"choices\":[{\"delta\":{\"content\":\"ne.\"}}]}\n\ndata: [DONE]\n\n",
+        )
+        .await;
+        let provider =
+            OpenAiProvider::new(format!("http://127.0.0.1:{}", port), "m".to_string(), None);
+
+        let tokens = std::sync::Mutex::new(String::new());
+        let completion = provider
+            .complete_streaming(&CompletionRequest::new("Hi".to_string()), &|token| {
+                tokens.lock().unwrap().push_str(token)
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(completion, "Done.");
+        assert_eq!(tokens.into_inner().unwrap(), "Done.");
+        assert_eq!(request.await.unwrap().body["stream"], true);
+    }
+
+    #[tokio::test]
+    async fn embeds_in_input_order() {
+        let (port, request) = serve_once(
+            200,
+            r#"{"data":[{"index":1,"embedding":[0.5,0.5]},{"index":0,"embedding":[1.0,0.0]}]}"#,
+        )
+        .await;
+        let provider = OpenAiProvider::new(
+            format!("http://127.0.0.1:{}", port),
+            "text-embedding-3-small".to_string(),
+            None,
+        );
+
+        let embeddings = provider
+            .embed(&["first".to_string(), "second".to_string()])
+            .await
+            .unwrap();
+
+        let request = request.await.unwrap();
+        assert_eq!(embeddings, vec![vec![1.0, 0.0], vec![0.5, 0.5]]);
+        assert_eq!(request.request_line, "POST /v1/embeddings HTTP/1.1");
+        assert_eq!(request.body["input"][1], "second");
+    }
+}
@@ -0,0 +1,424 @@
+use std::fmt;
+
+use log::warn;
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use super::{CompletionRequest, LlmProvider};
+
+/// Answers asked for in all, the first included.
+const MAX_ATTEMPTS: usize = 3;
+
+/// Why no valid structured answer came back.
+#[derive(Debug)]
+pub enum StructuredError {
+    /// The provider didn't answer at all.
+    Provider(String),
+    /// Every answer was malformed or didn't match the schema, the errors are of the last one.
+    Invalid {
+        response: String,
+        errors: Vec<String>,
+    },
+}
+
+impl fmt::Display for StructuredError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StructuredError::Provider(e) => write!(f, "{}", e),
+            StructuredError::Invalid { errors, .. } => write!(
+                f,
+                "no valid answer after {} attempts: {}",
+                MAX_ATTEMPTS,
+                errors.join("; ")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for StructuredError {}
+
+/// The JSON Schema of `T`, as its `Deserialize` impl reads it.
+pub fn schema_for<T: JsonSchema>() -> Value {
+    serde_json::to_value(schemars::schema_for!(T)).unwrap_or_default()
+}
+
+/// Asks for an answer of type `T`, see `complete_json`.
+pub async fn complete_structured<T: DeserializeOwned + JsonSchema>(
+    provider: &dyn LlmProvider,
+    request: CompletionRequest,
+) -> Result<T, StructuredError> {
+    let schema = schema_for::<T>();
+    complete_valid(provider, request, &schema, |value| {
+        // The schema allows what the type may not, e.g. an integer out of range.
+        serde_json::from_value(value).map_err(|e| vec![e.to_string()])
+    })
+    .await
+}
+
+/// Asks for a JSON object matching `schema`, which is appended to the prompt.
+///
+/// Answers are repaired where that's unambiguous, like a markdown fence around the object or a
+/// trailing comma. An answer that still doesn't match is sent back along with what's wrong with
+/// it, up to `MAX_ATTEMPTS` times.
+pub async fn complete_json(
+    provider: &dyn LlmProvider,
+    request: CompletionRequest,
+    schema: &Value,
+) -> Result<Value, StructuredError> {
+    complete_valid(provider, request, schema, Ok).await
+}
+
+async fn complete_valid<T>(
+    provider: &dyn LlmProvider,
+    request: CompletionRequest,
+    schema: &Value,
+    parse: impl Fn(Value) -> Result<T, Vec<String>>,
+) -> Result<T, StructuredError> {
+    let schema_text = serde_json::to_string_pretty(schema).unwrap_or_default();
+    let prompt = format!(
+        "{}\n\nAnswer with a JSON object matching this JSON Schema:\n{}",
+        request.prompt, schema_text
+    );
+    let mut request = CompletionRequest {
+        prompt: prompt.clone(),
+        json: true,
+        ..request
+    };
+
+    let mut attempt = 1;
+    loop {
+        let response = provider
+            .complete(&request)
+            .await
+            .map_err(StructuredError::Provider)?;
+
+        let result = repair(&response).map_err(|e| vec![e]).and_then(|value| {
+            let errors = validate(&value, schema);
+            if errors.is_empty() {
+                parse(value)
+            } else {
+                Err(errors)
+            }
+        });
+        let errors = match result {
+            Ok(answer) => return Ok(answer),
+            Err(errors) => errors,
+        };
+        if attempt == MAX_ATTEMPTS {
+            return Err(StructuredError::Invalid { response, errors });
+        }
+
+        warn!(
+            "Answer {} didn't match the schema, asking again: {}",
+            attempt,
+            errors.join("; ")
+        );
+        request = CompletionRequest {
+            prompt: format!(
+                "{}\n\nYour previous answer was:\n{}\nIt isn't valid: {}\nAnswer again with the corrected JSON object only.",
+                prompt,
+                response,
+                errors.join("; ")
+            ),
+            ..request
+        };
+        attempt += 1;
+    }
+}
+
+/// Parses an answer, cutting it down to the outermost object and dropping trailing commas.
+fn repair(response: &str) -> Result<Value, String> {
+    if let Ok(value) = serde_json::from_str(response.trim()) {
+        return Ok(value);
+    }
+
+    let start = response.find('{');
+    let end = response.rfind('}');
+    let object = match (start, end) {
+        (Some(start), Some(end)) if start < end => &response[start..=end],
+        _ => return Err("the answer contains no JSON object".to_string()),
+    };
+
+    serde_json::from_str(&strip_trailing_commas(object))
+        .map_err(|e| format!("the answer isn't valid JSON: {}", e))
+}
+
+/// Removes commas directly before a closing bracket, leaving strings alone.
+fn strip_trailing_commas(json: &str) -> String {
+    let chars: Vec<char> = json.chars().collect();
+    let mut stripped = String::with_capacity(json.len());
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in chars.iter().enumerate() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+        } else if *c == '"' {
+            in_string = true;
+        } else if *c == ',' {
+            let next = chars[i + 1..].iter().find(|c| !c.is_whitespace());
+            if matches!(next, Some('}') | Some(']')) {
+                continue;
+            }
+        }
+        stripped.push(*c);
+    }
+
+    stripped
+}
+
+/// Checks `value` against the parts of JSON Schema the answers are described with: types,
+/// enums, properties, required properties, items and references to definitions.
+pub fn validate(value: &Value, schema: &Value) -> Vec<String> {
+    let mut errors = Vec::new();
+    validate_at(value, schema, schema, "$", &mut errors);
+    errors
+}
+
+fn validate_at(value: &Value, schema: &Value, root: &Value, path: &str, errors: &mut Vec<String>) {
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        match resolve(root, reference) {
+            Some(schema) => validate_at(value, schema, root, path, errors),
+            None => errors.push(format!("{}: unknown reference {}", path, reference)),
+        }
+        return;
+    }
+
+    if let Some(schemas) = schema.get("allOf").and_then(Value::as_array) {
+        for schema in schemas {
+            validate_at(value, schema, root, path, errors);
+        }
+    }
+    for key in ["anyOf", "oneOf"] {
+        if let Some(schemas) = schema.get(key).and_then(Value::as_array) {
+            let matches = schemas.iter().any(|schema| {
+                let mut errors = Vec::new();
+                validate_at(value, schema, root, path, &mut errors);
+                errors.is_empty()
+            });
+            if !matches {
+                errors.push(format!("{}: matches none of the allowed shapes", path));
+            }
+        }
+    }
+
+    if let Some(types) = schema.get("type") {
+        let types: Vec<&str> = match types {
+            Value::String(name) => vec![name.as_str()],
+            Value::Array(names) => names.iter().filter_map(Value::as_str).collect(),
+            _ => Vec::new(),
+        };
+        if !types.iter().any(|name| has_type(value, name)) {
+            errors.push(format!(
+                "{}: expected {}, got {}",
+                path,
+                types.join(" or "),
+                type_name(value)
+            ));
+            return;
+        }
+    }
+
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        if !allowed.contains(value) {
+            errors.push(format!("{}: {} isn't one of {:?}", path, value, allowed));
+        }
+    }
+
+    match value {
+        Value::Object(object) => {
+            let properties = schema.get("properties").and_then(Value::as_object);
+            if let Some(required) = schema.get("required").and_then(Value::as_array) {
+                for key in required.iter().filter_map(Value::as_str) {
+                    if !object.contains_key(key) {
+                        errors.push(format!("{}: missing required property {}", path, key));
+                    }
+                }
+            }
+            for (key, value) in object {
+                match properties.and_then(|properties| properties.get(key)) {
+                    Some(property) => {
+                        validate_at(value, property, root, &format!("{}.{}", path, key), errors)
+                    }
+                    None if schema.get("additionalProperties") == Some(&Value::Bool(false)) => {
+                        errors.push(format!("{}: unexpected property {}", path, key))
+                    }
+                    None => {}
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(item_schema) = schema.get("items") {
+                for (i, item) in items.iter().enumerate() {
+                    validate_at(item, item_schema, root, &format!("{}[{}]", path, i), errors);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Looks up a local reference like `#/definitions/ActionItem`.
+fn resolve<'a>(root: &'a Value, reference: &str) -> Option<&'a Value> {
+    root.pointer(reference.strip_prefix('#')?)
+}
+
+fn has_type(value: &Value, name: &str) -> bool {
+    match name {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "number" => value.is_number(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use async_trait::async_trait;
+    use serde_json::json;
+
+    use super::*;
+
+    /// Answers with `responses` in turn and remembers the prompts it got.
+    struct ScriptedProvider {
+        responses: Mutex<Vec<&'static str>>,
+        prompts: Mutex<Vec<String>>,
+    }
+
+    impl ScriptedProvider {
+        fn new(responses: &[&'static str]) -> Self {
+            ScriptedProvider {
+                responses: Mutex::new(responses.iter().rev().copied().collect()),
+                prompts: Mutex::new(Vec::new()),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl LlmProvider for ScriptedProvider {
+        fn name(&self) -> String {
+            "scripted".to_string()
+        }
+
+        async fn complete(&self, request: &CompletionRequest) -> Result<String, String> {
+            assert!(request.json);
+            self.prompts.lock().unwrap().push(request.prompt.clone());
+            self.responses
+                .lock()
+                .unwrap()
+                .pop()
+                .map(str::to_string)
+                .ok_or("no more responses".to_string())
+        }
+    }
+
+    fn items_schema() -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "items": { "type": "array", "items": { "$ref": "#/definitions/Item" } }
+            },
+            "required": ["items"],
+            "definitions": {
+                "Item": {
+                    "type": "object",
+                    "properties": {
+                        "title": { "type": "string" },
+                        "due": { "type": ["string", "null"] }
+                    },
+                    "required": ["title"]
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn repairs_fenced_answers_with_trailing_commas() {
+        let response = "Here you go:\n```json\n{\"items\": [{\"title\": \"a, }\"},],}\n```";
+
+        assert_eq!(
+            repair(response).unwrap(),
+            json!({ "items": [{ "title": "a, }" }] })
+        );
+        assert!(repair("No action items.").is_err());
+    }
+
+    #[test]
+    fn reports_where_answers_break_the_schema() {
+        let errors = validate(
+            &json!({ "items": [{ "title": "a" }, { "due": 3 }] }),
+            &items_schema(),
+        );
+
+        assert_eq!(
+            errors,
+            vec![
+                "$.items[1]: missing required property title",
+                "$.items[1].due: expected string or null, got number",
+            ]
+        );
+        assert!(validate(&json!({ "items": [] }), &items_schema()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn asks_again_with_what_was_wrong() {
+        let provider = ScriptedProvider::new(&[r#"{"actionItems": []}"#, r#"{"items": []}"#]);
+
+        let answer = complete_json(
+            &provider,
+            CompletionRequest::new("Extract".to_string()),
+            &items_schema(),
+        )
+        .await
+        .unwrap();
+
+        let prompts = provider.prompts.lock().unwrap();
+        assert_eq!(answer, json!({ "items": [] }));
+        assert_eq!(prompts.len(), 2);
+        assert!(prompts[1].contains(r#"{"actionItems": []}"#));
+        assert!(prompts[1].contains("missing required property items"));
+    }
+
+    #[tokio::test]
+    async fn gives_up_with_the_last_errors() {
+        let provider = ScriptedProvider::new(&["no", "still no", "{\"items\": 1}"]);
+
+        let error = complete_json(
+            &provider,
+            CompletionRequest::new("Extract".to_string()),
+            &items_schema(),
+        )
+        .await
+        .unwrap_err();
+
+        match error {
+            StructuredError::Invalid { response, errors } => {
+                assert_eq!(response, "{\"items\": 1}");
+                assert_eq!(errors, vec!["$.items: expected array, got number"]);
+            }
+            StructuredError::Provider(e) => panic!("unexpected provider error {}", e),
+        }
+    }
+}
@@ -0,0 +1,472 @@
+use std::collections::HashMap;
+
+use entity::conversation;
+use log::info;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use service::{sea_orm::DatabaseConnection, Query};
+
+use crate::glossary::estimate_tokens;
+use crate::llm::CompletionRequest;
+use crate::summarize::{transcript_pieces, SummaryJSON};
+use crate::transcribe::TranscriptionJSON;
+
+pub const SUMMARY_PROMPT: &str = "summary";
+pub const CHUNK_SUMMARY_PROMPT: &str = "chunk_summary";
+pub const COMBINE_SUMMARIES_PROMPT: &str = "combine_summaries";
+pub const ACTION_ITEMS_PROMPT: &str = "action_items";
+pub const INSIGHTS_PROMPT: &str = "insights";
+pub const TITLE_PROMPT: &str = "title";
+
+/// One of the prompts the pipeline is built from, as it ships.
+pub struct DefaultPrompt {
+    pub key: &'static str,
+    pub template: &'static str,
+    /// What the pipeline fills in, the only variables the template can use.
+    pub variables: &'static [&'static str],
+}
+
+/// The prompt migration seeded the first version of each prompt from these. Changes here only
+/// reach existing installs through a migration adding a new version.
+pub const DEFAULT_PROMPTS: [DefaultPrompt; 6] = [
+    DefaultPrompt {
+        key: SUMMARY_PROMPT,
+        template: "{{instructions}}\ntranscript: {{transcript}}",
+        variables: &["instructions", "transcript"],
+    },
+    DefaultPrompt {
+        key: CHUNK_SUMMARY_PROMPT,
+        template: "Summarize this part of a meeting transcript, keeping decisions, numbers and who said what: {{transcript}}",
+        variables: &["transcript"],
+    },
+    DefaultPrompt {
+        key: COMBINE_SUMMARIES_PROMPT,
+        template: "The following are summaries of consecutive parts of one meeting.\n\n{{summaries}}",
+        variables: &["summaries"],
+    },
+    DefaultPrompt {
+        key: ACTION_ITEMS_PROMPT,
+        template: "Create action items from a transcript. Each line of the transcript starts with its timestamp.\ntranscript: {{transcript}}",
+        variables: &["transcript"],
+    },
+    DefaultPrompt {
+        key: INSIGHTS_PROMPT,
+        template: "List the decisions, open questions, risks and topics of a transcript. Each line of the transcript starts with its timestamp.\ntranscript: {{transcript}}",
+        variables: &["transcript"],
+    },
+    DefaultPrompt {
+        key: TITLE_PROMPT,
+        template: "Can you generate a short meeting title from this? Answer with the title only: {{summary}}",
+        variables: &["summary"],
+    },
+];
+
+pub fn default_prompt(key: &str) -> Result<&'static DefaultPrompt, String> {
+    DEFAULT_PROMPTS
+        .iter()
+        .find(|prompt| prompt.key == key)
+        .ok_or(format!("There's no prompt called {}", key))
+}
+
+/// Rejects edits the pipeline couldn't fill in, or that would leave out what it fills in.
+/// Returns the defaults of the prompt.
+pub fn validate_prompt(
+    key: &str,
+    template: &str,
+    model: Option<&str>,
+) -> Result<&'static DefaultPrompt, String> {
+    let default = default_prompt(key)?;
+    if template.trim().is_empty() {
+        return Err(format!("Prompt {} can't be empty", key));
+    }
+    check_variables(template, default.variables)?;
+    let used = prompt_variables(template);
+    if let Some(missing) = default
+        .variables
+        .iter()
+        .find(|name| !used.iter().any(|used| used == *name))
+    {
+        return Err(format!("Prompt {} has to use {{{{{}}}}}", key, missing));
+    }
+    // The combined summaries are sent inside the summary prompt, on its model.
+    if key == COMBINE_SUMMARIES_PROMPT && model.is_some_and(|model| !model.trim().is_empty()) {
+        return Err(format!(
+            "Prompt {} is sent with the {} prompt and can't have a model of its own",
+            key, SUMMARY_PROMPT
+        ));
+    }
+
+    Ok(default)
+}
+
+/// The pipeline's prompts, the latest version of each from the prompt library.
+#[derive(Clone, Debug)]
+pub struct PromptLibrary {
+    prompts: HashMap<String, LibraryPrompt>,
+}
+
+#[derive(Clone, Debug)]
+struct LibraryPrompt {
+    template: String,
+    model: Option<String>,
+}
+
+impl PromptLibrary {
+    /// The prompts as they ship, for when the database can't be read.
+    pub fn defaults() -> PromptLibrary {
+        PromptLibrary {
+            prompts: DEFAULT_PROMPTS
+                .iter()
+                .map(|prompt| {
+                    (
+                        prompt.key.to_string(),
+                        LibraryPrompt {
+                            template: prompt.template.to_string(),
+                            model: None,
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    pub async fn load(db: &DatabaseConnection) -> Result<PromptLibrary, String> {
+        let mut library = PromptLibrary::defaults();
+        for prompt in Query::find_latest_prompts(db)
+            .await
+            .map_err(|e| e.to_string())?
+        {
+            if prompt.version > 1 {
+                info!("Using version {} of prompt {}", prompt.version, prompt.key);
+            }
+            library.prompts.insert(
+                prompt.key,
+                LibraryPrompt {
+                    template: prompt.template,
+                    model: prompt.model,
+                },
+            );
+        }
+
+        Ok(library)
+    }
+
+    /// Sends every prompt to the model of the settings, for runs that pick the model themselves.
+    pub fn without_models(mut self) -> PromptLibrary {
+        for prompt in self.prompts.values_mut() {
+            prompt.model = None;
+        }
+        self
+    }
+
+    /// The prompt `key` filled in with `variables`, for the model it targets.
+    pub fn request(
+        &self,
+        key: &str,
+        variables: &[(&str, &str)],
+    ) -> Result<CompletionRequest, String> {
+        let prompt = self
+            .prompts
+            .get(key)
+            .ok_or(format!("There's no prompt called {}", key))?;
+        let variables: HashMap<&str, String> = variables
+            .iter()
+            .map(|(name, value)| (*name, value.to_string()))
+            .collect();
+        let text = render_prompt(&prompt.template, &variables)
+            .map_err(|e| format!("Prompt {}: {}", key, e))?;
+
+        Ok(CompletionRequest::new(text).model(prompt.model.clone()))
+    }
+}
+
+/// Prompts shared as a file, to move tuned prompts between machines.
+#[derive(Serialize, Deserialize)]
+pub struct PromptPack {
+    pub prompts: Vec<PackedPrompt>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PackedPrompt {
+    pub key: String,
+    pub template: String,
+    #[serde(default)]
+    pub model: Option<String>,
+}
+
+/// Rejects the whole pack if any of its prompts is invalid, so it's imported all or nothing.
+pub fn validate_pack(pack: &PromptPack) -> Result<(), String> {
+    for prompt in &pack.prompts {
+        validate_prompt(&prompt.key, &prompt.template, prompt.model.as_deref())?;
+    }
+
+    Ok(())
+}
+
+/// Variables a custom prompt can use, filled in from the conversation it's run against.
+pub const CONVERSATION_VARIABLES: [&str; 5] =
+    ["title", "date", "participants", "transcript", "summary"];
+
+fn variable_pattern() -> Regex {
+    Regex::new(r"\{\{\s*(\w+)\s*\}\}").unwrap()
+}
+
+/// The variables a prompt uses, once each in order of appearance.
+pub fn prompt_variables(prompt: &str) -> Vec<String> {
+    let mut variables: Vec<String> = Vec::new();
+    for capture in variable_pattern().captures_iter(prompt) {
+        let name = capture[1].to_string();
+        if !variables.contains(&name) {
+            variables.push(name);
+        }
+    }
+
+    variables
+}
+
+/// Rejects prompts with a variable that isn't one of `known`, so a typo doesn't reach the model.
+pub fn check_variables(prompt: &str, known: &[&str]) -> Result<(), String> {
+    match prompt_variables(prompt)
+        .into_iter()
+        .find(|name| !known.contains(&name.as_str()))
+    {
+        Some(name) => Err(format!(
+            "Unknown variable {{{{{}}}}}, use one of {}",
+            name,
+            known
+                .iter()
+                .map(|known| format!("{{{{{}}}}}", known))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Fills in a prompt's `{{variables}}`.
+pub fn render_prompt(prompt: &str, variables: &HashMap<&str, String>) -> Result<String, String> {
+    let known: Vec<&str> = variables.keys().copied().collect();
+    check_variables(prompt, &known)?;
+
+    Ok(variable_pattern()
+        .replace_all(prompt, |capture: &regex::Captures| {
+            variables[&capture[1]].clone()
+        })
+        .into_owned())
+}
+
+/// Fills in a custom prompt for a conversation. Prompts that use neither the transcript nor the
+/// summary get the transcript appended, since the model would have nothing to go on otherwise.
+///
+/// A transcript over `budget` tokens isn't cut short, which would quietly leave out the end of the
+/// meeting. The prompt is refused instead, the summary covers the whole meeting.
+pub fn render_custom_prompt(
+    prompt: &str,
+    variables: &HashMap<&str, String>,
+    budget: usize,
+) -> Result<String, String> {
+    let used = prompt_variables(prompt);
+    let uses_transcript = used.iter().any(|name| name == "transcript");
+    let uses_summary = used.iter().any(|name| name == "summary");
+
+    let rendered = if uses_transcript || uses_summary {
+        render_prompt(prompt, variables)?
+    } else {
+        render_prompt(
+            &format!("{}\n\ntranscript: {{{{transcript}}}}", prompt.trim_end()),
+            variables,
+        )?
+    };
+    let transcript_tokens = variables
+        .get("transcript")
+        .map_or(0, |transcript| estimate_tokens(transcript));
+    if (uses_transcript || !uses_summary) && transcript_tokens > budget {
+        return Err(format!(
+            "The transcript is too long for the model ({} of {} tokens), use {{{{summary}}}} instead of {{{{transcript}}}}",
+            transcript_tokens, budget
+        ));
+    }
+
+    Ok(rendered)
+}
+
+/// The values of [`CONVERSATION_VARIABLES`].
+pub fn conversation_variables(
+    conversation: &conversation::Model,
+    transcription: &TranscriptionJSON,
+    summary: Option<&SummaryJSON>,
+) -> HashMap<&'static str, String> {
+    let mut participants = conversation.participant_list();
+    if participants.is_empty() {
+        participants = transcription
+            .speakers()
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+    }
+
+    HashMap::from([
+        ("title", conversation.title.clone()),
+        (
+            "date",
+            conversation
+                .created_at
+                .get(..10)
+                .unwrap_or_default()
+                .to_string(),
+        ),
+        ("participants", participants.join(", ")),
+        (
+            "transcript",
+            // Whole speaker turns, the transcript isn't split.
+            transcript_pieces(transcription, usize::MAX).join("\n"),
+        ),
+        (
+            "summary",
+            summary.map(SummaryJSON::to_markdown).unwrap_or_default(),
+        ),
+    ])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn variables(transcript: &str) -> HashMap<&'static str, String> {
+        HashMap::from([
+            ("title", "Weekly sync".to_string()),
+            ("date", "2024-06-21".to_string()),
+            ("participants", "Ann, Bo".to_string()),
+            ("transcript", transcript.to_string()),
+            ("summary", "We agreed on the budget.".to_string()),
+        ])
+    }
+
+    #[test]
+    fn rejects_unknown_variables() {
+        let error = check_variables("Notes for {{atendees}}", &CONVERSATION_VARIABLES).unwrap_err();
+
+        assert!(
+            error.starts_with("Unknown variable {{atendees}}"),
+            "{}",
+            error
+        );
+        assert!(error.contains("{{participants}}"), "{}", error);
+        assert!(render_custom_prompt("Hi {{nope}}", &variables("Hello."), 100).is_err());
+    }
+
+    #[test]
+    fn allows_spaces_inside_braces() {
+        let rendered = render_custom_prompt(
+            "Minutes of {{ title }}: {{ transcript }}",
+            &variables("Hello."),
+            100,
+        )
+        .unwrap();
+
+        assert_eq!(rendered, "Minutes of Weekly sync: Hello.");
+    }
+
+    #[test]
+    fn appends_the_transcript_when_the_prompt_has_nothing_to_go_on() {
+        let rendered =
+            render_custom_prompt("Write a follow-up email.  ", &variables("Hello."), 100).unwrap();
+
+        assert_eq!(rendered, "Write a follow-up email.\n\ntranscript: Hello.");
+    }
+
+    #[test]
+    fn leaves_prompts_on_the_summary_alone() {
+        let rendered =
+            render_custom_prompt("Tweet this: {{summary}}", &variables("Hello."), 100).unwrap();
+
+        assert_eq!(rendered, "Tweet this: We agreed on the budget.");
+    }
+
+    #[test]
+    fn does_not_expand_variables_in_the_transcript() {
+        let rendered =
+            render_custom_prompt("{{transcript}}", &variables("Type {{summary}} here."), 100)
+                .unwrap();
+
+        assert_eq!(rendered, "Type {{summary}} here.");
+    }
+
+    #[test]
+    fn refuses_a_transcript_over_the_budget() {
+        let long = "word ".repeat(100);
+
+        let error = render_custom_prompt("Action items: {{transcript}}", &variables(&long), 10)
+            .unwrap_err();
+        assert!(error.contains("use {{summary}}"), "{}", error);
+        assert!(render_custom_prompt("Anything?", &variables(&long), 10).is_err());
+        assert!(render_custom_prompt("Recap {{summary}}", &variables(&long), 10).is_ok());
+    }
+
+    #[test]
+    fn renders_library_prompts() {
+        let request = PromptLibrary::defaults()
+            .request(TITLE_PROMPT, &[("summary", "Budget review")])
+            .unwrap();
+
+        assert!(request.prompt.ends_with(": Budget review"));
+        assert_eq!(request.model, None);
+        assert!(PromptLibrary::defaults()
+            .request(TITLE_PROMPT, &[("transcript", "Hello.")])
+            .is_err());
+    }
+
+    fn packed(key: &str, template: &str, model: Option<&str>) -> PackedPrompt {
+        PackedPrompt {
+            key: key.to_string(),
+            template: template.to_string(),
+            model: model.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn ships_valid_prompts() {
+        for prompt in &DEFAULT_PROMPTS {
+            validate_prompt(prompt.key, prompt.template, None).unwrap();
+        }
+    }
+
+    #[test]
+    fn rejects_prompts_leaving_out_a_variable() {
+        let error = validate_prompt(SUMMARY_PROMPT, "Summarize: {{transcript}}", None)
+            .map(|_| ())
+            .unwrap_err();
+
+        assert!(error.contains("{{instructions}}"), "{}", error);
+        assert!(validate_prompt(TITLE_PROMPT, "A title for {{ summary }}", None).is_ok());
+    }
+
+    #[test]
+    fn rejects_a_model_for_combined_summaries() {
+        let template = "Parts of one meeting:\n{{summaries}}";
+
+        assert!(validate_prompt(COMBINE_SUMMARIES_PROMPT, template, Some("llama3")).is_err());
+        assert!(validate_prompt(COMBINE_SUMMARIES_PROMPT, template, Some(" ")).is_ok());
+        assert!(validate_prompt(TITLE_PROMPT, "Title: {{summary}}", Some("llama3")).is_ok());
+    }
+
+    #[test]
+    fn rejects_the_whole_pack_when_one_prompt_is_invalid() {
+        let mut pack = PromptPack {
+            prompts: vec![
+                packed(TITLE_PROMPT, "Title: {{summary}}", Some("llama3")),
+                packed(CHUNK_SUMMARY_PROMPT, "Key points: {{transcript}}", None),
+            ],
+        };
+        assert!(validate_pack(&pack).is_ok());
+
+        pack.prompts
+            .push(packed(ACTION_ITEMS_PROMPT, "To-dos from {{summary}}", None));
+        assert!(validate_pack(&pack).is_err());
+
+        pack.prompts.pop();
+        pack.prompts
+            .push(packed("agenda", "Agenda: {{transcript}}", None));
+        assert!(validate_pack(&pack).is_err());
+    }
+}
@@ -8,18 +8,16 @@ use std::sync::{
     atomic::{AtomicBool, Ordering},
     Arc,
 };
-use std::time::Duration;
 use tauri::async_runtime::Mutex;
-use tauri::State;
+use tauri::{Manager, State};
 use tokio::process::Command;
 
 // Removed unused imports
 // use mac_notification_sys::{get_bundle_identifier_or_default, send_notification, set_application};
 // use crate::commands::conversation;
 // use crate::summarize::{generate_action_items, generate_title, summarize};
+use crate::jobs::{JobQueue, RECORDING_STAGES};
 use crate::media::MediaRecorder;
-use crate::summarize::summarize_and_write;
-use crate::transcribe::{load_transcription, transcribe_wav_file_and_write};
 use crate::utils::ffmpeg_path_as_str;
 use crate::DeviceState;
 
@@ -120,98 +118,82 @@ pub async fn start_recording(
 }
 use tokio::io::AsyncBufReadExt;
 
-async fn concat_segments(
-    audio_chunks_dir: &PathBuf,
-) -> Result<tokio::process::Child, std::io::Error> {
-    let ffmpeg_binary_path_str = ffmpeg_path_as_str().unwrap().to_owned();
-
+pub async fn concat_segments(audio_chunks_dir: &Path) -> Result<(), String> {
     let segment_list_path = audio_chunks_dir.join("segment_list.txt");
 
     // Read each line (segment file path) from the segment list file
-    let segment_files: Vec<String> = match std::fs::read_to_string(&segment_list_path) {
-        Ok(content) => Some(
-            content
-                .lines()
-                .map(|s| s.trim().to_string())
-                .collect::<Vec<String>>(),
-        ),
-        Err(e) => {
-            info!("Failed to read segment list: {}", e);
-            None
-        }
-    }
-    .expect("Failed to read segment list. This should never happen. Please report this bug.");
+    let segment_files: Vec<String> = std::fs::read_to_string(&segment_list_path)
+        .map_err(|e| {
+            format!(
+                "Failed to read segment list {}: {}",
+                segment_list_path.display(),
+                e
+            )
+        })?
+        .lines()
+        .map(|s| s.trim().to_string())
+        .collect();
 
     // Ensure there are segments to combine
     if segment_files.is_empty() {
         info!("No segments found to combine.");
     }
 
-    let concat_file_path = audio_chunks_dir.join("concat.txt").clone();
+    let concat_file_path = audio_chunks_dir.join("concat.txt");
     let combined_output_file_path = audio_chunks_dir.join("combined.wav");
 
-    write_concat_file(&concat_file_path, &segment_files).expect("error writing concat file");
+    write_concat_file(&concat_file_path, &segment_files)
+        .map_err(|e| format!("Failed to write {}: {}", concat_file_path.display(), e))?;
 
-    let args = vec![
+    run_ffmpeg(&[
+        "-y",
         "-f",
         "concat",
         "-safe",
         "0",
         "-i",
-        concat_file_path.to_str().unwrap(),
+        path_str(&concat_file_path)?,
         "-c",
         "copy",
-        combined_output_file_path.to_str().unwrap(),
-    ];
-
-    // Print the generated args for debugging
-    info!("FFmpeg args: {:?}", args);
-
-    let mut process = Command::new(ffmpeg_binary_path_str).args(args).spawn()?;
-
-    if let Some(process_stderr) = process.stderr.take() {
-        tokio::spawn(async move {
-            use tokio::io::BufReader;
-
-            let mut process_reader = BufReader::new(process_stderr).lines();
-            while let Ok(Some(line)) = process_reader.next_line().await {
-                info!("FFmpeg process STDERR: {}", line);
-            }
-        });
-    }
-
-    process.wait().await?;
-    Ok(process)
+        path_str(&combined_output_file_path)?,
+    ])
+    .await
 }
 
 //ffmpeg -i stream1_combined.wav -i stream2_combined.wav -filter_complex "[0:a][1:a]amerge=inputs=2,pan=mono|c0=.5*c0+.5*c1[aout]" -map "[aout]" -c:a pcm_s16le output_mono.wav
-async fn combine_segments(
-    audio_chunks_dir: &PathBuf,
-) -> Result<tokio::process::Child, std::io::Error> {
-    let ffmpeg_binary_path_str = ffmpeg_path_as_str().unwrap().to_owned();
-
+pub async fn combine_segments(audio_chunks_dir: &Path) -> Result<(), String> {
     let input_concat_file = audio_chunks_dir.join("input").join("combined.wav");
     let output_concat_file = audio_chunks_dir.join("output").join("combined.wav");
     let combined_output_file_path = audio_chunks_dir.join("combined.wav");
 
-    let args = vec![
+    run_ffmpeg(&[
+        "-y",
         "-i",
-        input_concat_file.to_str().unwrap(),
+        path_str(&input_concat_file)?,
         "-i",
-        output_concat_file.to_str().unwrap(),
+        path_str(&output_concat_file)?,
         "-filter_complex",
         "[0:a][1:a]amerge=inputs=2,pan=mono|c0=.5*c0+.5*c1[aout]",
         "-map",
         "[aout]",
         "-c:a",
         "pcm_s16le",
-        combined_output_file_path.to_str().unwrap(),
-    ];
+        path_str(&combined_output_file_path)?,
+    ])
+    .await
+}
+
+/// Runs ffmpeg to completion, failing when it exits with an error so the job is retried.
+async fn run_ffmpeg(args: &[&str]) -> Result<(), String> {
+    let ffmpeg_binary_path_str = ffmpeg_path_as_str()?;
 
     // Print the generated args for debugging
     info!("FFmpeg args: {:?}", args);
 
-    let mut process = Command::new(ffmpeg_binary_path_str).args(args).spawn()?;
+    let mut process = Command::new(ffmpeg_binary_path_str)
+        .args(args)
+        .spawn()
+        .map_err(|e| format!("Failed to start FFmpeg: {}", e))?;
 
     if let Some(process_stderr) = process.stderr.take() {
         tokio::spawn(async move {
@@ -224,16 +206,29 @@ async fn combine_segments(
         });
     }
 
-    process.wait().await?;
-    Ok(process)
+    let status = process
+        .wait()
+        .await
+        .map_err(|e| format!("Failed to wait for FFmpeg: {}", e))?;
+    if !status.success() {
+        return Err(match status.code() {
+            Some(code) => format!("FFmpeg exited with code {}", code),
+            None => "FFmpeg was killed by a signal".to_string(),
+        });
+    }
+
+    Ok(())
+}
+
+fn path_str(path: &Path) -> Result<&str, String> {
+    path.to_str()
+        .ok_or(format!("Path {} isn't valid UTF-8", path.display()))
 }
 
-fn write_concat_file(concat_file_path: &PathBuf, segment_files: &Vec<String>) -> io::Result<()> {
+fn write_concat_file(concat_file_path: &Path, segment_files: &[String]) -> io::Result<()> {
     let mut output_file = File::create(concat_file_path)?;
     for segment_file in segment_files {
-        output_file
-            .write_all(format!("file '{}'\n", segment_file).as_bytes())
-            .expect("error writing file");
+        output_file.write_all(format!("file '{}'\n", segment_file).as_bytes())?;
     }
     Ok(())
 }
@@ -277,43 +272,14 @@ pub async fn _stop_recording(
     //     tokio::time::sleep(Duration::from_millis(50)).await;
     // }
 
-    let data_dir = guard.data_dir.clone();
-    let recording_dir = data_dir
-        .expect("no data directory")
-        .join("chunks/audio")
-        .join(conversation_id.to_string());
-    let input_dir = recording_dir.join("input");
-    let output_dir = recording_dir.join("output");
-    concat_segments(&input_dir)
-        .await
-        .map_err(|e| e.to_string())?;
-    concat_segments(&output_dir)
-        .await
-        .map_err(|e| e.to_string())?;
-    combine_segments(&recording_dir)
-        .await
-        .map_err(|e| e.to_string())?;
-    tokio::time::sleep(Duration::from_millis(50)).await;
+    drop(guard);
 
-    info!("combined segments..");
-
-    let combined_audio_file = recording_dir.join("combined.wav");
-    let transcription_output_file = recording_dir.join("transcription.json");
-    let summary_output_file = recording_dir.join("summary.json");
-    transcribe_wav_file_and_write(handle, &combined_audio_file, &transcription_output_file)
-        .map_err(|e| e.to_string())?;
-    let transcription = load_transcription(transcription_output_file)
-        .await
-        .expect("Failed to load transcription");
-    summarize_and_write(
-        transcription.full_text.join(" CHANGE_SPEAKER_TOKEN "),
-        &summary_output_file,
-    )
-    .await
-    .expect("Couldn't generate summary");
+    // Processing happens on the job queue so the recording state is free for the next recording.
+    let job_queue = handle.state::<Arc<JobQueue>>();
+    job_queue
+        .enqueue(conversation_id as i32, &RECORDING_STAGES)
+        .await?;
 
-    // let action_items = generate_action_items(&summary);
-    // let title = generate_title(&summary);
     info!("All recordings and uploads stopped.");
 
     Ok(())
@@ -0,0 +1,654 @@
+use std::collections::HashMap;
+use std::fs::{read, write};
+use std::path::Path;
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chacha20poly1305::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use entity::audio_redaction::AudioRedactionMethod;
+use entity::redaction::{self, RedactionKind};
+use log::{info, warn};
+use regex::Regex;
+use serde::Serialize;
+use service::{sea_orm::DatabaseConnection, Mutation, Query};
+
+use crate::settings::{RedactionMode, RedactionSettings, Settings};
+use crate::transcribe::{
+    full_text_from_segments, TranscriptSegment, TranscriptWord, TranscriptionJSON,
+};
+
+/// Key for reversible redactions, created in the app data directory on first use.
+const KEY_FILE: &str = "redaction.key";
+const NONCE_LENGTH: usize = 12;
+
+const EMAIL_PATTERN: &str = r"(?i)\b[a-z0-9._%+-]+@[a-z0-9-]+(?:\.[a-z0-9-]+)*\.[a-z]{2,}\b";
+const SECRET_PATTERN: &str = r"\b(?:sk-(?:proj-)?[A-Za-z0-9_-]{20,}|gh[pousr]_[A-Za-z0-9]{36,}|github_pat_[A-Za-z0-9_]{22,}|AKIA[0-9A-Z]{16}|xox[abprs]-[A-Za-z0-9-]{10,}|AIza[0-9A-Za-z_-]{35}|eyJ[A-Za-z0-9_-]{10,}\.[A-Za-z0-9_-]{10,}\.[A-Za-z0-9_-]{10,})";
+const CARD_NUMBER_PATTERN: &str = r"\b(?:\d[ -]?){12,18}\d\b";
+const IBAN_PATTERN: &str = r"\b[A-Z]{2}\d{2}(?: ?[A-Z0-9]){11,30}\b";
+/// An international number starting with `+` and its country code, or a national one written in
+/// groups, so plain digit runs aren't taken for phone numbers.
+const PHONE_NUMBER_PATTERN: &str = r"(?:\+\d{1,3}[\s.-]?(?:\(\d{1,4}\)[\s.-]?)?\d{1,4}(?:[\s.-]?\d{2,4}){1,5}|(?:\(\d{2,4}\)[\s.-]?|\b\d{2,5}[\s.-])\d{2,4}(?:[\s.-]\d{2,4}){0,4})\b";
+
+struct Detector {
+    kind: RedactionKind,
+    pattern: Regex,
+    /// Checks a match further, e.g. its checksum.
+    validate: fn(&str) -> bool,
+}
+
+/// Start, end and kind of a value found in a text.
+type Detection = (usize, usize, RedactionKind);
+
+/// Masks personal data and secrets in a conversation's text.
+///
+/// Each distinct value gets a numbered placeholder like `[EMAIL_1]` that it keeps for the whole
+/// conversation, so the summary model can still tell values apart. New placeholders are held until
+/// `save`, which has to run before the redacted text is written.
+pub struct Redactor {
+    db: DatabaseConnection,
+    conversation_id: i32,
+    detectors: Vec<Detector>,
+    /// Set when redaction is reversible.
+    cipher: Option<ChaCha20Poly1305>,
+    audio_method: Option<AudioRedactionMethod>,
+    placeholders: HashMap<String, String>,
+    counts: HashMap<RedactionKind, usize>,
+    pending: Vec<redaction::Model>,
+    spans: Vec<RedactedSpan>,
+}
+
+/// Where a redacted value was said, in milliseconds.
+#[derive(Clone)]
+pub struct RedactedSpan {
+    pub t0: i64,
+    pub t1: i64,
+    pub kind: RedactionKind,
+    pub placeholder: String,
+}
+
+impl Redactor {
+    pub async fn load(
+        db: &DatabaseConnection,
+        data_dir: &Path,
+        conversation_id: i32,
+    ) -> Result<Redactor, String> {
+        let settings = Settings::load(db).await?.redaction;
+        let detectors = if settings.enabled {
+            detectors(&settings)?
+        } else {
+            Vec::new()
+        };
+        // Manual redactions still go through when the detectors are off.
+        let cipher = if settings.mode == RedactionMode::Reversible {
+            Some(load_cipher(data_dir)?)
+        } else {
+            None
+        };
+
+        let mut redactor = Redactor {
+            db: db.clone(),
+            conversation_id,
+            detectors,
+            cipher,
+            audio_method: settings.audio_method,
+            placeholders: HashMap::new(),
+            counts: HashMap::new(),
+            pending: Vec::new(),
+            spans: Vec::new(),
+        };
+
+        // Reprocessing a conversation reuses the placeholders it already has.
+        let existing = Query::find_redactions(db, conversation_id)
+            .await
+            .map_err(|e| e.to_string())?;
+        for redaction in existing {
+            *redactor.counts.entry(redaction.kind).or_default() += 1;
+            let original = match (&redactor.cipher, &redaction.original) {
+                (Some(cipher), Some(original)) => decrypt(cipher, original).ok(),
+                _ => None,
+            };
+            if let Some(original) = original {
+                redactor
+                    .placeholders
+                    .insert(original, redaction.placeholder);
+            }
+        }
+
+        Ok(redactor)
+    }
+
+    pub fn redact(&mut self, text: &str) -> String {
+        let detections = self.detect(text);
+        self.replace(text, &detections)
+    }
+
+    /// Redacts the kept and dropped segments, words included, and rebuilds the full text.
+    pub fn redact_transcription(&mut self, transcription: &mut TranscriptionJSON) {
+        if self.detectors.is_empty() {
+            return;
+        }
+
+        for segment in transcription.segments.iter_mut() {
+            self.redact_segment(segment);
+        }
+        for dropped in transcription.dropped_segments.iter_mut() {
+            self.redact_segment(&mut dropped.segment);
+        }
+
+        transcription.full_text = full_text_from_segments(&transcription.segments);
+    }
+
+    /// Stores the placeholders handed out since the last save.
+    pub async fn save(&mut self) -> Result<(), String> {
+        let pending = std::mem::take(&mut self.pending);
+        if !pending.is_empty() {
+            info!(
+                "Redacted {} values in conversation {}",
+                pending.len(),
+                self.conversation_id
+            );
+        }
+
+        Mutation::create_redactions(&self.db, pending)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Finds values to redact, earlier detectors winning where matches overlap.
+    fn detect(&self, text: &str) -> Vec<Detection> {
+        let mut detections: Vec<Detection> = Vec::new();
+
+        for detector in &self.detectors {
+            for found in detector.pattern.find_iter(text) {
+                let overlaps = detections
+                    .iter()
+                    .any(|(start, end, _)| found.start() < *end && *start < found.end());
+                if !overlaps && (detector.validate)(found.as_str()) {
+                    detections.push((found.start(), found.end(), detector.kind));
+                }
+            }
+        }
+
+        detections.sort_by_key(|(start, _, _)| *start);
+        detections
+    }
+
+    fn replace(&mut self, text: &str, detections: &[Detection]) -> String {
+        let mut redacted = String::with_capacity(text.len());
+        let mut last = 0;
+
+        for (start, end, kind) in detections {
+            redacted.push_str(&text[last..*start]);
+            redacted.push_str(&self.placeholder(&text[*start..*end], *kind));
+            last = *end;
+        }
+        redacted.push_str(&text[last..]);
+
+        redacted
+    }
+
+    fn placeholder(&mut self, original: &str, kind: RedactionKind) -> String {
+        if let Some(placeholder) = self.placeholders.get(original) {
+            return placeholder.clone();
+        }
+
+        let count = self.counts.entry(kind).or_default();
+        *count += 1;
+        let placeholder = format!("[{}_{}]", label(kind), count);
+
+        let encrypted = match &self.cipher {
+            Some(cipher) => match encrypt(cipher, original) {
+                Ok(encrypted) => Some(encrypted),
+                Err(e) => {
+                    warn!(
+                        "Failed to encrypt {}, it can't be revealed: {}",
+                        placeholder, e
+                    );
+                    None
+                }
+            },
+            None => None,
+        };
+        self.pending.push(redaction::Model {
+            id: 0,
+            conversation_id: self.conversation_id,
+            kind,
+            placeholder: placeholder.clone(),
+            original: encrypted,
+            created_at: String::new(),
+        });
+        self.placeholders
+            .insert(original.to_string(), placeholder.clone());
+
+        placeholder
+    }
+
+    /// Redacts every occurrence of text the user marked in a segment.
+    pub fn redact_literal(&mut self, segment: &mut TranscriptSegment, literal: &str) {
+        if literal.is_empty() {
+            return;
+        }
+
+        let detections: Vec<Detection> = segment
+            .text
+            .match_indices(literal)
+            .map(|(start, found)| (start, start + found.len(), RedactionKind::Manual))
+            .collect();
+        self.redact_detections(segment, &detections);
+    }
+
+    /// Placeholder for text the user marked, for places without timing like typed edits.
+    pub fn manual_placeholder(&mut self, literal: &str) -> String {
+        self.placeholder(literal, RedactionKind::Manual)
+    }
+
+    /// Spans of the transcript redacted since the last call, for masking the audio.
+    pub fn take_spans(&mut self) -> Vec<RedactedSpan> {
+        std::mem::take(&mut self.spans)
+    }
+
+    /// How the audio of redacted spans is covered, unset when it's left alone.
+    pub fn audio_method(&self) -> Option<AudioRedactionMethod> {
+        self.audio_method
+    }
+
+    fn redact_segment(&mut self, segment: &mut TranscriptSegment) {
+        let detections = self.detect(&segment.text);
+        self.redact_detections(segment, &detections);
+    }
+
+    fn redact_detections(&mut self, segment: &mut TranscriptSegment, detections: &[Detection]) {
+        if detections.is_empty() {
+            return;
+        }
+
+        let (words, times) = self.redact_words(&segment.text, &segment.words, detections);
+        for (index, (start, end, kind)) in detections.iter().enumerate() {
+            // Without words to go by, the time is estimated from the position in the text.
+            let (t0, t1) = times[index].unwrap_or_else(|| {
+                let length = segment.text.len().max(1) as i64;
+                let duration = segment.t1 - segment.t0;
+                (
+                    segment.t0 + duration * *start as i64 / length,
+                    segment.t0 + duration * *end as i64 / length,
+                )
+            });
+            let placeholder = self.placeholder(&segment.text[*start..*end], *kind);
+            self.spans.push(RedactedSpan {
+                t0,
+                t1,
+                kind: *kind,
+                placeholder,
+            });
+        }
+
+        segment.words = words;
+        segment.text = self.replace(&segment.text, detections);
+    }
+
+    /// Replaces the words making up a redacted value with a single word holding its placeholder
+    /// and spanning their time. Also returns the time each detection was found at in the words.
+    fn redact_words(
+        &mut self,
+        text: &str,
+        words: &[TranscriptWord],
+        detections: &[Detection],
+    ) -> (Vec<TranscriptWord>, Vec<Option<(i64, i64)>>) {
+        let mut redacted: Vec<TranscriptWord> = Vec::with_capacity(words.len());
+        let mut times: Vec<Option<(i64, i64)>> = vec![None; detections.len()];
+        let mut cursor = 0;
+        let mut previous_detection = None;
+
+        for word in words {
+            let needle = word.text.trim();
+            let position = text[cursor..]
+                .find(needle)
+                .filter(|_| !needle.is_empty())
+                .map(|offset| cursor + offset);
+            let Some(start) = position else {
+                // Words that don't line up with the text are checked on their own.
+                redacted.push(TranscriptWord {
+                    text: self.redact(&word.text),
+                    ..word.clone()
+                });
+                continue;
+            };
+            let end = start + needle.len();
+            cursor = end;
+
+            match detections
+                .iter()
+                .position(|(d_start, d_end, _)| start < *d_end && *d_start < end)
+            {
+                Some(index) if previous_detection == Some(index) => {
+                    if let Some(previous) = redacted.last_mut() {
+                        previous.t1 = word.t1;
+                    }
+                    if let Some(time) = times[index].as_mut() {
+                        time.1 = word.t1;
+                    }
+                }
+                Some(index) => {
+                    let (d_start, d_end, kind) = detections[index];
+                    let leading = &word.text[..word.text.len() - word.text.trim_start().len()];
+                    redacted.push(TranscriptWord {
+                        text: format!(
+                            "{}{}",
+                            leading,
+                            self.placeholder(&text[d_start..d_end], kind)
+                        ),
+                        ..word.clone()
+                    });
+                    times[index] = Some((word.t0, word.t1));
+                    previous_detection = Some(index);
+                }
+                None => redacted.push(word.clone()),
+            }
+        }
+
+        (redacted, times)
+    }
+}
+
+/// A redaction with its original value decrypted.
+#[derive(Serialize)]
+pub struct RevealedRedaction {
+    #[serde(flatten)]
+    pub redaction: redaction::Model,
+    /// Unset when the value was redacted irreversibly.
+    pub original: Option<String>,
+}
+
+/// Decrypts a conversation's reversible redactions for its owner.
+pub async fn reveal_redactions(
+    db: &DatabaseConnection,
+    data_dir: &Path,
+    conversation_id: i32,
+) -> Result<Vec<RevealedRedaction>, String> {
+    let redactions = Query::find_redactions(db, conversation_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let cipher = if redactions
+        .iter()
+        .any(|redaction| redaction.original.is_some())
+    {
+        Some(load_cipher(data_dir)?)
+    } else {
+        None
+    };
+
+    redactions
+        .into_iter()
+        .map(|redaction| {
+            let original = match (&cipher, &redaction.original) {
+                (Some(cipher), Some(original)) => Some(decrypt(cipher, original)?),
+                _ => None,
+            };
+            Ok(RevealedRedaction {
+                redaction,
+                original,
+            })
+        })
+        .collect()
+}
+
+fn detectors(settings: &RedactionSettings) -> Result<Vec<Detector>, String> {
+    let mut detectors = Vec::new();
+    let mut add = |enabled: bool,
+                   kind: RedactionKind,
+                   pattern: &str,
+                   validate: fn(&str) -> bool|
+     -> Result<(), String> {
+        if enabled {
+            detectors.push(Detector {
+                kind,
+                pattern: Regex::new(pattern).map_err(|e| e.to_string())?,
+                validate,
+            });
+        }
+        Ok(())
+    };
+
+    let mut terms: Vec<String> = settings
+        .terms
+        .iter()
+        .map(|term| term.trim())
+        .filter(|term| !term.is_empty())
+        .map(regex::escape)
+        .collect();
+    // Longest first, so a term containing another one is matched whole.
+    terms.sort_by_key(|term| std::cmp::Reverse(term.len()));
+    let terms_pattern = format!(r"(?i)\b(?:{})\b", terms.join("|"));
+
+    add(
+        !terms.is_empty(),
+        RedactionKind::Term,
+        &terms_pattern,
+        |_| true,
+    )?;
+    add(
+        settings.secrets,
+        RedactionKind::Secret,
+        SECRET_PATTERN,
+        |_| true,
+    )?;
+    add(settings.emails, RedactionKind::Email, EMAIL_PATTERN, |_| {
+        true
+    })?;
+    add(settings.ibans, RedactionKind::Iban, IBAN_PATTERN, is_iban)?;
+    add(
+        settings.card_numbers,
+        RedactionKind::CardNumber,
+        CARD_NUMBER_PATTERN,
+        is_card_number,
+    )?;
+    add(
+        settings.phone_numbers,
+        RedactionKind::PhoneNumber,
+        PHONE_NUMBER_PATTERN,
+        is_phone_number,
+    )?;
+
+    Ok(detectors)
+}
+
+fn label(kind: RedactionKind) -> &'static str {
+    match kind {
+        RedactionKind::Email => "EMAIL",
+        RedactionKind::PhoneNumber => "PHONE",
+        RedactionKind::CardNumber => "CARD",
+        RedactionKind::Iban => "IBAN",
+        RedactionKind::Secret => "SECRET",
+        RedactionKind::Term | RedactionKind::Manual => "REDACTED",
+    }
+}
+
+fn digits(text: &str) -> Vec<u32> {
+    text.chars().filter_map(|c| c.to_digit(10)).collect()
+}
+
+/// 13 to 19 digits passing the Luhn check.
+fn is_card_number(text: &str) -> bool {
+    let digits = digits(text);
+    if !(13..=19).contains(&digits.len()) {
+        return false;
+    }
+
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(index, digit)| match index % 2 {
+            0 => *digit,
+            _ if *digit * 2 > 9 => *digit * 2 - 9,
+            _ => *digit * 2,
+        })
+        .sum();
+
+    sum.is_multiple_of(10)
+}
+
+/// Country code, check digits and account passing the ISO 13616 mod 97 check.
+fn is_iban(text: &str) -> bool {
+    let iban: String = text.chars().filter(|c| !c.is_whitespace()).collect();
+    if !(15..=34).contains(&iban.len()) {
+        return false;
+    }
+
+    let (head, tail) = iban.split_at(4);
+    let mut remainder: u32 = 0;
+    for c in tail.chars().chain(head.chars()) {
+        let Some(value) = c.to_digit(36) else {
+            return false;
+        };
+        remainder = if value < 10 {
+            (remainder * 10 + value) % 97
+        } else {
+            (remainder * 100 + value) % 97
+        };
+    }
+
+    remainder == 1
+}
+
+/// E.164 allows up to 15 digits, anything under 7 is more likely a plain number. Dates are
+/// grouped like short numbers, so they're left alone.
+fn is_phone_number(text: &str) -> bool {
+    (7..=15).contains(&digits(text).len()) && (text.starts_with('+') || !is_date(text))
+}
+
+/// `2024-06-21`, `21.06.2024`, `06/21/24` and the like.
+fn is_date(text: &str) -> bool {
+    let groups: Vec<&str> = text
+        .split(|c: char| !c.is_ascii_digit())
+        .filter(|group| !group.is_empty())
+        .collect();
+    let is_day_and_month = |day: &str, month: &str| {
+        day.len() <= 2
+            && month.len() <= 2
+            && day.parse().is_ok_and(|day: u32| (1..=31).contains(&day))
+            && month
+                .parse()
+                .is_ok_and(|month: u32| (1..=12).contains(&month))
+    };
+
+    match groups.as_slice() {
+        [year, month, day] if year.len() == 4 => is_day_and_month(day, month),
+        [first, second, year] if year.len() == 2 || year.len() == 4 => {
+            is_day_and_month(first, second) || is_day_and_month(second, first)
+        }
+        _ => false,
+    }
+}
+
+fn load_cipher(data_dir: &Path) -> Result<ChaCha20Poly1305, String> {
+    let key_path = data_dir.join(KEY_FILE);
+
+    let key = if key_path.exists() {
+        let key =
+            read(&key_path).map_err(|e| format!("Failed to read {}: {}", key_path.display(), e))?;
+        if key.len() != 32 {
+            return Err(format!("{} isn't a valid key", key_path.display()));
+        }
+        key
+    } else {
+        let key = ChaCha20Poly1305::generate_key(&mut OsRng);
+        write(&key_path, key)
+            .map_err(|e| format!("Failed to write {}: {}", key_path.display(), e))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&key_path, std::fs::Permissions::from_mode(0o600))
+                .map_err(|e| e.to_string())?;
+        }
+        key.to_vec()
+    };
+
+    Ok(ChaCha20Poly1305::new(Key::from_slice(&key)))
+}
+
+fn encrypt(cipher: &ChaCha20Poly1305, plaintext: &str) -> Result<String, String> {
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext.as_bytes())
+        .map_err(|e| e.to_string())?;
+
+    Ok(STANDARD.encode([nonce.as_slice(), &ciphertext].concat()))
+}
+
+fn decrypt(cipher: &ChaCha20Poly1305, encoded: &str) -> Result<String, String> {
+    let bytes = STANDARD.decode(encoded).map_err(|e| e.to_string())?;
+    if bytes.len() < NONCE_LENGTH {
+        return Err("Encrypted redaction is too short".to_string());
+    }
+
+    let (nonce, ciphertext) = bytes.split_at(NONCE_LENGTH);
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| "Failed to decrypt redaction, the key may have changed".to_string())?;
+
+    String::from_utf8(plaintext).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn phone_numbers(text: &str) -> Vec<&str> {
+        Regex::new(PHONE_NUMBER_PATTERN)
+            .unwrap()
+            .find_iter(text)
+            .map(|found| found.as_str())
+            .filter(|found| is_phone_number(found))
+            .collect()
+    }
+
+    #[test]
+    fn accepts_card_numbers_passing_luhn() {
+        assert!(is_card_number("4111 1111 1111 1111"));
+        assert!(is_card_number("5500-0000-0000-0004"));
+        assert!(is_card_number("378282246310005"));
+    }
+
+    #[test]
+    fn rejects_card_numbers_failing_luhn() {
+        assert!(!is_card_number("4111 1111 1111 1112"));
+        assert!(!is_card_number("1234 5678 9012 3456"));
+        assert!(!is_card_number("4111 1111 111"));
+    }
+
+    #[test]
+    fn accepts_valid_ibans() {
+        assert!(is_iban("DE89 3704 0044 0532 0130 00"));
+        assert!(is_iban("GB82WEST12345698765432"));
+    }
+
+    #[test]
+    fn rejects_ibans_with_wrong_check_digits() {
+        assert!(!is_iban("DE89 3704 0044 0532 0130 01"));
+        assert!(!is_iban("GB00WEST12345698765432"));
+        assert!(!is_iban("DE89 3704"));
+    }
+
+    #[test]
+    fn finds_phone_numbers() {
+        assert_eq!(
+            phone_numbers("Call +49 151 2345 6789 or +1 (415) 555-0132."),
+            ["+49 151 2345 6789", "+1 (415) 555-0132"]
+        );
+        assert_eq!(phone_numbers("It's 555-123-4567."), ["555-123-4567"]);
+        assert_eq!(phone_numbers("Try (030) 123 4567"), ["(030) 123 4567"]);
+    }
+
+    #[test]
+    fn ignores_dates_versions_and_digit_runs() {
+        assert!(phone_numbers("We ship on 2024-06-21.").is_empty());
+        assert!(phone_numbers("Due 21.06.2024 or 06/21/24").is_empty());
+        assert!(phone_numbers("Upgrade to 10.12.3 or 1.2.3.4").is_empty());
+        assert!(phone_numbers("Order 12345678 is late").is_empty());
+        assert!(phone_numbers("It costs 1500 euros").is_empty());
+    }
+}
@@ -0,0 +1,347 @@
+use std::collections::HashMap;
+
+use entity::{
+    conversation,
+    embedding::{self, EmbeddingSource},
+};
+use log::info;
+use serde::{Deserialize, Serialize};
+use service::{sea_orm::DatabaseConnection, Mutation, Query};
+
+use crate::llm::LlmProvider;
+use crate::summarize::{pack, transcript_pieces, SectionContent, SummaryJSON};
+use crate::transcribe::TranscriptionJSON;
+use crate::utils::parse_timestamp;
+
+/// Transcript passages are embedded about this long, in tokens.
+const PASSAGE_TOKENS: usize = 256;
+/// Passages sent to the model per request.
+const EMBED_BATCH_SIZE: usize = 32;
+const DEFAULT_LIMIT: usize = 20;
+const SNIPPET_CHARS: usize = 240;
+
+#[derive(Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SearchFilters {
+    pub conversation_ids: Option<Vec<i32>>,
+    /// Only conversations created on or after this date, e.g. `2024-06-01`.
+    pub created_after: Option<String>,
+    /// Only conversations created before this date.
+    pub created_before: Option<String>,
+    pub source: Option<EmbeddingSource>,
+    /// Only conversations with one of these tags.
+    pub tags: Option<Vec<String>>,
+    /// Only conversations one of these people spoke in.
+    pub participants: Option<Vec<String>>,
+    /// Passages returned at most.
+    pub limit: Option<usize>,
+}
+
+#[derive(Serialize)]
+pub struct SearchResults {
+    /// Conversations with a matching passage, best match first.
+    pub conversations: Vec<ConversationHit>,
+    /// The matching passages, best first.
+    pub segments: Vec<SegmentHit>,
+}
+
+#[derive(Serialize)]
+pub struct ConversationHit {
+    pub conversation: conversation::Model,
+    /// Score of the conversation's best passage.
+    pub score: f32,
+    pub hits: usize,
+}
+
+#[derive(Serialize)]
+pub struct SegmentHit {
+    pub conversation_id: i32,
+    pub conversation_title: String,
+    pub source: EmbeddingSource,
+    /// Where the passage starts in the recording, in milliseconds.
+    pub t0: Option<i64>,
+    pub snippet: String,
+    /// Cosine similarity to the query.
+    pub score: f32,
+}
+
+/// A passage found for a query, with its conversation.
+pub struct RankedPassage {
+    pub embedding: embedding::Model,
+    pub conversation: conversation::Model,
+    pub score: f32,
+}
+
+/// Replaces a conversation's passages in the search index with ones from its transcript and
+/// summary. Returns how many passages were indexed.
+pub async fn index_conversation(
+    db: &DatabaseConnection,
+    provider: &dyn LlmProvider,
+    conversation_id: i32,
+    transcription: &TranscriptionJSON,
+    summary: Option<&SummaryJSON>,
+) -> Result<usize, String> {
+    let mut passages: Vec<(EmbeddingSource, Option<i64>, String)> = pack(
+        &transcript_pieces(transcription, PASSAGE_TOKENS),
+        PASSAGE_TOKENS,
+    )
+    .into_iter()
+    .map(|passage| {
+        // Pieces start with their `[mm:ss]` timestamp when the transcript has segments.
+        let t0 = passage
+            .strip_prefix('[')
+            .and_then(|rest| rest.split_once(']'))
+            .and_then(|(timestamp, _)| parse_timestamp(timestamp));
+        (EmbeddingSource::Transcript, t0, passage)
+    })
+    .collect();
+    if let Some(summary) = summary {
+        passages.extend(
+            summary_passages(summary)
+                .into_iter()
+                .map(|passage| (EmbeddingSource::Summary, None, passage)),
+        );
+    }
+
+    let model = provider.name();
+    let mut embeddings = Vec::new();
+    for batch in passages.chunks(EMBED_BATCH_SIZE) {
+        let texts: Vec<String> = batch.iter().map(|(_, _, text)| text.clone()).collect();
+        let vectors = provider
+            .embed(&texts)
+            .await
+            .map_err(|e| format!("Failed to embed passages: {}", e))?;
+        if vectors.len() != batch.len() {
+            return Err(format!(
+                "Failed to embed passages: got {} vectors for {} passages",
+                vectors.len(),
+                batch.len()
+            ));
+        }
+
+        for ((source, t0, content), vector) in batch.iter().zip(vectors) {
+            embeddings.push(embedding::Model {
+                id: 0,
+                conversation_id,
+                source: *source,
+                t0: *t0,
+                content: content.clone(),
+                model: model.clone(),
+                vector: to_bytes(&normalize(vector)),
+                created_at: String::new(),
+            });
+        }
+    }
+
+    let count = embeddings.len();
+    Mutation::replace_embeddings(db, conversation_id, embeddings)
+        .await
+        .map_err(|e| e.to_string())?;
+    let speakers = transcription.speakers();
+    let participants = (!speakers.is_empty()).then(|| speakers.join(", "));
+    Mutation::set_conversation_participants(db, conversation_id, participants)
+        .await
+        .map_err(|e| e.to_string())?;
+    info!(
+        "Indexed {} passages of conversation {}",
+        count, conversation_id
+    );
+
+    Ok(count)
+}
+
+/// Passages of the indexed conversations closest in meaning to `query`, best first.
+pub async fn rank_passages(
+    db: &DatabaseConnection,
+    provider: &dyn LlmProvider,
+    query: &str,
+    filters: &SearchFilters,
+) -> Result<Vec<RankedPassage>, String> {
+    let conversation_ids = candidate_conversations(db, filters).await?;
+    if conversation_ids.as_ref().is_some_and(Vec::is_empty) {
+        return Ok(Vec::new());
+    }
+
+    let query_vector = provider
+        .embed(&[query.to_string()])
+        .await
+        .map_err(|e| format!("Failed to embed the query: {}", e))?
+        .into_iter()
+        .next()
+        .map(normalize)
+        .ok_or("The model returned no embedding for the query")?;
+
+    let candidates = Query::find_embeddings(
+        db,
+        &provider.name(),
+        conversation_ids,
+        filters.created_after.clone(),
+        filters.created_before.clone(),
+        filters.source,
+    )
+    .await
+    .map_err(|e| e.to_string())?;
+
+    let mut ranked: Vec<RankedPassage> = candidates
+        .into_iter()
+        .filter_map(|(embedding, conversation)| {
+            let score = dot(&query_vector, &from_bytes(&embedding.vector));
+            Some(RankedPassage {
+                conversation: conversation?,
+                embedding,
+                score,
+            })
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.score.total_cmp(&a.score));
+    ranked.truncate(filters.limit.unwrap_or(DEFAULT_LIMIT));
+
+    Ok(ranked)
+}
+
+/// The conversations `filters` leave to search, unset when that's all of them. Tags and
+/// participants are matched here, so only the passages of matching conversations are loaded.
+async fn candidate_conversations(
+    db: &DatabaseConnection,
+    filters: &SearchFilters,
+) -> Result<Option<Vec<i32>>, String> {
+    let has_filter = |wanted: &Option<Vec<String>>| wanted.as_ref().is_some_and(|w| !w.is_empty());
+    if !has_filter(&filters.tags) && !has_filter(&filters.participants) {
+        return Ok(filters.conversation_ids.clone());
+    }
+
+    let ids = Query::find_conversations(db)
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|conversation| {
+            filters
+                .conversation_ids
+                .as_ref()
+                .is_none_or(|ids| ids.contains(&conversation.id))
+                && matches_any(&conversation.tag_list(), &filters.tags)
+                && matches_any(&conversation.participant_list(), &filters.participants)
+        })
+        .map(|conversation| conversation.id)
+        .collect();
+
+    Ok(Some(ids))
+}
+
+pub async fn semantic_search(
+    db: &DatabaseConnection,
+    provider: &dyn LlmProvider,
+    query: &str,
+    filters: &SearchFilters,
+) -> Result<SearchResults, String> {
+    let ranked = rank_passages(db, provider, query, filters).await?;
+
+    let mut conversations: Vec<ConversationHit> = Vec::new();
+    let mut positions: HashMap<i32, usize> = HashMap::new();
+    for passage in &ranked {
+        match positions.get(&passage.conversation.id) {
+            // Passages come best first, so the first one sets the conversation's score.
+            Some(position) => conversations[*position].hits += 1,
+            None => {
+                positions.insert(passage.conversation.id, conversations.len());
+                conversations.push(ConversationHit {
+                    conversation: passage.conversation.clone(),
+                    score: passage.score,
+                    hits: 1,
+                });
+            }
+        }
+    }
+
+    let segments = ranked
+        .into_iter()
+        .map(|passage| SegmentHit {
+            conversation_id: passage.conversation.id,
+            conversation_title: passage.conversation.title,
+            source: passage.embedding.source,
+            t0: passage.embedding.t0,
+            snippet: snippet(&passage.embedding.content),
+            score: passage.score,
+        })
+        .collect();
+
+    Ok(SearchResults {
+        conversations,
+        segments,
+    })
+}
+
+/// Whether `values` has one of `wanted`, ignoring case. No filter matches everything.
+fn matches_any(values: &[String], wanted: &Option<Vec<String>>) -> bool {
+    match wanted {
+        Some(wanted) if !wanted.is_empty() => values
+            .iter()
+            .any(|value| wanted.iter().any(|w| w.trim().eq_ignore_ascii_case(value))),
+        _ => true,
+    }
+}
+
+/// A passage per summary section, with the action items as one more.
+fn summary_passages(summary: &SummaryJSON) -> Vec<String> {
+    let mut passages: Vec<String> = summary
+        .sections
+        .iter()
+        .map(|section| {
+            let content = match &section.content {
+                SectionContent::Text(text) => text.clone(),
+                SectionContent::List(items) => items.join("\n"),
+            };
+            format!("{}\n{}", section.title, content)
+        })
+        .collect();
+    if !summary.action_items.is_empty() {
+        let items: Vec<&str> = summary
+            .action_items
+            .iter()
+            .map(|item| item.title.as_str())
+            .collect();
+        passages.push(format!("Action items\n{}", items.join("\n")));
+    }
+
+    passages
+        .into_iter()
+        .filter(|passage| !passage.trim().is_empty())
+        .collect()
+}
+
+fn snippet(content: &str) -> String {
+    let content = content.split_whitespace().collect::<Vec<_>>().join(" ");
+    if content.chars().count() <= SNIPPET_CHARS {
+        return content;
+    }
+
+    let cut: String = content.chars().take(SNIPPET_CHARS).collect();
+    match cut.rsplit_once(' ') {
+        Some((words, _)) => format!("{}…", words),
+        None => format!("{}…", cut),
+    }
+}
+
+/// Scales a vector to unit length, so similarity is a dot product.
+fn normalize(vector: Vec<f32>) -> Vec<f32> {
+    let length = vector.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if length == 0.0 {
+        return vector;
+    }
+    vector.into_iter().map(|x| x / length).collect()
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b).map(|(a, b)| a * b).sum()
+}
+
+fn to_bytes(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|x| x.to_le_bytes()).collect()
+}
+
+fn from_bytes(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(4)
+        .map(|chunk| f32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+        .collect()
+}
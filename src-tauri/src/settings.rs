@@ -0,0 +1,300 @@
+use entity::audio_redaction::AudioRedactionMethod;
+use serde::{Deserialize, Serialize};
+use service::{sea_orm::DatabaseConnection, Mutation, Query};
+
+const SETTINGS_KEY: &str = "settings";
+
+/// User preferences, stored as a single JSON document in the `setting` table.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub transcription: TranscriptionSettings,
+    pub redaction: RedactionSettings,
+    pub llm: LlmSettings,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TranscriptionSettings {
+    pub backend: TranscriptionBackend,
+    /// Spoken language as an ISO 639-1 code, or `auto` to detect it. Unset leaves it to the
+    /// backend, which is English for the bundled model.
+    pub language: Option<String>,
+    /// Decoding parameters for the local backend.
+    pub decoding: DecodingSettings,
+    /// Minutes the local model stays loaded after its last use, 0 keeps it loaded.
+    pub unload_model_after_minutes: u64,
+    /// Text Whisper is primed with ahead of the glossary terms.
+    pub initial_prompt: String,
+    /// Replace known mis-hearings of glossary terms once transcription is done.
+    pub substitute_glossary_terms: bool,
+    /// Label segments with speakers from voice embeddings when the speaker model is installed.
+    pub diarize_speakers: bool,
+    pub hallucination_filter: HallucinationFilterSettings,
+}
+
+impl Default for TranscriptionSettings {
+    fn default() -> Self {
+        TranscriptionSettings {
+            backend: TranscriptionBackend::default(),
+            language: None,
+            decoding: DecodingSettings::default(),
+            unload_model_after_minutes: 10,
+            initial_prompt: String::new(),
+            substitute_glossary_terms: true,
+            diarize_speakers: true,
+            hallucination_filter: HallucinationFilterSettings::default(),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DecodingSettings {
+    pub strategy: DecodingStrategy,
+    /// CPU threads, whisper.cpp picks up to four when unset.
+    pub threads: Option<i32>,
+    /// Temperature of the first decoding attempt.
+    pub temperature: f32,
+    /// Step the temperature is raised by when a segment fails to decode cleanly, 0 disables the
+    /// fallback.
+    pub temperature_increment: f32,
+}
+
+impl Default for DecodingSettings {
+    fn default() -> Self {
+        DecodingSettings {
+            strategy: DecodingStrategy::default(),
+            threads: None,
+            temperature: 0.0,
+            temperature_increment: 0.2,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DecodingStrategy {
+    /// Best of `best_of` samples, taken at temperatures above zero.
+    Greedy { best_of: i32 },
+    /// Slower, usually a little more accurate.
+    BeamSearch { beam_size: i32 },
+}
+
+impl Default for DecodingStrategy {
+    fn default() -> Self {
+        DecodingStrategy::Greedy { best_of: 1 }
+    }
+}
+
+/// Thresholds for dropping segments Whisper made up over silence or music.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HallucinationFilterSettings {
+    pub enabled: bool,
+    /// Drop segments the backend thinks are more likely silence than speech. Only the remote
+    /// backends report it, local transcription is never filtered on it.
+    pub no_speech_threshold: f32,
+    /// Drop segments whose text compresses better than this, Whisper's sign of looping.
+    pub compression_ratio_threshold: f32,
+    /// How often a phrase or segment may repeat back to back before the rest is dropped, 0
+    /// doesn't drop repeats.
+    pub max_repeats: usize,
+    /// Phrases dropped when they make up a whole segment, on top of the built-in list.
+    pub blacklist: Vec<String>,
+}
+
+impl Default for HallucinationFilterSettings {
+    fn default() -> Self {
+        HallucinationFilterSettings {
+            enabled: true,
+            no_speech_threshold: 0.6,
+            compression_ratio_threshold: 2.4,
+            max_repeats: 4,
+            blacklist: Vec::new(),
+        }
+    }
+}
+
+/// Personal data and secrets masked out of transcripts and summaries before they're written or
+/// sent to a model.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RedactionSettings {
+    pub enabled: bool,
+    pub mode: RedactionMode,
+    pub emails: bool,
+    pub phone_numbers: bool,
+    /// Numbers passing the Luhn check.
+    pub card_numbers: bool,
+    /// IBANs passing the mod 97 check.
+    pub ibans: bool,
+    /// API keys and tokens in well-known formats.
+    pub secrets: bool,
+    /// Names, projects and anything else to mask wherever it comes up, matched as whole words.
+    pub terms: Vec<String>,
+    /// How redacted spans are covered in the stored audio, unset leaves the audio as recorded.
+    pub audio_method: Option<AudioRedactionMethod>,
+}
+
+impl Default for RedactionSettings {
+    fn default() -> Self {
+        RedactionSettings {
+            enabled: true,
+            mode: RedactionMode::default(),
+            emails: true,
+            phone_numbers: true,
+            card_numbers: true,
+            ibans: true,
+            secrets: true,
+            terms: Vec::new(),
+            audio_method: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RedactionMode {
+    /// Originals are kept encrypted with a key in the app data directory, so they can be revealed.
+    #[default]
+    Reversible,
+    /// Originals are discarded.
+    Irreversible,
+}
+
+/// Where speech is turned into text.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TranscriptionBackend {
+    /// Whisper run in-process on a model from the bundled models directory, the bundled
+    /// tinydiarize model when `model` is unset.
+    Local {
+        #[serde(default)]
+        model: Option<String>,
+    },
+    /// A whisper.cpp `server`, e.g. `http://192.168.1.10:8080`.
+    WhisperServer { url: String },
+    /// Any OpenAI-compatible `/v1/audio/transcriptions` endpoint.
+    #[serde(rename = "openai")]
+    OpenAi {
+        url: String,
+        model: String,
+        api_key: Option<String>,
+    },
+}
+
+impl Default for TranscriptionBackend {
+    fn default() -> Self {
+        TranscriptionBackend::Local { model: None }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LlmSettings {
+    pub backend: LlmBackend,
+    /// Context window of the model in tokens. Longer transcripts are summarized in parts, and
+    /// llama.cpp sessions are created this large.
+    pub context_tokens: usize,
+    /// Model the search index is built with, served by the same backend. Search needs Ollama or
+    /// an OpenAI-compatible server, llama.cpp doesn't embed.
+    pub embedding_model: String,
+}
+
+impl Default for LlmSettings {
+    fn default() -> Self {
+        LlmSettings {
+            backend: LlmBackend::default(),
+            context_tokens: 8192,
+            embedding_model: "nomic-embed-text".to_string(),
+        }
+    }
+}
+
+impl LlmSettings {
+    /// The backend serving `embedding_model`. A GGUF file is a single model, so llama.cpp has no
+    /// embedding model to switch to.
+    pub fn embedding_backend(&self) -> Result<LlmBackend, String> {
+        match self.backend {
+            LlmBackend::LlamaCpp { .. } => Err(
+                "Search needs Ollama or an OpenAI-compatible server, llama.cpp can't embed text"
+                    .to_string(),
+            ),
+            _ => Ok(self.backend.with_model(Some(self.embedding_model.clone()))),
+        }
+    }
+}
+
+/// The language model summaries, action items and titles come from.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LlmBackend {
+    /// An Ollama server, `host` including the scheme.
+    Ollama {
+        host: String,
+        port: u16,
+        model: String,
+    },
+    /// Any OpenAI-compatible `/v1/chat/completions` endpoint.
+    #[serde(rename = "openai")]
+    OpenAi {
+        url: String,
+        model: String,
+        api_key: Option<String>,
+    },
+    /// A GGUF model file run in-process.
+    LlamaCpp { model_path: String },
+}
+
+impl Default for LlmBackend {
+    fn default() -> Self {
+        LlmBackend::Ollama {
+            host: "http://localhost".to_string(),
+            port: 11434,
+            model: "llama3:latest".to_string(),
+        }
+    }
+}
+
+impl LlmBackend {
+    /// The same backend with another model, for one-off overrides. For llama.cpp the model is a
+    /// file path.
+    pub fn with_model(&self, model: Option<String>) -> LlmBackend {
+        let Some(model) = model else {
+            return self.clone();
+        };
+
+        match self.clone() {
+            LlmBackend::Ollama { host, port, .. } => LlmBackend::Ollama { host, port, model },
+            LlmBackend::OpenAi { url, api_key, .. } => LlmBackend::OpenAi {
+                url,
+                model,
+                api_key,
+            },
+            LlmBackend::LlamaCpp { .. } => LlmBackend::LlamaCpp { model_path: model },
+        }
+    }
+}
+
+impl Settings {
+    pub async fn load(db: &DatabaseConnection) -> Result<Settings, String> {
+        let setting = Query::find_setting(db, SETTINGS_KEY)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        match setting {
+            Some(setting) => serde_json::from_str(&setting.value)
+                .map_err(|e| format!("Failed to parse settings: {}", e)),
+            None => Ok(Settings::default()),
+        }
+    }
+
+    pub async fn save(&self, db: &DatabaseConnection) -> Result<(), String> {
+        let value = serde_json::to_string(self).map_err(|e| e.to_string())?;
+
+        Mutation::upsert_setting(db, SETTINGS_KEY, value)
+            .await
+            .map_err(|e| e.to_string())
+    }
+}
@@ -1,127 +1,732 @@
-use std::{fs::File, io::Write, path::PathBuf};
+use std::collections::HashMap;
+use std::fs::{read_to_string, write, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
-use log::info;
-use ollama_rs::{
-    generation::{completion::request::GenerationRequest, parameters::FormatType},
-    Ollama,
-};
+use async_trait::async_trait;
+use entity::action_item::{self, ActionItemStatus};
+use entity::insight::{self, InsightKind};
+use log::{info, warn};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::glossary::estimate_tokens;
+use crate::llm::{
+    complete_json, complete_structured, CompletionRequest, GenerationKind, LlmProvider, TokenSink,
+};
+use crate::prompts::{
+    PromptLibrary, ACTION_ITEMS_PROMPT, CHUNK_SUMMARY_PROMPT, COMBINE_SUMMARIES_PROMPT,
+    INSIGHTS_PROMPT, SUMMARY_PROMPT, TITLE_PROMPT,
+};
+use crate::redaction::Redactor;
+use crate::summary_template::{SectionKind, SummaryTemplate};
+use crate::transcribe::{TranscriptSegment, TranscriptionJSON};
+use crate::utils::{format_timestamp, parse_timestamp};
 
 #[derive(Serialize, Deserialize)]
+#[serde(from = "StoredSummary")]
 pub struct SummaryJSON {
-    pub result: String,
+    /// Name of the template the sections follow.
+    pub template: String,
+    pub sections: Vec<SummarySection>,
     pub action_items: Vec<ActionItem>,
+    pub insights: Insights,
+}
+
+impl SummaryJSON {
+    /// The sections as Markdown, headed by their titles.
+    pub fn to_markdown(&self) -> String {
+        self.sections
+            .iter()
+            .map(|section| {
+                let content = match &section.content {
+                    SectionContent::Text(text) => text.clone(),
+                    SectionContent::List(items) => items
+                        .iter()
+                        .map(|item| format!("- {}", item))
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                };
+                format!("## {}\n{}", section.title, content)
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n")
+    }
 }
 
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SummarySection {
+    pub key: String,
+    pub title: String,
+    pub content: SectionContent,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum SectionContent {
+    Text(String),
+    List(Vec<String>),
+}
+
+/// `summary.json` as written now, or before templates when it was a single `result` text.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredSummary {
+    Sections {
+        template: String,
+        sections: Vec<SummarySection>,
+        action_items: Vec<ActionItem>,
+        #[serde(default)]
+        insights: Insights,
+    },
+    Result {
+        result: String,
+        action_items: Vec<ActionItem>,
+    },
+}
+
+impl From<StoredSummary> for SummaryJSON {
+    fn from(stored: StoredSummary) -> Self {
+        match stored {
+            StoredSummary::Sections {
+                template,
+                sections,
+                action_items,
+                insights,
+            } => SummaryJSON {
+                template,
+                sections,
+                action_items,
+                insights,
+            },
+            StoredSummary::Result {
+                result,
+                action_items,
+            } => SummaryJSON {
+                template: SummaryTemplate::general().name,
+                sections: vec![SummarySection {
+                    key: "summary".to_string(),
+                    title: "Summary".to_string(),
+                    content: SectionContent::Text(result),
+                }],
+                action_items,
+                insights: Insights::default(),
+            },
+        }
+    }
+}
+
+pub struct SummaryOptions {
+    /// Sections to write and the instruction to write them with.
+    pub template: SummaryTemplate,
+    /// Context window of the model. Transcripts are cut into chunks of half of it, leaving room
+    /// for the instructions and the answer.
+    pub context_tokens: usize,
+    pub prompts: PromptLibrary,
+}
+
+impl Default for SummaryOptions {
+    fn default() -> Self {
+        SummaryOptions {
+            template: SummaryTemplate::general(),
+            context_tokens: 8192,
+            prompts: PromptLibrary::defaults(),
+        }
+    }
+}
+
+/// Summarizes a transcript into the sections of a template and extracts its action items,
+/// decisions, open questions, risks and topics. Returns the summary as written.
+///
+/// Transcripts that don't fit the model's context are summarized chunk by chunk, then the chunk
+/// summaries are summarized. Chunk results are cached in `cache_path`, so a re-run only sends the
+/// chunks that changed.
 pub async fn summarize_and_write(
-    text: String,
+    provider: &dyn LlmProvider,
+    transcription: &TranscriptionJSON,
     summary_output_file_path: &PathBuf,
-) -> Result<(), String> {
-    let summary = summarize(&text).await?;
-    let action_items = generate_action_items(&text).await?;
+    cache_path: &Path,
+    options: &SummaryOptions,
+    redactor: &mut Redactor,
+) -> Result<SummaryJSON, String> {
+    let budget = options.context_tokens / 2;
+    let prompts = &options.prompts;
+    // The model only ever sees placeholders, and anything it echoes back is masked again.
+    let chunks: Vec<String> = chunk_transcript(transcription, budget)
+        .iter()
+        .map(|chunk| redactor.redact(chunk))
+        .collect();
+
+    let (sections, action_items, insights) = if chunks.len() <= 1 {
+        let text = chunks.into_iter().next().unwrap_or_default();
+        let sections = generate_sections(provider, &text, &options.template, prompts).await?;
+        let action_items = generate_action_items(provider, &text, prompts).await?;
+        let insights = insights_or_default(provider, &text, prompts).await;
+        (sections, action_items.action_items, insights)
+    } else {
+        info!("Summarizing transcript in {} chunks", chunks.len());
+        let cached = CachedProvider::load(provider, cache_path);
+
+        let mut partials = Vec::new();
+        let mut action_items: Vec<ActionItem> = Vec::new();
+        let mut insights = Insights::default();
+        for chunk in &chunks {
+            partials.push(summarize_chunk(&cached, chunk, prompts).await?);
+            for item in generate_action_items(&cached, chunk, prompts)
+                .await?
+                .action_items
+            {
+                let duplicate = action_items
+                    .iter()
+                    .any(|existing| existing.title.eq_ignore_ascii_case(&item.title));
+                if !duplicate {
+                    action_items.push(item);
+                }
+            }
+            insights.merge(insights_or_default(&cached, chunk, prompts).await);
+            // A run that fails further on still keeps the chunks it got through.
+            cached.save_progress(cache_path);
+        }
+
+        let sections = match reduce_summaries(provider, &cached, partials, options, budget).await {
+            Ok(sections) => sections,
+            Err(e) => {
+                cached.save_progress(cache_path);
+                return Err(e);
+            }
+        };
+        cached.save(cache_path);
+        (sections, action_items, insights)
+    };
 
     let summary = SummaryJSON {
-        result: summary,
-        action_items: action_items.action_items,
+        template: options.template.name.clone(),
+        sections: sections
+            .into_iter()
+            .map(|section| SummarySection {
+                content: match section.content {
+                    SectionContent::Text(text) => SectionContent::Text(redactor.redact(&text)),
+                    SectionContent::List(items) => SectionContent::List(
+                        items.iter().map(|item| redactor.redact(item)).collect(),
+                    ),
+                },
+                ..section
+            })
+            .collect(),
+        action_items: action_items
+            .into_iter()
+            .map(|item| ActionItem {
+                title: redactor.redact(&item.title),
+                assignee: item
+                    .assignee
+                    .as_deref()
+                    .map(|assignee| redactor.redact(assignee)),
+                ..item
+            })
+            .collect(),
+        insights: insights.redact(redactor),
     };
+    redactor.save().await?;
 
-    let json_string =
-        serde_json::to_string_pretty(&summary).expect("failed to serialize transcription");
+    let json_string = serde_json::to_string_pretty(&summary)
+        .map_err(|e| format!("Failed to serialize summary: {}", e))?;
 
-    let mut file = File::create(summary_output_file_path).expect("couldn't create file");
-    file.write_all(json_string.as_bytes())
-        .expect("could not write to file");
+    let mut file = File::create(summary_output_file_path).map_err(|e| {
+        format!(
+            "Failed to create {}: {}",
+            summary_output_file_path.display(),
+            e
+        )
+    })?;
+    file.write_all(json_string.as_bytes()).map_err(|e| {
+        format!(
+            "Failed to write {}: {}",
+            summary_output_file_path.display(),
+            e
+        )
+    })?;
 
-    Ok(())
+    Ok(summary)
 }
 
-pub async fn summarize(text: &String) -> Result<String, String> {
-    let ollama = Ollama::default();
+/// Asks for the template's sections as JSON matching its schema.
+pub async fn generate_sections(
+    provider: &dyn LlmProvider,
+    text: &str,
+    template: &SummaryTemplate,
+    prompts: &PromptLibrary,
+) -> Result<Vec<SummarySection>, String> {
+    let request = prompts.request(
+        SUMMARY_PROMPT,
+        &[
+            ("instructions", template.prompt.as_str()),
+            ("transcript", text),
+        ],
+    )?;
+
+    let answer = complete_json(provider, request, &template.json_schema)
+        .await
+        .map_err(|e| format!("Failed to generate summary: {}", e))?;
+
+    Ok(template
+        .sections
+        .iter()
+        .map(|section| {
+            let value = answer.get(&section.key).unwrap_or(&Value::Null);
+            SummarySection {
+                key: section.key.clone(),
+                title: section.title.clone(),
+                content: section_content(section.kind, value),
+            }
+        })
+        .collect())
+}
 
-    let model = "llama3:latest".to_string();
-    let prompt = format!("Can you summarize this: {}", text);
+/// Reads a section's value, taking a list for a text section or the other way round.
+fn section_content(kind: SectionKind, value: &Value) -> SectionContent {
+    let items: Vec<String> = match value {
+        Value::Null => Vec::new(),
+        Value::String(text) => vec![text.clone()],
+        Value::Array(items) => items
+            .iter()
+            .map(|item| match item {
+                Value::String(text) => text.clone(),
+                other => other.to_string(),
+            })
+            .collect(),
+        other => vec![other.to_string()],
+    };
 
-    let res = ollama
-        .generate(GenerationRequest::new(model, prompt))
+    match kind {
+        SectionKind::Text => SectionContent::Text(items.join("\n")),
+        SectionKind::List => SectionContent::List(items),
+    }
+}
+
+async fn summarize_chunk(
+    provider: &dyn LlmProvider,
+    text: &str,
+    prompts: &PromptLibrary,
+) -> Result<String, String> {
+    provider
+        .complete(&prompts.request(CHUNK_SUMMARY_PROMPT, &[("transcript", text)])?)
         .await
-        .expect("Failed to generate summary");
+        .map_err(|e| format!("Failed to summarize transcript chunk: {}", e))
+}
+
+/// Writes the summary sections from the chunk summaries, first condensing them in groups for as
+/// long as they don't fit together.
+async fn reduce_summaries(
+    provider: &dyn LlmProvider,
+    cached: &CachedProvider<'_>,
+    mut partials: Vec<String>,
+    options: &SummaryOptions,
+    budget: usize,
+) -> Result<Vec<SummarySection>, String> {
+    while estimate_tokens(&partials.join("\n\n")) > budget {
+        let groups = pack(&partials, budget);
+        // Every summary fills the budget by itself, grouping won't get any further.
+        if groups.len() == partials.len() {
+            break;
+        }
+
+        let mut condensed = Vec::new();
+        for group in groups {
+            condensed.push(summarize_chunk(cached, &group, &options.prompts).await?);
+        }
+        partials = condensed;
+    }
 
-    Ok(res.response)
+    // Only the text is kept, it's sent with the summary prompt on that prompt's model.
+    let text = options
+        .prompts
+        .request(
+            COMBINE_SUMMARIES_PROMPT,
+            &[("summaries", partials.join("\n\n").as_str())],
+        )?
+        .prompt;
+    generate_sections(provider, &text, &options.template, &options.prompts).await
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Cuts a transcript into chunks of at most `budget` tokens. Chunks end between speaker turns,
+/// and turns too long for one chunk are split between their segments.
+fn chunk_transcript(transcription: &TranscriptionJSON, budget: usize) -> Vec<String> {
+    pack(&transcript_pieces(transcription, budget), budget)
+}
+
+/// The transcript's speaker turns, split between segments where they're over `budget` tokens.
+/// Each piece starts with its timestamp so the model can point back at it.
+pub fn transcript_pieces(transcription: &TranscriptionJSON, budget: usize) -> Vec<String> {
+    if transcription.segments.is_empty() {
+        return transcription.full_text.clone();
+    }
+
+    let has_speakers = transcription
+        .segments
+        .iter()
+        .any(|segment| segment.speaker.is_some());
+
+    let mut turns: Vec<(Option<&str>, Vec<&TranscriptSegment>)> = Vec::new();
+    let mut turn_ended = true;
+    for segment in &transcription.segments {
+        let speaker = segment.speaker.as_deref();
+        match turns.last_mut() {
+            Some((turn_speaker, segments))
+                if (has_speakers && *turn_speaker == speaker) || (!has_speakers && !turn_ended) =>
+            {
+                segments.push(segment);
+            }
+            _ => turns.push((speaker, vec![segment])),
+        }
+        turn_ended = segment.speaker_turn_next;
+    }
+
+    let mut pieces: Vec<String> = Vec::new();
+    for (speaker, segments) in turns {
+        let label = |t0: i64| match speaker {
+            Some(speaker) => format!("[{}] {}: ", format_timestamp(t0), speaker),
+            None => format!("[{}] ", format_timestamp(t0)),
+        };
+        let mut piece = String::new();
+        for segment in segments {
+            let text = segment.text.trim();
+            if !piece.is_empty() && estimate_tokens(&piece) + estimate_tokens(text) > budget {
+                pieces.push(std::mem::take(&mut piece));
+            }
+            if piece.is_empty() {
+                piece = label(segment.t0);
+            } else {
+                piece.push(' ');
+            }
+            piece.push_str(text);
+        }
+        pieces.push(piece);
+    }
+
+    pieces
+}
+
+/// Joins consecutive pieces, one per line, into chunks of at most `budget` tokens. A piece over
+/// the budget gets a chunk of its own.
+pub fn pack(pieces: &[String], budget: usize) -> Vec<String> {
+    let mut chunks: Vec<String> = Vec::new();
+    let mut chunk = String::new();
+
+    for piece in pieces.iter().filter(|piece| !piece.trim().is_empty()) {
+        if !chunk.is_empty() && estimate_tokens(&chunk) + estimate_tokens(piece) > budget {
+            chunks.push(std::mem::take(&mut chunk));
+        }
+        if !chunk.is_empty() {
+            chunk.push('\n');
+        }
+        chunk.push_str(piece);
+    }
+    if !chunk.is_empty() {
+        chunks.push(chunk);
+    }
+
+    chunks
+}
+
+/// Remembers what the model answered for each chunk, keyed by the model and the exact request.
+/// Only the entries used by the latest run are written back.
+struct CachedProvider<'a> {
+    provider: &'a dyn LlmProvider,
+    entries: Mutex<HashMap<String, String>>,
+    used: Mutex<HashMap<String, String>>,
+}
+
+impl<'a> CachedProvider<'a> {
+    fn load(provider: &'a dyn LlmProvider, path: &Path) -> Self {
+        let entries = read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+
+        CachedProvider {
+            provider,
+            entries: Mutex::new(entries),
+            used: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn save(&self, path: &Path) {
+        write_cache(path, &self.used.lock().unwrap());
+    }
+
+    /// Saves the answers so far without dropping unused entries, the run may still need them.
+    fn save_progress(&self, path: &Path) {
+        let mut entries = self.entries.lock().unwrap().clone();
+        entries.extend(self.used.lock().unwrap().clone());
+        write_cache(path, &entries);
+    }
+}
+
+fn write_cache(path: &Path, entries: &HashMap<String, String>) {
+    let result = serde_json::to_string(entries)
+        .map_err(|e| e.to_string())
+        .and_then(|json| write(path, json).map_err(|e| e.to_string()));
+    if let Err(e) = result {
+        warn!("Failed to write {}: {}", path.display(), e);
+    }
+}
+
+#[async_trait]
+impl LlmProvider for CachedProvider<'_> {
+    fn name(&self) -> String {
+        self.provider.name()
+    }
+
+    async fn complete(&self, request: &CompletionRequest) -> Result<String, String> {
+        self.complete_streaming(request, &|_| {}).await
+    }
+
+    async fn complete_streaming(
+        &self,
+        request: &CompletionRequest,
+        on_token: TokenSink<'_>,
+    ) -> Result<String, String> {
+        let key = request.cache_key(&self.provider.name());
+
+        let cached = self.entries.lock().unwrap().get(&key).cloned();
+        let response = match cached {
+            Some(response) => {
+                on_token(&response);
+                response
+            }
+            None => self.provider.complete_streaming(request, on_token).await?,
+        };
+        self.used.lock().unwrap().insert(key, response.clone());
+
+        Ok(response)
+    }
+}
+
+// Doc comments end up in the schema as descriptions for the model.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct ActionItem {
-    title: String,
+    /// The title of the action item
+    pub title: String,
+    /// Who took the item on, if anyone did
+    #[serde(default)]
+    pub assignee: Option<String>,
+    /// When it's due, as YYYY-MM-DD if a date was given
+    #[serde(default)]
+    pub due_date: Option<String>,
+    /// Timestamp of the line the item came up in, e.g. 12:34
+    #[serde(default)]
+    pub timestamp: Option<String>,
+}
+
+impl ActionItem {
+    pub fn to_model(&self, conversation_id: i32) -> action_item::Model {
+        action_item::Model {
+            id: 0,
+            conversation_id,
+            title: self.title.clone(),
+            assignee: self.assignee.clone(),
+            due_date: self.due_date.clone(),
+            status: ActionItemStatus::Open,
+            source_t0: self.timestamp.as_deref().and_then(parse_timestamp),
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 pub struct ActionItems {
+    /// Every task someone took on, once each
     action_items: Vec<ActionItem>,
 }
 
-pub async fn generate_action_items(text: &String) -> Result<ActionItems, String> {
-    let ollama = Ollama::default();
-
-    let model = "llama3:latest".to_string();
-    let prompt = format!(
-        "Create action items from a transcript.
-        You must format your output as a JSON value that adheres to a given \"JSON Schema\" instance.
-        \"JSON Schema\" is a declarative language that allows you to annotate and validate JSON documents.
-        For example, the example \"JSON Schema\" instance {{\"properties\": {{\"foo\": {{\"description\": \"a list of test words\", \"type\": \"array\", \"items\": {{\"type\": \"string\"}}}}}}, \"required\": [\"foo\"]}}
-        would match an object with one required property, \"foo\". The \"type\" property specifies \"foo\" must be an \"array\", and the \"description\" property semantically describes it as \"a list of test words\". The items within \"foo\" must be strings.
-        Thus, the object {{\"foo\": [\"bar\", \"baz\"]}} is a well-formatted instance of this example \"JSON Schema\". The object {{\"properties\": {{\"foo\": [\"bar\", \"baz\"]}}}} is not well-formatted.
-        Your output will be parsed and type-checked according to the provided schema instance, so make sure all fields in your output match the schema exactly and there are no trailing commas!
-        Here is the JSON Schema instance your output must adhere to. Include the enclosing markdown codeblock:
-        ```json
-        {{
-            \"type\": \"object\",
-            \"properties\": {{
-                \"action_items\": {{
-                    \"type\": \"array\",
-                    \"items\": {{
-                        \"type\": \"object\",
-                        \"properties\": {{
-                            \"title\": {{
-                                \"type\": \"string\",
-                                \"description\": \"The title of the action item\"
-                            }}
-                        }},
-                        \"required\": [\"title\"],
-                        \"additionalProperties\": false
-                    }}
-                }}
-            }},
-            \"required\": [\"actionItems\"],
-            \"additionalProperties\": false,
-            \"$schema\": \"http://json-schema.org/draft-07/schema#\"
-        }}
-        ```
-        transcript: {}",
-        text
-    );
-
-    let generation_request = GenerationRequest::new(model, prompt).format(FormatType::Json);
-
-    let res = ollama
-        .generate(generation_request)
+pub async fn generate_action_items(
+    provider: &dyn LlmProvider,
+    text: &str,
+    prompts: &PromptLibrary,
+) -> Result<ActionItems, String> {
+    let request = prompts.request(ACTION_ITEMS_PROMPT, &[("transcript", text)])?;
+
+    let action_items: ActionItems =
+        complete_structured(provider, request.kind(GenerationKind::ActionItems))
+            .await
+            .map_err(|e| format!("Failed to generate action items: {}", e))?;
+    info!("action items: {:?}", action_items);
+    Ok(action_items)
+}
+
+/// Something that came up in a meeting, like a decision or a risk.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct Insight {
+    /// What was decided, asked or raised, in one sentence
+    pub text: String,
+    /// Timestamp of the line it came up in, e.g. 12:34
+    #[serde(default)]
+    pub timestamp: Option<String>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct Insights {
+    /// Decisions the meeting made
+    #[serde(default)]
+    pub decisions: Vec<Insight>,
+    /// Questions that were raised and left unanswered
+    #[serde(default)]
+    pub open_questions: Vec<Insight>,
+    /// Risks, blockers and concerns that were raised
+    #[serde(default)]
+    pub risks: Vec<Insight>,
+    /// The main topics discussed, as a few words each
+    #[serde(default)]
+    pub topics: Vec<Insight>,
+}
+
+impl Insights {
+    fn lists(&self) -> [(InsightKind, &Vec<Insight>); 4] {
+        [
+            (InsightKind::Decision, &self.decisions),
+            (InsightKind::OpenQuestion, &self.open_questions),
+            (InsightKind::Risk, &self.risks),
+            (InsightKind::Topic, &self.topics),
+        ]
+    }
+
+    fn lists_mut(&mut self) -> [&mut Vec<Insight>; 4] {
+        [
+            &mut self.decisions,
+            &mut self.open_questions,
+            &mut self.risks,
+            &mut self.topics,
+        ]
+    }
+
+    pub fn to_models(&self, conversation_id: i32) -> Vec<insight::Model> {
+        self.lists()
+            .into_iter()
+            .flat_map(|(kind, insights)| {
+                insights.iter().map(move |insight| insight::Model {
+                    id: 0,
+                    conversation_id,
+                    kind,
+                    content: insight.text.clone(),
+                    source_t0: insight.timestamp.as_deref().and_then(parse_timestamp),
+                    created_at: String::new(),
+                })
+            })
+            .collect()
+    }
+
+    /// Adds the insights of a later chunk, skipping ones already found.
+    fn merge(&mut self, mut other: Insights) {
+        for (list, more) in self.lists_mut().into_iter().zip(other.lists_mut()) {
+            for insight in more.drain(..) {
+                let duplicate = list
+                    .iter()
+                    .any(|existing| existing.text.eq_ignore_ascii_case(&insight.text));
+                if !duplicate {
+                    list.push(insight);
+                }
+            }
+        }
+    }
+
+    fn redact(mut self, redactor: &mut Redactor) -> Self {
+        for list in self.lists_mut() {
+            for insight in list.iter_mut() {
+                insight.text = redactor.redact(&insight.text);
+            }
+        }
+        self
+    }
+}
+
+pub async fn generate_insights(
+    provider: &dyn LlmProvider,
+    text: &str,
+    prompts: &PromptLibrary,
+) -> Result<Insights, String> {
+    let request = prompts.request(INSIGHTS_PROMPT, &[("transcript", text)])?;
+
+    let insights: Insights = complete_structured(provider, request.kind(GenerationKind::Insights))
         .await
-        .expect("failed to generate action items");
-    info!("action items: {}", res.response);
-    let json: ActionItems =
-        serde_json::from_str(&res.response).expect("Action items not formatted correctly");
-    Ok(json)
+        .map_err(|e| format!("Failed to generate insights: {}", e))?;
+    info!("insights: {:?}", insights);
+    Ok(insights)
 }
 
-pub async fn generate_title(text: &String) -> Result<String, String> {
-    let ollama = Ollama::default();
+/// Insights come on top of the summary, a model that can't produce them shouldn't cost the
+/// summary as well.
+async fn insights_or_default(
+    provider: &dyn LlmProvider,
+    text: &str,
+    prompts: &PromptLibrary,
+) -> Insights {
+    generate_insights(provider, text, prompts)
+        .await
+        .unwrap_or_else(|e| {
+            warn!("{}, continuing without them", e);
+            Insights::default()
+        })
+}
 
-    let model = "llama3:latest".to_string();
-    let prompt = format!("Can you generate a short meeting title from this: {}", text);
+pub async fn generate_title(
+    provider: &dyn LlmProvider,
+    text: &str,
+    prompts: &PromptLibrary,
+) -> Result<String, String> {
+    let request = prompts.request(TITLE_PROMPT, &[("summary", text)])?;
 
-    let res = ollama
-        .generate(GenerationRequest::new(model, prompt))
+    let response = provider
+        .complete(&request.kind(GenerationKind::Title))
         .await
-        .expect("failed to generate title");
+        .map_err(|e| format!("Failed to generate title: {}", e))?;
+
+    clean_title(&response).ok_or("The model didn't answer with a title".to_string())
+}
+
+/// Longest title kept, longer ones are cut at a word boundary.
+const MAX_TITLE_CHARS: usize = 80;
+
+/// Pulls the title out of answers like `Sure! Here's a title:\n\n"**Q3 Planning**"`.
+fn clean_title(response: &str) -> Option<String> {
+    let lines: Vec<&str> = response
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect();
+    // A line ending in a colon introduces the title on the next one.
+    let line = lines
+        .iter()
+        .find(|line| !line.ends_with(':'))
+        .or(lines.first())?;
+
+    let mut title = line.to_string();
+    if let Some((label, rest)) = title.split_once(':') {
+        if label.to_lowercase().contains("title") && !rest.trim().is_empty() {
+            title = rest.to_string();
+        }
+    }
+
+    let title = title
+        .trim_matches(|c: char| {
+            c.is_whitespace() || matches!(c, '"' | '\'' | '`' | '*' | '#' | '“' | '”' | '‘' | '’')
+        })
+        .trim_end_matches('.')
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    if title.is_empty() {
+        return None;
+    }
+    if title.chars().count() <= MAX_TITLE_CHARS {
+        return Some(title);
+    }
 
-    Ok(res.response)
+    let cut: String = title.chars().take(MAX_TITLE_CHARS).collect();
+    Some(match cut.rsplit_once(' ') {
+        Some((words, _)) => words.to_string(),
+        None => cut,
+    })
 }
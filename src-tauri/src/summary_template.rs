@@ -0,0 +1,167 @@
+use std::collections::HashSet;
+
+use entity::{conversation, summary_template};
+use log::info;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use service::{sea_orm::DatabaseConnection, Query};
+
+/// One part of a summary, e.g. the blockers of a standup.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TemplateSection {
+    /// Property the model answers under, unique within a template.
+    pub key: String,
+    pub title: String,
+    pub kind: SectionKind,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SectionKind {
+    /// Markdown text.
+    Text,
+    /// Short items, one sentence each.
+    List,
+}
+
+/// A summary template ready to be sent to the model.
+#[derive(Clone, Debug)]
+pub struct SummaryTemplate {
+    pub name: String,
+    pub prompt: String,
+    pub sections: Vec<TemplateSection>,
+    pub json_schema: Value,
+}
+
+impl SummaryTemplate {
+    pub fn from_model(model: &summary_template::Model) -> Result<SummaryTemplate, String> {
+        Ok(SummaryTemplate {
+            name: model.name.clone(),
+            prompt: model.prompt.clone(),
+            sections: parse_sections(&model.sections)?,
+            json_schema: serde_json::from_str(&model.json_schema)
+                .map_err(|e| format!("Invalid JSON schema in template {}: {}", model.name, e))?,
+        })
+    }
+
+    /// Used when the database has no template to fall back on.
+    pub fn general() -> SummaryTemplate {
+        let sections = vec![TemplateSection {
+            key: "summary".to_string(),
+            title: "Summary".to_string(),
+            kind: SectionKind::Text,
+        }];
+
+        SummaryTemplate {
+            name: "General".to_string(),
+            prompt: "Summarize this meeting transcript. Keep it short and factual, in Markdown."
+                .to_string(),
+            json_schema: schema_for_sections(&sections),
+            sections,
+        }
+    }
+}
+
+/// Parses the `sections` column, rejecting templates without sections or with duplicate keys.
+pub fn parse_sections(sections: &str) -> Result<Vec<TemplateSection>, String> {
+    let sections: Vec<TemplateSection> =
+        serde_json::from_str(sections).map_err(|e| format!("Invalid sections: {}", e))?;
+    if sections.is_empty() {
+        return Err("A template needs at least one section".to_string());
+    }
+
+    let mut keys = HashSet::new();
+    for section in &sections {
+        if section.key.trim().is_empty() {
+            return Err("Section keys can't be empty".to_string());
+        }
+        if !keys.insert(section.key.as_str()) {
+            return Err(format!("Section key {} is used twice", section.key));
+        }
+    }
+
+    Ok(sections)
+}
+
+/// The schema of an answer with a property per section, for templates saved without one.
+pub fn schema_for_sections(sections: &[TemplateSection]) -> Value {
+    let mut properties = Map::new();
+    for section in sections {
+        let property = match section.kind {
+            SectionKind::Text => json!({ "type": "string", "description": section.title }),
+            SectionKind::List => json!({
+                "type": "array",
+                "items": { "type": "string" },
+                "description": section.title,
+            }),
+        };
+        properties.insert(section.key.clone(), property);
+    }
+
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": sections.iter().map(|section| &section.key).collect::<Vec<_>>(),
+        "additionalProperties": false,
+    })
+}
+
+/// Picks the template a conversation is summarized with.
+///
+/// An explicit choice wins. Otherwise the first template whose title keywords match the
+/// conversation title is used, then the first whose transcript keywords come up in the
+/// transcript, then the first template without any rules.
+pub async fn select_template(
+    db: &DatabaseConnection,
+    conversation: &conversation::Model,
+    transcript: &str,
+    template_id: Option<i32>,
+) -> Result<SummaryTemplate, String> {
+    if let Some(template_id) = template_id.or(conversation.summary_template_id) {
+        let template = Query::find_summary_template_by_id(db, template_id)
+            .await
+            .map_err(|e| e.to_string())?
+            .ok_or(format!("Summary template {} doesn't exist", template_id))?;
+        return SummaryTemplate::from_model(&template);
+    }
+
+    let templates = Query::find_summary_templates(db)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let matched = templates
+        .iter()
+        .find(|template| mentions_any(&conversation.title, &template.title_keyword_list()))
+        .or_else(|| {
+            templates
+                .iter()
+                .find(|template| mentions_any(transcript, &template.transcript_keyword_list()))
+        })
+        .or_else(|| {
+            templates.iter().find(|template| {
+                template.title_keyword_list().is_empty()
+                    && template.transcript_keyword_list().is_empty()
+            })
+        });
+
+    match matched {
+        Some(template) => {
+            info!(
+                "Summarizing conversation {} with template {}",
+                conversation.id, template.name
+            );
+            SummaryTemplate::from_model(template)
+        }
+        None => Ok(SummaryTemplate::general()),
+    }
+}
+
+/// Whether `text` contains one of `keywords` as a whole word, ignoring case.
+fn mentions_any(text: &str, keywords: &[String]) -> bool {
+    keywords.iter().any(|keyword| {
+        Regex::new(&format!(r"(?i)(^|\W){}($|\W)", regex::escape(keyword)))
+            .map(|pattern| pattern.is_match(text))
+            .unwrap_or(false)
+    })
+}
@@ -1,118 +1,525 @@
 use std::{
-    fs::{read_dir, read_to_string, File},
-    io::Write,
+    fs::{create_dir_all, read_dir, read_to_string, remove_dir_all, write, File},
+    io::{BufReader, Write},
     path::PathBuf,
-    sync::Arc,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
 };
 
+use entity::{glossary_term, job::JobStage};
 use hound::{SampleFormat, WavReader};
-use log::info;
+use log::{info, warn};
 use serde::{Deserialize, Serialize};
+use service::{sea_orm::DatabaseConnection, Query};
 use tauri::Manager;
-use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
 
+use crate::diarize::{diarize, SpeakerEmbedder, SPEAKER_MODEL_DIR, SPEAKER_MODEL_FILE};
+use crate::glossary::substitute_glossary_terms;
+use crate::hallucination::{filter_hallucinations, DroppedSegment};
+use crate::jobs::JobQueue;
 use crate::recorder::RecordingState;
+use crate::redaction::Redactor;
+use crate::settings::{
+    DecodingSettings, HallucinationFilterSettings, Settings, TranscriptionBackend,
+};
+use crate::transcriber::{ProgressCallback, Transcriber, TranscriptionRequest};
+use crate::transcript_edits::{apply_edits, load_edited_transcription};
+use crate::AppState;
 
 #[derive(Serialize, Deserialize)]
 pub struct TranscriptionJSON {
     pub full_text: Vec<String>,
+    #[serde(default)]
+    pub segments: Vec<TranscriptSegment>,
+    /// Segments the hallucination filter removed, restorable with `restore_dropped_segment`.
+    #[serde(default)]
+    pub dropped_segments: Vec<DroppedSegment>,
 }
 
-pub fn transcribe_wav_file_and_write(
-    handle: tauri::AppHandle,
-    wav_filepath: &PathBuf,
-    transcription_output_file_path: &PathBuf,
-) -> Result<(), String> {
-    let filepath_str = wav_filepath.to_str().unwrap_or_default().to_owned();
-    info!("{}", filepath_str);
+impl TranscriptionJSON {
+    /// Transcript text as handed to the LLM, one line per speaker run when speakers are known.
+    pub fn speaker_text(&self) -> String {
+        if self
+            .segments
+            .iter()
+            .all(|segment| segment.speaker.is_none())
+        {
+            return self.full_text.join(" CHANGE_SPEAKER_TOKEN ");
+        }
 
-    let whisper_path = handle
-        .path()
-        .resource_dir()
-        .expect("failed to get resource dir")
-        .join("src/models/ggml-small.en-tdrz.bin");
-    if !whisper_path.exists() {
-        panic!("whisper file doesn't exist");
+        speaker_runs(&self.segments).join("\n")
     }
 
-    let mut reader = WavReader::open(filepath_str).expect("failed to read file");
-    let spec = reader.spec();
+    /// The speakers in the order they first spoke.
+    pub fn speakers(&self) -> Vec<&str> {
+        let mut speakers: Vec<&str> = Vec::new();
+        for segment in &self.segments {
+            if let Some(speaker) = segment.speaker.as_deref().map(str::trim) {
+                if !speaker.is_empty() && !speakers.contains(&speaker) {
+                    speakers.push(speaker);
+                }
+            }
+        }
 
-    if spec.channels != 1 {
-        panic!("expected mono audio file");
-    }
-    if spec.sample_format != SampleFormat::Int {
-        panic!("expected integer sample format");
+        speakers
     }
-    if spec.bits_per_sample != 16 {
-        panic!("expected 16 bits per sample");
+}
+
+/// Joins consecutive segments of the same speaker into `Speaker N: text` paragraphs.
+fn speaker_runs(segments: &[TranscriptSegment]) -> Vec<String> {
+    let mut runs: Vec<(Option<&str>, String)> = Vec::new();
+
+    for segment in segments {
+        let speaker = segment.speaker.as_deref();
+        match runs.last_mut() {
+            Some((run_speaker, text)) if *run_speaker == speaker => {
+                text.push_str(&segment.text);
+            }
+            _ => runs.push((speaker, segment.text.clone())),
+        }
     }
 
-    // Read all samples
-    let original_samples: Vec<i16> = reader
-        .samples::<i16>()
-        .map(|s| s.expect("failed to read sample"))
-        .collect();
-    let mut samples = vec![0.0f32; original_samples.len()];
+    runs.into_iter()
+        .map(|(speaker, text)| match speaker {
+            Some(speaker) => format!("{}: {}", speaker, text.trim()),
+            None => text.trim().to_string(),
+        })
+        .collect()
+}
 
-    whisper_rs::convert_integer_to_float_audio(&original_samples, &mut samples)
-        .expect("failed to convert samples");
+/// Splits the transcript into speaker runs, or at the backend's speaker turns when diarization
+/// didn't run.
+pub fn full_text_from_segments(segments: &[TranscriptSegment]) -> Vec<String> {
+    if segments.iter().any(|segment| segment.speaker.is_some()) {
+        return speaker_runs(segments);
+    }
 
-    let ctx = WhisperContext::new_with_params(
-        &whisper_path.to_string_lossy(),
-        WhisperContextParameters::default(),
-    )
-    .expect("failed to open model");
-    let mut state = ctx.create_state().expect("failed to create state");
-    let mut params = FullParams::new(SamplingStrategy::default());
-    params.set_initial_prompt("experience");
-    params.set_progress_callback_safe(|progress| info!("Progress callback: {}%", progress));
-    params.set_tdrz_enable(true);
-
-    let st = std::time::Instant::now();
-    state
-        .full(params, &samples)
-        .expect("failed to transcribe audio");
-
-    let et = std::time::Instant::now();
-
-    let num_segments = state
-        .full_n_segments()
-        .expect("failed to get number of segments");
     let mut full_text: Vec<String> = vec![String::new()];
-    let mut full_text_index = 0;
-    for i in 0..num_segments {
-        let segment = state
-            .full_get_segment_text(i)
-            .expect("failed to get segment");
-        full_text[full_text_index].push_str(&segment);
-        if state.full_get_segment_speaker_turn_next(i) {
+    for segment in segments {
+        full_text.last_mut().unwrap().push_str(&segment.text);
+        if segment.speaker_turn_next {
             full_text.push(String::new());
-            full_text_index += 1
         }
-        let start_timestamp = state
-            .full_get_segment_t0(i)
-            .expect("failed to get start timestamp");
-        let end_timestamp = state
-            .full_get_segment_t1(i)
-            .expect("failed to get end timestamp");
-        info!("[{} - {}]: {}", start_timestamp, end_timestamp, segment);
-    }
-    info!("Transcription took {}ms", (et - st).as_millis());
-
-    let transcription = TranscriptionJSON {
-        full_text: full_text,
+    }
+    full_text
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TranscriptSegment {
+    /// Start and end of the segment in milliseconds.
+    pub t0: i64,
+    pub t1: i64,
+    pub text: String,
+    pub speaker_turn_next: bool,
+    /// Stable speaker id such as `Speaker 1`, set when diarization ran.
+    #[serde(default)]
+    pub speaker: Option<String>,
+    #[serde(default)]
+    pub words: Vec<TranscriptWord>,
+    /// Probability the segment is silence, when the backend reports it.
+    #[serde(default)]
+    pub no_speech_prob: Option<f32>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct TranscriptWord {
+    pub text: String,
+    /// Start and end of the word in milliseconds.
+    pub t0: i64,
+    pub t1: i64,
+    /// Lowest probability of the tokens making up the word.
+    pub p: f32,
+}
+
+/// User-managed inputs to a transcription run.
+pub struct TranscriptionOptions {
+    pub backend: TranscriptionBackend,
+    pub language: Option<String>,
+    pub decoding: DecodingSettings,
+    pub initial_prompt: String,
+    pub glossary: Vec<glossary_term::Model>,
+    pub substitute_glossary_terms: bool,
+    pub diarize_speakers: bool,
+    pub hallucination_filter: HallucinationFilterSettings,
+}
+
+impl TranscriptionOptions {
+    pub async fn load(db: &DatabaseConnection) -> Result<TranscriptionOptions, String> {
+        let settings = Settings::load(db).await?;
+        let glossary = Query::find_glossary_terms(db)
+            .await
+            .map_err(|e| e.to_string())?;
+
+        Ok(TranscriptionOptions {
+            backend: settings.transcription.backend,
+            language: settings.transcription.language,
+            decoding: settings.transcription.decoding,
+            initial_prompt: settings.transcription.initial_prompt,
+            glossary,
+            substitute_glossary_terms: settings.transcription.substitute_glossary_terms,
+            diarize_speakers: settings.transcription.diarize_speakers,
+            hallucination_filter: settings.transcription.hallucination_filter,
+        })
+    }
+
+    /// Identifies everything the backend is asked with, so windows checkpointed with one backend,
+    /// model, language or glossary aren't resumed under another.
+    fn checkpoint_key(&self) -> Result<String, String> {
+        let terms: Vec<&str> = self
+            .glossary
+            .iter()
+            .map(|term| term.term.as_str())
+            .collect();
+        let options = serde_json::to_string(&(
+            &self.backend,
+            &self.language,
+            &self.decoding,
+            &self.initial_prompt,
+            terms,
+        ))
+        .map_err(|e| e.to_string())?;
+
+        Ok(format!("{:016x}", fnv1a(options.as_bytes())))
+    }
+}
+
+/// 64 bit FNV-1a. Unlike the standard library's hasher its output never changes, so checkpoints
+/// survive app updates.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ *byte as u64).wrapping_mul(0x100000001b3)
+    })
+}
+
+/// Length of the audio windows handed to the backend. Whisper decodes in 30 second pieces
+/// regardless, so windows only bound memory use and the work a crash can lose.
+const WINDOW_MS: i64 = 5 * 60 * 1000;
+/// Windows overlap so speech cut at one window's edge is heard whole by the next.
+const WINDOW_OVERLAP_MS: i64 = 10 * 1000;
+
+/// Random access to a 16 bit mono wav file, so long recordings are never fully in memory.
+pub struct WavSamples {
+    reader: WavReader<BufReader<File>>,
+    sample_rate: i64,
+}
+
+impl WavSamples {
+    pub fn open(wav_filepath: &PathBuf) -> Result<WavSamples, String> {
+        let reader = WavReader::open(wav_filepath)
+            .map_err(|e| format!("failed to read {}: {}", wav_filepath.display(), e))?;
+        let spec = reader.spec();
+
+        if spec.channels != 1 {
+            return Err("expected mono audio file".to_string());
+        }
+        if spec.sample_format != SampleFormat::Int {
+            return Err("expected integer sample format".to_string());
+        }
+        if spec.bits_per_sample != 16 {
+            return Err("expected 16 bits per sample".to_string());
+        }
+
+        Ok(WavSamples {
+            reader,
+            sample_rate: spec.sample_rate as i64,
+        })
+    }
+
+    pub fn duration_ms(&self) -> i64 {
+        self.reader.duration() as i64 * 1000 / self.sample_rate
+    }
+
+    /// Float samples between two offsets in milliseconds, clamped to the file.
+    pub fn read_ms(&mut self, t0: i64, t1: i64) -> Result<Vec<f32>, String> {
+        let total = self.reader.duration() as i64;
+        let start = (t0.max(0) * self.sample_rate / 1000).min(total);
+        let end = (t1.max(0) * self.sample_rate / 1000).min(total);
+
+        self.reader
+            .seek(start as u32)
+            .map_err(|e| format!("failed to seek: {}", e))?;
+        let original_samples: Vec<i16> = self
+            .reader
+            .samples::<i16>()
+            .take((end - start).max(0) as usize)
+            .collect::<Result<_, _>>()
+            .map_err(|e| format!("failed to read sample: {}", e))?;
+        let mut samples = vec![0.0f32; original_samples.len()];
+
+        whisper_rs::convert_integer_to_float_audio(&original_samples, &mut samples)
+            .map_err(|e| format!("failed to convert samples: {}", e))?;
+
+        Ok(samples)
+    }
+}
+
+/// Splits a recording into overlapping `(start, end)` windows in milliseconds.
+fn plan_windows(duration_ms: i64) -> Vec<(i64, i64)> {
+    let mut windows = Vec::new();
+    let mut start = 0;
+
+    loop {
+        let end = (start + WINDOW_MS).min(duration_ms);
+        windows.push((start, end));
+        if end >= duration_ms {
+            break;
+        }
+        start = end - WINDOW_OVERLAP_MS;
+    }
+
+    windows
+}
+
+/// A finished window, saved so an interrupted transcription resumes where it stopped.
+#[derive(Serialize, Deserialize)]
+struct WindowCheckpoint {
+    start_ms: i64,
+    end_ms: i64,
+    /// [`TranscriptionOptions::checkpoint_key`] of the run that wrote it.
+    options_key: String,
+    segments: Vec<TranscriptSegment>,
+}
+
+fn checkpoint_dir(transcription_output_file_path: &PathBuf) -> PathBuf {
+    transcription_output_file_path.with_extension("windows")
+}
+
+fn load_checkpoint(
+    checkpoint_path: &PathBuf,
+    start_ms: i64,
+    end_ms: i64,
+    options_key: &str,
+) -> Option<Vec<TranscriptSegment>> {
+    let content = read_to_string(checkpoint_path).ok()?;
+    let checkpoint: WindowCheckpoint = serde_json::from_str(&content).ok()?;
+
+    // A checkpoint for a different window layout or from other settings is stale.
+    if checkpoint.start_ms != start_ms
+        || checkpoint.end_ms != end_ms
+        || checkpoint.options_key != options_key
+    {
+        return None;
+    }
+
+    Some(checkpoint.segments)
+}
+
+/// Shifts a window's segments to recording time and keeps the ones the window owns.
+///
+/// Each pair of neighbouring windows splits their overlap down the middle, and a segment belongs
+/// to the window holding its midpoint, so nothing in the overlap is transcribed twice.
+fn stitch_window(
+    segments: Vec<TranscriptSegment>,
+    window: (i64, i64),
+    is_first: bool,
+    is_last: bool,
+) -> Vec<TranscriptSegment> {
+    let (start_ms, end_ms) = window;
+    let owned_from = if is_first {
+        i64::MIN
+    } else {
+        start_ms + WINDOW_OVERLAP_MS / 2
+    };
+    let owned_to = if is_last {
+        i64::MAX
+    } else {
+        end_ms - WINDOW_OVERLAP_MS / 2
     };
 
-    let json_string =
-        serde_json::to_string_pretty(&transcription).expect("failed to serialize transcription");
+    segments
+        .into_iter()
+        .map(|segment| TranscriptSegment {
+            t0: segment.t0 + start_ms,
+            t1: segment.t1 + start_ms,
+            words: segment
+                .words
+                .into_iter()
+                .map(|word| TranscriptWord {
+                    t0: word.t0 + start_ms,
+                    t1: word.t1 + start_ms,
+                    ..word
+                })
+                .collect(),
+            ..segment
+        })
+        .filter(|segment| {
+            let midpoint = (segment.t0 + segment.t1) / 2;
+            midpoint >= owned_from && midpoint < owned_to
+        })
+        .collect()
+}
+
+/// Transcribes the recording window by window, reusing windows checkpointed by an earlier run.
+async fn transcribe_windows(
+    transcriber: &dyn Transcriber,
+    audio: &mut WavSamples,
+    checkpoint_dir: &PathBuf,
+    options: &TranscriptionOptions,
+    on_progress: ProgressCallback,
+    cancel_flag: Arc<AtomicBool>,
+) -> Result<Vec<TranscriptSegment>, String> {
+    create_dir_all(checkpoint_dir).map_err(|e| e.to_string())?;
+
+    let options_key = options.checkpoint_key()?;
+    let windows = plan_windows(audio.duration_ms());
+    let window_count = windows.len() as i32;
+    let on_progress = Arc::new(std::sync::Mutex::new(on_progress));
+    let mut segments: Vec<TranscriptSegment> = Vec::new();
+
+    for (index, window) in windows.iter().enumerate() {
+        if cancel_flag.load(Ordering::SeqCst) {
+            return Err("transcription cancelled".to_string());
+        }
+
+        let checkpoint_path = checkpoint_dir.join(format!("{:05}.json", index));
+        if let Some(window_segments) =
+            load_checkpoint(&checkpoint_path, window.0, window.1, &options_key)
+        {
+            info!(
+                "Resuming window {} of {} from checkpoint",
+                index + 1,
+                window_count
+            );
+            segments.extend(window_segments);
+            continue;
+        }
+
+        info!(
+            "Transcribing window {} of {} {:?}",
+            index + 1,
+            window_count,
+            window
+        );
+        let request = Arc::new(TranscriptionRequest {
+            samples: Arc::new(audio.read_ms(window.0, window.1)?),
+            initial_prompt: options.initial_prompt.clone(),
+            glossary: options.glossary.clone(),
+            language: options.language.clone(),
+        });
+        let window_progress = on_progress.clone();
+        let window_index = index as i32;
+        let window_segments = transcriber
+            .transcribe(
+                request,
+                Box::new(move |progress| {
+                    (window_progress.lock().unwrap())(
+                        (window_index * 100 + progress) / window_count,
+                    )
+                }),
+                cancel_flag.clone(),
+            )
+            .await?;
+        let window_segments = stitch_window(
+            window_segments,
+            *window,
+            index == 0,
+            index == windows.len() - 1,
+        );
+
+        let checkpoint = WindowCheckpoint {
+            start_ms: window.0,
+            end_ms: window.1,
+            options_key: options_key.clone(),
+            segments: window_segments,
+        };
+        let json_string = serde_json::to_string(&checkpoint).map_err(|e| e.to_string())?;
+        write(&checkpoint_path, json_string).map_err(|e| e.to_string())?;
+        segments.extend(checkpoint.segments);
+    }
+
+    Ok(segments)
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn transcribe_wav_file_and_write(
+    handle: &tauri::AppHandle,
+    transcriber: &dyn Transcriber,
+    wav_filepath: &PathBuf,
+    transcription_output_file_path: &PathBuf,
+    options: &TranscriptionOptions,
+    redactor: &mut Redactor,
+    on_progress: ProgressCallback,
+    cancel_flag: Arc<AtomicBool>,
+) -> Result<(), String> {
+    info!("{}", wav_filepath.display());
+
+    let mut audio = WavSamples::open(wav_filepath)?;
+    let checkpoint_dir = checkpoint_dir(transcription_output_file_path);
+    let mut segments = transcribe_windows(
+        transcriber,
+        &mut audio,
+        &checkpoint_dir,
+        options,
+        on_progress,
+        cancel_flag,
+    )
+    .await?;
+
+    // Filter before diarization so music and silence don't end up as a speaker of their own.
+    let mut dropped_segments = Vec::new();
+    if options.hallucination_filter.enabled {
+        (segments, dropped_segments) =
+            filter_hallucinations(segments, &options.hallucination_filter);
+    }
+
+    if options.diarize_speakers {
+        let speaker_model_path = handle
+            .path()
+            .app_data_dir()
+            .map_err(|e| e.to_string())?
+            .join(SPEAKER_MODEL_DIR)
+            .join(SPEAKER_MODEL_FILE);
+        if speaker_model_path.exists() {
+            segments = tauri::async_runtime::spawn_blocking(move || {
+                let embedder = SpeakerEmbedder::new(&speaker_model_path)?;
+                diarize(&embedder, &mut audio, &mut segments);
+                Ok::<_, String>(segments)
+            })
+            .await
+            .map_err(|e| e.to_string())??;
+        } else {
+            info!("No speaker model found, keeping the backend's speaker turns");
+        }
+    }
+
+    let mut transcription = TranscriptionJSON {
+        full_text: full_text_from_segments(&segments),
+        segments,
+        dropped_segments,
+    };
+
+    if options.substitute_glossary_terms {
+        substitute_glossary_terms(&mut transcription, &options.glossary);
+    }
+
+    // Glossary terms first, they can restore a term the user wants masked.
+    redactor.redact_transcription(&mut transcription);
+    redactor.save().await?;
+
+    write_transcription(transcription_output_file_path, &transcription)?;
+
+    // Checkpoints only matter until the transcript is written.
+    if let Err(e) = remove_dir_all(&checkpoint_dir) {
+        warn!("Failed to remove {}: {}", checkpoint_dir.display(), e);
+    }
 
-    let mut file = File::create(transcription_output_file_path).expect("couldn't create file");
-    file.write_all(json_string.as_bytes())
-        .expect("could not write to file");
     Ok(())
 }
 
+pub fn write_transcription(
+    transcription_path: &PathBuf,
+    transcription: &TranscriptionJSON,
+) -> Result<(), String> {
+    let json_string = serde_json::to_string_pretty(transcription).map_err(|e| e.to_string())?;
+
+    let mut file = File::create(transcription_path)
+        .map_err(|e| format!("Failed to create {}: {}", transcription_path.display(), e))?;
+    file.write_all(json_string.as_bytes())
+        .map_err(|e| format!("Failed to write {}: {}", transcription_path.display(), e))
+}
+
 #[tauri::command]
 pub async fn get_real_time_transcription(
     state: tauri::State<'_, Arc<tauri::async_runtime::Mutex<RecordingState>>>,
@@ -143,6 +550,8 @@ pub async fn get_real_time_transcription(
 
     let mut merged_content = TranscriptionJSON {
         full_text: Vec::new(),
+        segments: Vec::new(),
+        dropped_segments: Vec::new(),
     };
 
     for path in paths {
@@ -156,6 +565,10 @@ pub async fn get_real_time_transcription(
                 })?;
 
             merged_content.full_text.extend(json_content.full_text);
+            merged_content.segments.extend(json_content.segments);
+            merged_content
+                .dropped_segments
+                .extend(json_content.dropped_segments);
         }
     }
 
@@ -165,6 +578,7 @@ pub async fn get_real_time_transcription(
 #[tauri::command]
 pub async fn get_complete_transcription(
     state: tauri::State<'_, Arc<tauri::async_runtime::Mutex<RecordingState>>>,
+    app_state: tauri::State<'_, AppState>,
     conversation_id: u64,
 ) -> Result<TranscriptionJSON, String> {
     let state_guard = state.lock().await;
@@ -197,6 +611,8 @@ pub async fn get_complete_transcription(
 
     let mut merged_content = TranscriptionJSON {
         full_text: Vec::new(),
+        segments: Vec::new(),
+        dropped_segments: Vec::new(),
     };
 
     for path in paths {
@@ -210,15 +626,31 @@ pub async fn get_complete_transcription(
                 })?;
 
             merged_content.full_text.extend(json_content.full_text);
+            merged_content.segments.extend(json_content.segments);
+            merged_content
+                .dropped_segments
+                .extend(json_content.dropped_segments);
         }
     }
 
+    let edits = Query::find_transcript_edits(&app_state.db, conversation_id as i32)
+        .await
+        .map_err(|e| e.to_string())?;
+    apply_edits(&mut merged_content, &edits);
+
+    // Words are paged separately through `get_transcript_words`.
+    for segment in merged_content.segments.iter_mut() {
+        segment.words = Vec::new();
+    }
+
     Ok(merged_content)
 }
 
 pub async fn load_transcription(transcription_path: PathBuf) -> Result<TranscriptionJSON, String> {
     let mut json = TranscriptionJSON {
         full_text: Vec::new(),
+        segments: Vec::new(),
+        dropped_segments: Vec::new(),
     };
 
     let content = read_to_string(&transcription_path).map_err(|err| {
@@ -238,6 +670,190 @@ pub async fn load_transcription(transcription_path: PathBuf) -> Result<Transcrip
     })?;
 
     json.full_text.extend(json_content.full_text);
+    json.segments.extend(json_content.segments);
+    json.dropped_segments.extend(json_content.dropped_segments);
 
     Ok(json)
-}
\ No newline at end of file
+}
+
+#[derive(Deserialize)]
+pub struct WordRange {
+    pub offset: usize,
+    pub limit: usize,
+}
+
+#[derive(Serialize)]
+pub struct SegmentWord {
+    /// Index of the segment the word belongs to.
+    pub segment: usize,
+    #[serde(flatten)]
+    pub word: TranscriptWord,
+}
+
+#[derive(Serialize)]
+pub struct TranscriptWordsPage {
+    pub words: Vec<SegmentWord>,
+    pub offset: usize,
+    pub total: usize,
+}
+
+/// Pages through the words of a conversation's transcript so long meetings can be loaded lazily.
+#[tauri::command]
+pub async fn get_transcript_words(
+    state: tauri::State<'_, Arc<tauri::async_runtime::Mutex<RecordingState>>>,
+    app_state: tauri::State<'_, AppState>,
+    conversation_id: u64,
+    range: WordRange,
+) -> Result<TranscriptWordsPage, String> {
+    let transcription_path = {
+        let state_guard = state.lock().await;
+        let data_dir = match &state_guard.data_dir {
+            Some(dir) => dir,
+            None => return Err("Data directory not set".to_string()),
+        };
+        data_dir
+            .join("chunks/audio")
+            .join(conversation_id.to_string())
+            .join("transcription.json")
+    };
+
+    let transcription =
+        load_edited_transcription(&app_state.db, transcription_path, conversation_id as i32)
+            .await?;
+
+    let words: Vec<SegmentWord> = transcription
+        .segments
+        .into_iter()
+        .enumerate()
+        .flat_map(|(index, segment)| {
+            segment.words.into_iter().map(move |word| SegmentWord {
+                segment: index,
+                word,
+            })
+        })
+        .collect();
+    let total = words.len();
+
+    Ok(TranscriptWordsPage {
+        words: words
+            .into_iter()
+            .skip(range.offset)
+            .take(range.limit)
+            .collect(),
+        offset: range.offset,
+        total,
+    })
+}
+
+/// Puts a segment dropped by the hallucination filter back into the transcript.
+#[tauri::command]
+pub async fn restore_dropped_segment(
+    state: tauri::State<'_, Arc<tauri::async_runtime::Mutex<RecordingState>>>,
+    app_state: tauri::State<'_, AppState>,
+    job_queue: tauri::State<'_, Arc<JobQueue>>,
+    conversation_id: u64,
+    index: usize,
+) -> Result<TranscriptionJSON, String> {
+    let transcription_path = {
+        let state_guard = state.lock().await;
+        let data_dir = match &state_guard.data_dir {
+            Some(dir) => dir,
+            None => return Err("Data directory not set".to_string()),
+        };
+        data_dir
+            .join("chunks/audio")
+            .join(conversation_id.to_string())
+            .join("transcription.json")
+    };
+
+    let mut transcription = load_transcription(transcription_path.clone()).await?;
+    if index >= transcription.dropped_segments.len() {
+        return Err(format!("No dropped segment {}", index));
+    }
+
+    let mut segment = transcription.dropped_segments.remove(index).segment;
+    let position = transcription
+        .segments
+        .partition_point(|existing| existing.t0 <= segment.t0);
+    // Dropped segments skipped diarization, so they take the speaker they interrupt.
+    if segment.speaker.is_none() && position > 0 {
+        segment.speaker = transcription.segments[position - 1].speaker.clone();
+    }
+    transcription.segments.insert(position, segment);
+    transcription.full_text = full_text_from_segments(&transcription.segments);
+
+    write_transcription(&transcription_path, &transcription)?;
+    job_queue
+        .enqueue(conversation_id as i32, &[JobStage::Index])
+        .await?;
+
+    load_edited_transcription(&app_state.db, transcription_path, conversation_id as i32).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(t0: i64, t1: i64, text: &str) -> TranscriptSegment {
+        TranscriptSegment {
+            t0,
+            t1,
+            text: text.to_string(),
+            speaker_turn_next: false,
+            speaker: None,
+            words: vec![TranscriptWord {
+                text: text.to_string(),
+                t0,
+                t1,
+                p: 1.0,
+            }],
+            no_speech_prob: None,
+        }
+    }
+
+    #[test]
+    fn hashes_checkpoint_keys_stably() {
+        assert_eq!(fnv1a(b""), 0xcbf29ce484222325);
+        assert_eq!(fnv1a(b"a"), 0xaf63dc4c8601ec8c);
+        assert_eq!(fnv1a(b"foobar"), 0x85944171f73967e8);
+    }
+
+    #[test]
+    fn plans_overlapping_windows() {
+        assert_eq!(plan_windows(0), [(0, 0)]);
+        assert_eq!(plan_windows(60_000), [(0, 60_000)]);
+        assert_eq!(plan_windows(WINDOW_MS), [(0, WINDOW_MS)]);
+        assert_eq!(
+            plan_windows(12 * 60 * 1000),
+            [(0, 300_000), (290_000, 590_000), (580_000, 720_000)]
+        );
+    }
+
+    #[test]
+    fn stitches_windows_at_the_middle_of_their_overlap() {
+        let window = (290_000, 590_000);
+        let segments = vec![
+            segment(0, 4_000, "heard whole by the previous window"),
+            segment(4_000, 8_000, "straddles the middle"),
+            segment(100_000, 102_000, "inside"),
+            segment(296_000, 300_000, "cut at the edge"),
+        ];
+
+        let stitched = stitch_window(segments, window, false, false);
+        let texts: Vec<&str> = stitched.iter().map(|s| s.text.as_str()).collect();
+        assert_eq!(texts, ["straddles the middle", "inside"]);
+        assert_eq!((stitched[1].t0, stitched[1].t1), (390_000, 392_000));
+        assert_eq!(stitched[1].words[0].t0, 390_000);
+    }
+
+    #[test]
+    fn keeps_the_outer_edges_of_the_first_and_last_windows() {
+        let segments = vec![
+            segment(0, 1_000, "first"),
+            segment(298_000, 300_000, "last"),
+        ];
+
+        let stitched = stitch_window(segments, (0, 300_000), true, true);
+        assert_eq!(stitched.len(), 2);
+    }
+}
@@ -0,0 +1,192 @@
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+use async_trait::async_trait;
+use log::info;
+use whisper_rs::{FullParams, SamplingStrategy, WhisperContext};
+
+use super::{ProgressCallback, Transcriber, TranscriptionRequest};
+use crate::glossary::build_initial_prompt;
+use crate::settings::{DecodingSettings, DecodingStrategy};
+use crate::transcribe::{TranscriptSegment, TranscriptWord};
+use crate::whisper_service::WhisperService;
+
+/// In-process whisper.cpp through whisper-rs, on the model kept by [`WhisperService`].
+pub struct LocalWhisper {
+    service: Arc<WhisperService>,
+    model: String,
+    decoding: DecodingSettings,
+}
+
+impl LocalWhisper {
+    pub fn new(service: Arc<WhisperService>, model: String, decoding: DecodingSettings) -> Self {
+        LocalWhisper {
+            service,
+            model,
+            decoding,
+        }
+    }
+}
+
+#[async_trait]
+impl Transcriber for LocalWhisper {
+    async fn transcribe(
+        &self,
+        request: Arc<TranscriptionRequest>,
+        on_progress: ProgressCallback,
+        cancel_flag: Arc<AtomicBool>,
+    ) -> Result<Vec<TranscriptSegment>, String> {
+        let service = self.service.clone();
+        let model = self.model.clone();
+        let decoding = self.decoding.clone();
+
+        tauri::async_runtime::spawn_blocking(move || {
+            let ctx = service.context(&model)?;
+            transcribe_blocking(&ctx, &decoding, &request, on_progress, cancel_flag)
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    }
+}
+
+fn sampling_strategy(decoding: &DecodingSettings) -> SamplingStrategy {
+    match decoding.strategy {
+        DecodingStrategy::Greedy { best_of } => SamplingStrategy::Greedy { best_of },
+        DecodingStrategy::BeamSearch { beam_size } => SamplingStrategy::BeamSearch {
+            beam_size,
+            // Not implemented by whisper.cpp.
+            patience: -1.0,
+        },
+    }
+}
+
+fn transcribe_blocking(
+    ctx: &WhisperContext,
+    decoding: &DecodingSettings,
+    request: &TranscriptionRequest,
+    on_progress: ProgressCallback,
+    cancel_flag: Arc<AtomicBool>,
+) -> Result<Vec<TranscriptSegment>, String> {
+    let mut state = ctx
+        .create_state()
+        .map_err(|e| format!("failed to create state: {}", e))?;
+    let mut params = FullParams::new(sampling_strategy(decoding));
+    if let Some(threads) = decoding.threads {
+        params.set_n_threads(threads);
+    }
+    params.set_temperature(decoding.temperature);
+    params.set_temperature_inc(decoding.temperature_increment);
+    if let Some(language) = &request.language {
+        params.set_language(Some(language.as_str()));
+    }
+    let initial_prompt = build_initial_prompt(
+        &request.initial_prompt,
+        &request.glossary,
+        (ctx.n_text_ctx() / 2) as usize,
+        |text| {
+            ctx.tokenize(text, text.len() + 1)
+                .map(|tokens| tokens.len())
+                .unwrap_or(usize::MAX)
+        },
+    );
+    if !initial_prompt.is_empty() {
+        info!("Initial prompt: {}", initial_prompt);
+        params.set_initial_prompt(&initial_prompt);
+    }
+    params.set_progress_callback_safe(on_progress);
+    params.set_abort_callback_safe(move || cancel_flag.load(Ordering::SeqCst));
+    params.set_tdrz_enable(true);
+    params.set_token_timestamps(true);
+
+    let st = std::time::Instant::now();
+    state
+        .full(params, &request.samples)
+        .map_err(|e| format!("failed to transcribe audio: {}", e))?;
+
+    let et = std::time::Instant::now();
+
+    let num_segments = state
+        .full_n_segments()
+        .map_err(|e| format!("failed to get number of segments: {}", e))?;
+    let mut segments: Vec<TranscriptSegment> = Vec::new();
+    for i in 0..num_segments {
+        let segment = state
+            .full_get_segment_text(i)
+            .map_err(|e| format!("failed to get segment: {}", e))?;
+        let speaker_turn_next = state.full_get_segment_speaker_turn_next(i);
+        let start_timestamp = state
+            .full_get_segment_t0(i)
+            .map_err(|e| format!("failed to get start timestamp: {}", e))?;
+        let end_timestamp = state
+            .full_get_segment_t1(i)
+            .map_err(|e| format!("failed to get end timestamp: {}", e))?;
+        info!("[{} - {}]: {}", start_timestamp, end_timestamp, segment);
+
+        let num_tokens = state
+            .full_n_tokens(i)
+            .map_err(|e| format!("failed to get number of tokens: {}", e))?;
+        let mut tokens = Vec::new();
+        for j in 0..num_tokens {
+            let data = state
+                .full_get_token_data(i, j)
+                .map_err(|e| format!("failed to get token data: {}", e))?;
+            // Timestamp, speaker turn and other special tokens sort after end-of-text.
+            if data.id >= ctx.token_eot() {
+                continue;
+            }
+            let text = state
+                .full_get_token_text_lossy(i, j)
+                .map_err(|e| format!("failed to get token text: {}", e))?;
+            tokens.push((text, data.t0, data.t1, data.p));
+        }
+
+        segments.push(TranscriptSegment {
+            t0: whisper_time_to_ms(start_timestamp),
+            t1: whisper_time_to_ms(end_timestamp),
+            text: segment,
+            speaker_turn_next,
+            speaker: None,
+            words: words_from_tokens(tokens),
+            // Not exposed by whisper-rs, so the no speech check only applies to remote backends.
+            no_speech_prob: None,
+        });
+    }
+    info!("Transcription took {}ms", (et - st).as_millis());
+
+    Ok(segments)
+}
+
+/// Whisper reports timestamps in centiseconds.
+fn whisper_time_to_ms(t: i64) -> i64 {
+    t * 10
+}
+
+/// Merges Whisper's sub-word tokens into words. A token starting with a space begins a new word.
+fn words_from_tokens(tokens: Vec<(String, i64, i64, f32)>) -> Vec<TranscriptWord> {
+    let mut words: Vec<TranscriptWord> = Vec::new();
+
+    for (text, t0, t1, p) in tokens {
+        match words.last_mut() {
+            Some(word) if !text.starts_with(' ') => {
+                word.text.push_str(&text);
+                word.t1 = whisper_time_to_ms(t1);
+                word.p = word.p.min(p);
+            }
+            _ => {
+                if text.trim().is_empty() {
+                    continue;
+                }
+                words.push(TranscriptWord {
+                    text: text.trim_start().to_string(),
+                    t0: whisper_time_to_ms(t0),
+                    t1: whisper_time_to_ms(t1),
+                    p,
+                })
+            }
+        }
+    }
+
+    words
+}
@@ -0,0 +1,264 @@
+mod local;
+mod openai;
+mod whisper_server;
+
+use std::future::Future;
+use std::io::Cursor;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use entity::glossary_term;
+use hound::{SampleFormat, WavSpec, WavWriter};
+use serde::Deserialize;
+use tauri::{AppHandle, Manager};
+
+pub use local::LocalWhisper;
+pub use openai::OpenAiTranscriber;
+pub use whisper_server::WhisperServerTranscriber;
+
+use crate::settings::{DecodingSettings, TranscriptionBackend};
+use crate::transcribe::{TranscriptSegment, TranscriptWord};
+use crate::whisper_service::{WhisperService, DEFAULT_WHISPER_MODEL};
+
+pub type ProgressCallback = Box<dyn FnMut(i32) + Send + 'static>;
+
+pub struct TranscriptionRequest {
+    /// 16kHz mono samples of the window being transcribed.
+    pub samples: Arc<Vec<f32>>,
+    pub initial_prompt: String,
+    pub glossary: Vec<glossary_term::Model>,
+    /// ISO 639-1 code or `auto`, backend default when unset.
+    pub language: Option<String>,
+}
+
+/// A speech to text engine. Implementations turn a 16kHz mono recording into timed segments.
+#[async_trait]
+pub trait Transcriber: Send + Sync {
+    async fn transcribe(
+        &self,
+        request: Arc<TranscriptionRequest>,
+        on_progress: ProgressCallback,
+        cancel_flag: Arc<AtomicBool>,
+    ) -> Result<Vec<TranscriptSegment>, String>;
+}
+
+pub fn create_transcriber(
+    handle: &AppHandle,
+    backend: &TranscriptionBackend,
+    decoding: &DecodingSettings,
+) -> Box<dyn Transcriber> {
+    match backend {
+        TranscriptionBackend::Local { model } => Box::new(LocalWhisper::new(
+            handle.state::<Arc<WhisperService>>().inner().clone(),
+            model.clone().unwrap_or(DEFAULT_WHISPER_MODEL.to_string()),
+            decoding.clone(),
+        )),
+        TranscriptionBackend::WhisperServer { url } => {
+            Box::new(WhisperServerTranscriber::new(url.clone()))
+        }
+        TranscriptionBackend::OpenAi {
+            url,
+            model,
+            api_key,
+        } => Box::new(OpenAiTranscriber::new(
+            url.clone(),
+            model.clone(),
+            api_key.clone(),
+        )),
+    }
+}
+
+/// Runs `future` until it finishes or the job is cancelled.
+async fn cancellable<T>(
+    future: impl Future<Output = Result<T, String>>,
+    cancel_flag: Arc<AtomicBool>,
+) -> Result<T, String> {
+    let cancelled = async {
+        while !cancel_flag.load(Ordering::SeqCst) {
+            tokio::time::sleep(Duration::from_millis(250)).await;
+        }
+    };
+
+    tokio::select! {
+        result = future => result,
+        _ = cancelled => Err("transcription cancelled".to_string()),
+    }
+}
+
+/// 16 bit wav upload of the request's samples for HTTP backends.
+fn encode_wav(samples: &[f32]) -> Result<Vec<u8>, String> {
+    let spec = WavSpec {
+        channels: 1,
+        sample_rate: 16000,
+        bits_per_sample: 16,
+        sample_format: SampleFormat::Int,
+    };
+
+    let mut cursor = Cursor::new(Vec::new());
+    let mut writer = WavWriter::new(&mut cursor, spec).map_err(|e| e.to_string())?;
+    for sample in samples {
+        writer
+            .write_sample((sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)
+            .map_err(|e| e.to_string())?;
+    }
+    writer.finalize().map_err(|e| e.to_string())?;
+
+    Ok(cursor.into_inner())
+}
+
+/// `verbose_json` response shared by the whisper.cpp server and OpenAI-compatible endpoints.
+#[derive(Deserialize)]
+struct VerboseTranscription {
+    #[serde(default)]
+    segments: Vec<VerboseSegment>,
+    /// OpenAI returns words at the top level when word timestamps are requested.
+    #[serde(default)]
+    words: Vec<VerboseWord>,
+}
+
+#[derive(Deserialize)]
+struct VerboseSegment {
+    start: f64,
+    end: f64,
+    text: String,
+    #[serde(default)]
+    words: Vec<VerboseWord>,
+    no_speech_prob: Option<f32>,
+}
+
+#[derive(Deserialize)]
+struct VerboseWord {
+    word: String,
+    start: f64,
+    end: f64,
+    probability: Option<f32>,
+}
+
+impl VerboseWord {
+    fn into_word(self) -> TranscriptWord {
+        TranscriptWord {
+            text: self.word.trim().to_string(),
+            t0: seconds_to_ms(self.start),
+            t1: seconds_to_ms(self.end),
+            // Backends that don't report confidence shouldn't light up as low confidence.
+            p: self.probability.unwrap_or(1.0),
+        }
+    }
+}
+
+fn seconds_to_ms(seconds: f64) -> i64 {
+    (seconds * 1000.0).round() as i64
+}
+
+impl VerboseTranscription {
+    fn into_segments(self) -> Vec<TranscriptSegment> {
+        let mut top_level_words = self.words.into_iter().peekable();
+
+        self.segments
+            .into_iter()
+            .map(|segment| {
+                let mut words: Vec<TranscriptWord> = segment
+                    .words
+                    .into_iter()
+                    .map(VerboseWord::into_word)
+                    .collect();
+                if words.is_empty() {
+                    while let Some(word) = top_level_words.next_if(|word| word.start < segment.end)
+                    {
+                        words.push(word.into_word());
+                    }
+                }
+
+                TranscriptSegment {
+                    t0: seconds_to_ms(segment.start),
+                    t1: seconds_to_ms(segment.end),
+                    text: segment.text,
+                    speaker_turn_next: false,
+                    speaker: None,
+                    words,
+                    no_speech_prob: segment.no_speech_prob,
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_segments_to_milliseconds() {
+        let response: VerboseTranscription = serde_json::from_str(
+            r#"{"segments":[
+                {"start":0.0,"end":2.4996,"text":" Hello there.","no_speech_prob":0.01,
+                 "words":[{"word":" Hello","start":0.0,"end":0.52,"probability":0.9},
+                          {"word":" there.","start":0.52,"end":2.4996}]},
+                {"start":2.5,"end":4.0,"text":" Bye."}
+            ]}"#,
+        )
+        .unwrap();
+
+        let segments = response.into_segments();
+
+        assert_eq!(segments.len(), 2);
+        assert_eq!((segments[0].t0, segments[0].t1), (0, 2500));
+        assert_eq!(segments[0].text, " Hello there.");
+        assert_eq!(segments[0].no_speech_prob, Some(0.01));
+        assert_eq!(segments[0].words.len(), 2);
+        assert_eq!(segments[0].words[0].text, "Hello");
+        assert_eq!((segments[0].words[0].t0, segments[0].words[0].t1), (0, 520));
+        assert_eq!(segments[0].words[0].p, 0.9);
+        assert_eq!(segments[0].words[1].p, 1.0);
+        assert_eq!((segments[1].t0, segments[1].t1), (2500, 4000));
+        assert_eq!(segments[1].no_speech_prob, None);
+        assert!(segments[1].words.is_empty());
+    }
+
+    #[test]
+    fn leaves_speaker_turns_to_diarization() {
+        let response: VerboseTranscription = serde_json::from_str(
+            r#"{"segments":[
+                {"start":0.0,"end":1.0,"text":" Ready?"},
+                {"start":1.0,"end":2.0,"text":" Yes."}
+            ]}"#,
+        )
+        .unwrap();
+
+        for segment in response.into_segments() {
+            assert!(!segment.speaker_turn_next);
+            assert_eq!(segment.speaker, None);
+        }
+    }
+
+    #[test]
+    fn assigns_top_level_words_to_their_segments() {
+        let response: VerboseTranscription = serde_json::from_str(
+            r#"{"segments":[
+                {"start":0.0,"end":1.0,"text":" One two."},
+                {"start":1.0,"end":2.0,"text":" Three."}
+            ],"words":[
+                {"word":"One","start":0.0,"end":0.4},
+                {"word":"two.","start":0.4,"end":1.0},
+                {"word":"Three.","start":1.0,"end":1.8}
+            ]}"#,
+        )
+        .unwrap();
+
+        let segments = response.into_segments();
+
+        let texts = |segment: &TranscriptSegment| -> Vec<String> {
+            segment.words.iter().map(|word| word.text.clone()).collect()
+        };
+        assert_eq!(texts(&segments[0]), ["One", "two."]);
+        assert_eq!(texts(&segments[1]), ["Three."]);
+        assert_eq!(
+            (segments[1].words[0].t0, segments[1].words[0].t1),
+            (1000, 1800)
+        );
+    }
+}
@@ -0,0 +1,195 @@
+use std::sync::{atomic::AtomicBool, Arc};
+
+use async_trait::async_trait;
+use reqwest::multipart::{Form, Part};
+
+use super::{
+    cancellable, encode_wav, ProgressCallback, Transcriber, TranscriptionRequest,
+    VerboseTranscription,
+};
+use crate::glossary::{build_initial_prompt, estimate_tokens, WHISPER_PROMPT_TOKENS};
+use crate::transcribe::TranscriptSegment;
+
+/// Any server implementing OpenAI's `/v1/audio/transcriptions`.
+pub struct OpenAiTranscriber {
+    url: String,
+    model: String,
+    api_key: Option<String>,
+    client: reqwest::Client,
+}
+
+impl OpenAiTranscriber {
+    pub fn new(url: String, model: String, api_key: Option<String>) -> Self {
+        OpenAiTranscriber {
+            url: url.trim_end_matches('/').to_string(),
+            model,
+            api_key,
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl Transcriber for OpenAiTranscriber {
+    async fn transcribe(
+        &self,
+        request: Arc<TranscriptionRequest>,
+        mut on_progress: ProgressCallback,
+        cancel_flag: Arc<AtomicBool>,
+    ) -> Result<Vec<TranscriptSegment>, String> {
+        let audio = encode_wav(&request.samples)?;
+        let prompt = build_initial_prompt(
+            &request.initial_prompt,
+            &request.glossary,
+            WHISPER_PROMPT_TOKENS,
+            estimate_tokens,
+        );
+
+        let mut form = Form::new()
+            .part(
+                "file",
+                Part::bytes(audio)
+                    .file_name("combined.wav")
+                    .mime_str("audio/wav")
+                    .map_err(|e| e.to_string())?,
+            )
+            .text("model", self.model.clone())
+            .text("response_format", "verbose_json")
+            .text("timestamp_granularities[]", "segment")
+            .text("timestamp_granularities[]", "word")
+            .text("prompt", prompt);
+        // OpenAI detects the language when it's left out and rejects `auto`.
+        if let Some(language) = request
+            .language
+            .as_ref()
+            .filter(|language| *language != "auto")
+        {
+            form = form.text("language", language.clone());
+        }
+
+        let mut http_request = self
+            .client
+            .post(format!("{}/v1/audio/transcriptions", self.url))
+            .multipart(form);
+        if let Some(api_key) = &self.api_key {
+            http_request = http_request.bearer_auth(api_key);
+        }
+
+        let response = cancellable(
+            async {
+                http_request
+                    .send()
+                    .await
+                    .and_then(|response| response.error_for_status())
+                    .map_err(|e| format!("transcription request failed: {}", e))?
+                    .json::<VerboseTranscription>()
+                    .await
+                    .map_err(|e| format!("unexpected transcription response: {}", e))
+            },
+            cancel_flag,
+        )
+        .await?;
+        on_progress(100);
+
+        Ok(response.into_segments())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::mock_server::serve_once;
+
+    const TRANSCRIPTION: &str = r#"{"text":"Hello there.","segments":[{"start":0.0,"end":1.5,"text":" Hello there.","no_speech_prob":0.02}],"words":[{"word":"Hello","start":0.0,"end":0.6},{"word":"there.","start":0.6,"end":1.5}]}"#;
+
+    fn request(language: Option<&str>) -> Arc<TranscriptionRequest> {
+        Arc::new(TranscriptionRequest {
+            samples: Arc::new(vec![0.0; 1600]),
+            initial_prompt: "Weekly sync.".to_string(),
+            glossary: Vec::new(),
+            language: language.map(str::to_string),
+        })
+    }
+
+    #[tokio::test]
+    async fn uploads_the_recording_as_a_form() {
+        let (port, sent) = serve_once(200, TRANSCRIPTION).await;
+        let transcriber = OpenAiTranscriber::new(
+            format!("http://127.0.0.1:{}/", port),
+            "whisper-1".to_string(),
+            Some("sk-test".to_string()),
+        );
+
+        let segments = transcriber
+            .transcribe(
+                request(Some("de")),
+                Box::new(|_| {}),
+                Arc::new(AtomicBool::new(false)),
+            )
+            .await
+            .unwrap();
+
+        let sent = sent.await.unwrap();
+        assert_eq!(sent.request_line, "POST /v1/audio/transcriptions HTTP/1.1");
+        assert_eq!(sent.header("authorization"), Some("Bearer sk-test"));
+        assert_eq!(sent.form_fields("model"), ["whisper-1"]);
+        assert_eq!(sent.form_fields("response_format"), ["verbose_json"]);
+        assert_eq!(
+            sent.form_fields("timestamp_granularities[]"),
+            ["segment", "word"]
+        );
+        assert_eq!(sent.form_fields("prompt"), ["Weekly sync."]);
+        assert_eq!(sent.form_fields("language"), ["de"]);
+        assert!(sent.form_fields("file")[0].starts_with("RIFF"));
+
+        assert_eq!(segments.len(), 1);
+        assert_eq!((segments[0].t0, segments[0].t1), (0, 1500));
+        assert_eq!(segments[0].words.len(), 2);
+        assert_eq!(segments[0].no_speech_prob, Some(0.02));
+    }
+
+    #[tokio::test]
+    async fn leaves_out_auto_language() {
+        let (port, sent) = serve_once(200, TRANSCRIPTION).await;
+        let transcriber = OpenAiTranscriber::new(
+            format!("http://127.0.0.1:{}", port),
+            "whisper-1".to_string(),
+            None,
+        );
+
+        transcriber
+            .transcribe(
+                request(Some("auto")),
+                Box::new(|_| {}),
+                Arc::new(AtomicBool::new(false)),
+            )
+            .await
+            .unwrap();
+
+        let sent = sent.await.unwrap();
+        assert!(sent.form_fields("language").is_empty());
+        assert_eq!(sent.header("authorization"), None);
+    }
+
+    #[tokio::test]
+    async fn reports_server_errors() {
+        let (port, _) = serve_once(401, r#"{"error":{"message":"Invalid API key"}}"#).await;
+        let transcriber = OpenAiTranscriber::new(
+            format!("http://127.0.0.1:{}", port),
+            "whisper-1".to_string(),
+            Some("wrong".to_string()),
+        );
+
+        let error = transcriber
+            .transcribe(
+                request(None),
+                Box::new(|_| {}),
+                Arc::new(AtomicBool::new(false)),
+            )
+            .await
+            .err()
+            .unwrap();
+
+        assert!(error.contains("401"), "{}", error);
+    }
+}
@@ -0,0 +1,171 @@
+use std::sync::{atomic::AtomicBool, Arc};
+
+use async_trait::async_trait;
+use reqwest::multipart::{Form, Part};
+
+use super::{
+    cancellable, encode_wav, ProgressCallback, Transcriber, TranscriptionRequest,
+    VerboseTranscription,
+};
+use crate::glossary::{build_initial_prompt, estimate_tokens, WHISPER_PROMPT_TOKENS};
+use crate::transcribe::TranscriptSegment;
+
+/// whisper.cpp's `server` example, e.g. running on a workstation for a laptop to use.
+pub struct WhisperServerTranscriber {
+    url: String,
+    client: reqwest::Client,
+}
+
+impl WhisperServerTranscriber {
+    pub fn new(url: String) -> Self {
+        WhisperServerTranscriber {
+            url: url.trim_end_matches('/').to_string(),
+            client: reqwest::Client::new(),
+        }
+    }
+}
+
+#[async_trait]
+impl Transcriber for WhisperServerTranscriber {
+    async fn transcribe(
+        &self,
+        request: Arc<TranscriptionRequest>,
+        mut on_progress: ProgressCallback,
+        cancel_flag: Arc<AtomicBool>,
+    ) -> Result<Vec<TranscriptSegment>, String> {
+        let audio = encode_wav(&request.samples)?;
+        let prompt = build_initial_prompt(
+            &request.initial_prompt,
+            &request.glossary,
+            WHISPER_PROMPT_TOKENS,
+            estimate_tokens,
+        );
+
+        let mut form = Form::new()
+            .part(
+                "file",
+                Part::bytes(audio)
+                    .file_name("combined.wav")
+                    .mime_str("audio/wav")
+                    .map_err(|e| e.to_string())?,
+            )
+            .text("response_format", "verbose_json")
+            .text("temperature", "0.0")
+            .text("prompt", prompt);
+        if let Some(language) = &request.language {
+            form = form.text("language", language.clone());
+        }
+
+        let response = cancellable(
+            async {
+                self.client
+                    .post(format!("{}/inference", self.url))
+                    .multipart(form)
+                    .send()
+                    .await
+                    .and_then(|response| response.error_for_status())
+                    .map_err(|e| format!("whisper server request failed: {}", e))?
+                    .json::<VerboseTranscription>()
+                    .await
+                    .map_err(|e| format!("unexpected whisper server response: {}", e))
+            },
+            cancel_flag,
+        )
+        .await?;
+        on_progress(100);
+
+        Ok(response.into_segments())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::mock_server::serve_once;
+
+    fn request(language: Option<&str>) -> Arc<TranscriptionRequest> {
+        Arc::new(TranscriptionRequest {
+            samples: Arc::new(vec![0.0; 1600]),
+            initial_prompt: "Weekly sync.".to_string(),
+            glossary: Vec::new(),
+            language: language.map(str::to_string),
+        })
+    }
+
+    #[tokio::test]
+    async fn uploads_the_recording_as_a_form() {
+        let (port, sent) = serve_once(
+            200,
+            r#"{"segments":[{"start":0.0,"end":1.25,"text":" Hello.","words":[{"word":" Hello.","start":0.0,"end":1.25,"probability":0.8}]},{"start":1.25,"end":3.0,"text":" Bye."}]}"#,
+        )
+        .await;
+        let transcriber = WhisperServerTranscriber::new(format!("http://127.0.0.1:{}/", port));
+        let progress = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let reported = progress.clone();
+
+        let segments = transcriber
+            .transcribe(
+                request(Some("auto")),
+                Box::new(move |percent| reported.lock().unwrap().push(percent)),
+                Arc::new(AtomicBool::new(false)),
+            )
+            .await
+            .unwrap();
+
+        let sent = sent.await.unwrap();
+        assert_eq!(sent.request_line, "POST /inference HTTP/1.1");
+        assert_eq!(sent.form_fields("response_format"), ["verbose_json"]);
+        assert_eq!(sent.form_fields("temperature"), ["0.0"]);
+        assert_eq!(sent.form_fields("prompt"), ["Weekly sync."]);
+        // whisper.cpp understands `auto` itself.
+        assert_eq!(sent.form_fields("language"), ["auto"]);
+        assert!(sent.form_fields("file")[0].starts_with("RIFF"));
+        assert_eq!(*progress.lock().unwrap(), [100]);
+
+        assert_eq!(segments.len(), 2);
+        assert_eq!((segments[0].t0, segments[0].t1), (0, 1250));
+        assert_eq!(segments[0].words[0].text, "Hello.");
+        assert_eq!(segments[0].words[0].p, 0.8);
+        assert_eq!((segments[1].t0, segments[1].t1), (1250, 3000));
+    }
+
+    #[tokio::test]
+    async fn leaves_out_an_unset_language() {
+        let (port, sent) = serve_once(200, r#"{"segments":[]}"#).await;
+        let transcriber = WhisperServerTranscriber::new(format!("http://127.0.0.1:{}", port));
+
+        let segments = transcriber
+            .transcribe(
+                request(None),
+                Box::new(|_| {}),
+                Arc::new(AtomicBool::new(false)),
+            )
+            .await
+            .unwrap();
+
+        assert!(segments.is_empty());
+        assert!(sent.await.unwrap().form_fields("language").is_empty());
+    }
+
+    #[tokio::test]
+    async fn rejects_a_malformed_response() {
+        let (port, _) = serve_once(200, r#"{"segments":"none"}"#).await;
+        let transcriber = WhisperServerTranscriber::new(format!("http://127.0.0.1:{}", port));
+
+        let error = transcriber
+            .transcribe(
+                request(None),
+                Box::new(|_| {}),
+                Arc::new(AtomicBool::new(false)),
+            )
+            .await
+            .err()
+            .unwrap();
+
+        assert!(
+            error.starts_with("unexpected whisper server response"),
+            "{}",
+            error
+        );
+    }
+}
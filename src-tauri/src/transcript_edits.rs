@@ -0,0 +1,221 @@
+use std::path::PathBuf;
+
+use entity::transcript_edit::{self, TranscriptEditKind};
+use log::warn;
+use service::{sea_orm::DatabaseConnection, Query};
+
+use crate::transcribe::{
+    full_text_from_segments, load_transcription, TranscriptSegment, TranscriptWord,
+    TranscriptionJSON,
+};
+
+/// Loads the machine transcript and replays the conversation's edits on top of it.
+pub async fn load_edited_transcription(
+    db: &DatabaseConnection,
+    transcription_path: PathBuf,
+    conversation_id: i32,
+) -> Result<TranscriptionJSON, String> {
+    let mut transcription = load_transcription(transcription_path).await?;
+    let edits = Query::find_transcript_edits(db, conversation_id)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    apply_edits(&mut transcription, &edits);
+
+    Ok(transcription)
+}
+
+pub fn apply_edits(transcription: &mut TranscriptionJSON, edits: &[transcript_edit::Model]) {
+    if edits.is_empty() {
+        return;
+    }
+
+    for edit in edits {
+        if let Err(e) = apply_edit(&mut transcription.segments, edit) {
+            warn!("Skipping transcript edit {}: {}", edit.id, e);
+        }
+    }
+
+    transcription.full_text = full_text_from_segments(&transcription.segments);
+}
+
+/// Finds the segment an edit was made on. Re-transcription shifts boundaries a little, so when
+/// there's no exact match the segment overlapping the original range the most is used.
+fn find_segment(segments: &[TranscriptSegment], t0: i64, t1: i64) -> Option<usize> {
+    if let Some(index) = segments
+        .iter()
+        .position(|segment| segment.t0 == t0 && segment.t1 == t1)
+    {
+        return Some(index);
+    }
+
+    segments
+        .iter()
+        .enumerate()
+        .map(|(index, segment)| (index, segment.t1.min(t1) - segment.t0.max(t0)))
+        .filter(|(_, overlap)| *overlap > 0)
+        .max_by_key(|(_, overlap)| *overlap)
+        .map(|(index, _)| index)
+}
+
+pub fn apply_edit(
+    segments: &mut Vec<TranscriptSegment>,
+    edit: &transcript_edit::Model,
+) -> Result<(), String> {
+    let index = find_segment(segments, edit.segment_t0, edit.segment_t1)
+        .ok_or("segment no longer exists")?;
+
+    match edit.kind {
+        TranscriptEditKind::EditText => {
+            let text = edit.text.as_deref().ok_or("missing text")?;
+            let segment = &mut segments[index];
+            segment.words = retime_words(&segment.words, text, segment.t0, segment.t1);
+            // Whisper segments start with a space, keep the edited ones consistent.
+            segment.text = format!(" {}", text.trim());
+        }
+        TranscriptEditKind::SetSpeaker => {
+            segments[index].speaker = edit.speaker.clone();
+        }
+        TranscriptEditKind::Split => {
+            let offset = edit.split_offset.ok_or("missing split offset")?;
+            let (first, second) = split_segment(&segments[index], offset.max(0) as usize)?;
+            segments[index] = first;
+            segments.insert(index + 1, second);
+        }
+        TranscriptEditKind::Merge => {
+            if index + 1 >= segments.len() {
+                return Err("no segment to merge with".to_string());
+            }
+            let next = segments.remove(index + 1);
+            let segment = &mut segments[index];
+            segment.text.push_str(&next.text);
+            segment.t1 = next.t1;
+            segment.speaker_turn_next = next.speaker_turn_next;
+            segment.words.extend(next.words);
+        }
+        TranscriptEditKind::Delete => {
+            segments.remove(index);
+        }
+    }
+
+    Ok(())
+}
+
+/// Splits a segment at a character offset into its text, timing the split from the words.
+fn split_segment(
+    segment: &TranscriptSegment,
+    offset: usize,
+) -> Result<(TranscriptSegment, TranscriptSegment), String> {
+    let text = segment.text.as_str();
+    let char_count = text.chars().count();
+    let offset = offset.min(char_count);
+    let index = text
+        .char_indices()
+        .nth(offset)
+        .map(|(index, _)| index)
+        .unwrap_or(text.len());
+    let (first_text, second_text) = text.split_at(index);
+    if first_text.trim().is_empty() || second_text.trim().is_empty() {
+        return Err("split would leave an empty segment".to_string());
+    }
+
+    let first_word_count = first_text.split_whitespace().count();
+    let at = match segment.words.get(first_word_count) {
+        Some(word) if segment.words.len() == text.split_whitespace().count() => word.t0,
+        _ => segment.t0 + (segment.t1 - segment.t0) * offset as i64 / char_count.max(1) as i64,
+    };
+    let (first_words, second_words): (Vec<TranscriptWord>, Vec<TranscriptWord>) =
+        segment.words.iter().cloned().partition(|word| word.t0 < at);
+
+    Ok((
+        TranscriptSegment {
+            t1: at,
+            text: first_text.trim_end().to_string(),
+            speaker_turn_next: false,
+            words: first_words,
+            ..segment.clone()
+        },
+        TranscriptSegment {
+            t0: at,
+            text: format!(" {}", second_text.trim()),
+            words: second_words,
+            ..segment.clone()
+        },
+    ))
+}
+
+/// Words for edited text. When only spellings changed the original timings are kept, otherwise
+/// the new words are spread evenly over the segment.
+fn retime_words(words: &[TranscriptWord], text: &str, t0: i64, t1: i64) -> Vec<TranscriptWord> {
+    let new_words: Vec<&str> = text.split_whitespace().collect();
+
+    if new_words.len() == words.len() {
+        return words
+            .iter()
+            .zip(new_words)
+            .map(|(word, text)| TranscriptWord {
+                text: text.to_string(),
+                p: if word.text == text { word.p } else { 1.0 },
+                ..word.clone()
+            })
+            .collect();
+    }
+
+    let step = (t1 - t0) / new_words.len().max(1) as i64;
+    new_words
+        .iter()
+        .enumerate()
+        .map(|(index, text)| TranscriptWord {
+            text: text.to_string(),
+            t0: t0 + step * index as i64,
+            t1: t0 + step * (index as i64 + 1),
+            // Typed by a person, so nothing to flag as low confidence.
+            p: 1.0,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(text: &str, t0: i64, t1: i64) -> TranscriptWord {
+        TranscriptWord {
+            text: text.to_string(),
+            t0,
+            t1,
+            p: 1.0,
+        }
+    }
+
+    #[test]
+    fn splits_at_a_character_offset_after_multibyte_text() {
+        let segment = TranscriptSegment {
+            t0: 0,
+            t1: 3000,
+            text: "Café über 東京 ok".to_string(),
+            speaker_turn_next: true,
+            speaker: Some("Speaker 1".to_string()),
+            words: vec![
+                word("Café", 0, 1000),
+                word("über", 1000, 2000),
+                word("東京", 2000, 2500),
+                word("ok", 2500, 3000),
+            ],
+            no_speech_prob: None,
+        };
+
+        let (first, second) = split_segment(&segment, 10).unwrap();
+        assert_eq!(first.text, "Café über");
+        assert_eq!(second.text, " 東京 ok");
+        assert_eq!((first.t1, second.t0), (2000, 2000));
+        assert_eq!(second.words.len(), 2);
+
+        let (first, second) = split_segment(&segment, 13).unwrap();
+        assert_eq!(
+            (first.text.as_str(), second.text.as_str()),
+            ("Café über 東京", " ok")
+        );
+        assert!(split_segment(&segment, 100).is_err());
+    }
+}
@@ -38,3 +38,31 @@ pub fn load_segment_list(segment_list_path: &Path) -> io::Result<HashSet<String>
 
     Ok(segments)
 }
+
+/// `mm:ss`, or `h:mm:ss` past the first hour, as timestamps are shown to the model and the user.
+pub fn format_timestamp(ms: i64) -> String {
+    let seconds = ms.max(0) / 1000;
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{:02}:{:02}", minutes, seconds)
+    }
+}
+
+/// Reads a timestamp written by `format_timestamp`, tolerating brackets around it.
+pub fn parse_timestamp(timestamp: &str) -> Option<i64> {
+    let parts: Vec<i64> = timestamp
+        .trim()
+        .trim_matches(|c| c == '[' || c == ']')
+        .split(':')
+        .map(|part| part.trim().parse().ok())
+        .collect::<Option<_>>()?;
+
+    let seconds = match parts[..] {
+        [minutes, seconds] => minutes * 60 + seconds,
+        [hours, minutes, seconds] => hours * 3600 + minutes * 60 + seconds,
+        _ => return None,
+    };
+    Some(seconds * 1000)
+}
@@ -0,0 +1,111 @@
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use log::{info, warn};
+use service::sea_orm::DatabaseConnection;
+use whisper_rs::{WhisperContext, WhisperContextParameters};
+
+use crate::settings::Settings;
+
+/// Directory of local Whisper models, relative to the resource dir.
+pub const WHISPER_MODELS_DIR: &str = "src/models";
+/// Model used unless the settings pick another one from the models directory.
+pub const DEFAULT_WHISPER_MODEL: &str = "ggml-small.en-tdrz.bin";
+
+/// How often the idle unloader looks at the loaded model.
+const IDLE_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+struct LoadedContext {
+    model: String,
+    ctx: Arc<WhisperContext>,
+    last_used: Instant,
+}
+
+/// Keeps a local Whisper model in memory between transcriptions.
+///
+/// The model is loaded on first use and handed out as a shared context, each caller creating its
+/// own state from it. Once nobody has used it for a while it's dropped again, so the few hundred
+/// megabytes it takes are only held around meetings. Asking for another model replaces it.
+pub struct WhisperService {
+    models_dir: PathBuf,
+    loaded: Mutex<Option<LoadedContext>>,
+}
+
+impl WhisperService {
+    pub fn new(models_dir: PathBuf) -> Self {
+        WhisperService {
+            models_dir,
+            loaded: Mutex::new(None),
+        }
+    }
+
+    /// Returns the shared context of a model in the models directory, loading it if it isn't in
+    /// memory. Blocks while loading.
+    pub fn context(&self, model: &str) -> Result<Arc<WhisperContext>, String> {
+        let mut loaded = self.loaded.lock().unwrap();
+
+        if let Some(loaded) = loaded.as_mut().filter(|loaded| loaded.model == model) {
+            loaded.last_used = Instant::now();
+            return Ok(loaded.ctx.clone());
+        }
+
+        // Model names come from settings, keep them inside the models directory.
+        let model_path = self.models_dir.join(model);
+        if model.contains(['/', '\\']) || !model_path.exists() {
+            return Err(format!("whisper model {} doesn't exist", model));
+        }
+
+        let st = Instant::now();
+        let ctx = WhisperContext::new_with_params(
+            &model_path.to_string_lossy(),
+            WhisperContextParameters::default(),
+        )
+        .map_err(|e| format!("failed to open model: {}", e))?;
+        info!(
+            "Loaded whisper model {} in {}ms",
+            model,
+            st.elapsed().as_millis()
+        );
+
+        let ctx = Arc::new(ctx);
+        *loaded = Some(LoadedContext {
+            model: model.to_string(),
+            ctx: ctx.clone(),
+            last_used: Instant::now(),
+        });
+
+        Ok(ctx)
+    }
+
+    /// Drops the model if it's been idle for `idle_timeout` and no transcription still holds it.
+    pub fn unload_if_idle(&self, idle_timeout: Duration) {
+        let mut loaded = self.loaded.lock().unwrap();
+
+        let idle = loaded.as_ref().is_some_and(|loaded| {
+            Arc::strong_count(&loaded.ctx) == 1 && loaded.last_used.elapsed() >= idle_timeout
+        });
+        if idle {
+            *loaded = None;
+            info!("Unloaded idle whisper model");
+        }
+    }
+
+    /// Background loop, spawned once at startup.
+    pub async fn run_idle_unloader(self: Arc<Self>, db: DatabaseConnection) {
+        loop {
+            tokio::time::sleep(IDLE_CHECK_INTERVAL).await;
+
+            let minutes = match Settings::load(&db).await {
+                Ok(settings) => settings.transcription.unload_model_after_minutes,
+                Err(e) => {
+                    warn!("Failed to load settings: {}", e);
+                    continue;
+                }
+            };
+            if minutes > 0 {
+                self.unload_if_idle(Duration::from_secs(minutes * 60));
+            }
+        }
+    }
+}
@@ -0,0 +1,58 @@
+import { useMutation, useQuery, useQueryClient } from "@tanstack/react-query";
+import { useToast } from "@/components/ui/use-toast";
+import { invoke } from "@tauri-apps/api/core";
+
+export interface ActionItem {
+  id: number;
+  conversation_id: number;
+  title: string;
+  assignee: string | null;
+  due_date: string | null;
+  status: "open" | "done";
+  source_t0: number | null;
+  created_at: string;
+  updated_at: string;
+}
+
+export const useActionItems = (conversationId?: number) => {
+  const actionItems = useQuery({
+    queryKey: ["action_items", conversationId],
+    queryFn: async () => {
+      return (await invoke("get_action_items", {
+        conversationId,
+      })) as ActionItem[];
+    },
+  });
+
+  return actionItems;
+};
+
+export const useSetActionItemDoneMutation = () => {
+  const queryClient = useQueryClient();
+  const { toast } = useToast();
+
+  const setActionItemDoneMutation = useMutation({
+    mutationFn: async ({
+      actionItemId,
+      done,
+    }: {
+      actionItemId: number;
+      done: boolean;
+    }) => {
+      return invoke(done ? "complete_action_item" : "reopen_action_item", {
+        actionItemId,
+      }) as Promise<ActionItem>;
+    },
+    onError(error) {
+      toast({
+        title: "Error Updating Action Item",
+        description: error.message,
+      });
+    },
+    onSuccess: () => {
+      queryClient.invalidateQueries({ queryKey: ["action_items"] });
+    },
+  });
+
+  return setActionItemDoneMutation;
+};
@@ -102,7 +102,8 @@ export const useConverstaionSummary = (conversationId: number) => {
   const useConversationQuery = useQuery({
     queryKey: ["conversations", conversationId, "summary"],
     queryFn: async (): Promise<{
-      result: string;
+      template: string;
+      sections: { key: string; title: string; content: string | string[] }[];
       action_items: { title: string }[];
     }> => {
       return invoke("get_summary_for_converstation", { conversationId });
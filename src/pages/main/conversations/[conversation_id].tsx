@@ -15,6 +15,10 @@ import Link from "next/link";
 import { useRouter } from "next/router";
 import { Skeleton } from "@/components/ui/skeleton";
 import { useCompleteTranscription } from "@/hooks/useTranscription";
+import {
+  useActionItems,
+  useSetActionItemDoneMutation,
+} from "@/hooks/useActionItems";
 import { MainLayout } from "@/components/layout/main";
 import { ReactElement, useState } from "react";
 import { NextPageWithLayout } from "@/pages/_app";
@@ -45,6 +49,8 @@ const Page: NextPageWithLayout = () => {
   );
 
   const conversationSummary = useConverstaionSummary(Number(conversation_id));
+  const actionItems = useActionItems(Number(conversation_id));
+  const setActionItemDone = useSetActionItemDoneMutation();
   const [copied, setCopied] = useState(false);
 
   const copyTranscription = () => {
@@ -76,7 +82,6 @@ const Page: NextPageWithLayout = () => {
   }
 
   const date = new Date(conversation?.data?.created_at);
-  const summary = conversationSummary.data?.result;
   return (
     <div className="p-2 h-screen flex flex-col gap-4">
       <Card>
@@ -96,25 +101,48 @@ const Page: NextPageWithLayout = () => {
                 Action Items
               </h4>
               <div className="flex flex-col gap-4 py-4">
-                {conversationSummary.data?.action_items.map((actionItem) => (
+                {actionItems.data?.map((actionItem) => (
                   <div
                     className="flex items-center space-x-2"
-                    key={actionItem.title}
+                    key={actionItem.id}
                   >
-                    <Checkbox id="terms" />
+                    <Checkbox
+                      id={`action-item-${actionItem.id}`}
+                      checked={actionItem.status === "done"}
+                      onCheckedChange={(checked) =>
+                        setActionItemDone.mutate({
+                          actionItemId: actionItem.id,
+                          done: checked === true,
+                        })
+                      }
+                    />
                     <label
-                      htmlFor="terms"
+                      htmlFor={`action-item-${actionItem.id}`}
                       className="text-sm font-medium leading-none peer-disabled:cursor-not-allowed peer-disabled:opacity-70"
                     >
                       {actionItem.title}
+                      {actionItem.assignee && ` (${actionItem.assignee})`}
+                      {actionItem.due_date && `, due ${actionItem.due_date}`}
                     </label>
                   </div>
                 ))}
               </div>
-              <h4 className="scroll-m-20 text-xl font-semibold tracking-tight">
-                Summary
-              </h4>
-              <Markdown>{summary}</Markdown>
+              {conversationSummary.data?.sections.map((section) => (
+                <div key={section.key} className="flex flex-col gap-2 pb-4">
+                  <h4 className="scroll-m-20 text-xl font-semibold tracking-tight">
+                    {section.title}
+                  </h4>
+                  {Array.isArray(section.content) ? (
+                    <ul className="list-disc pl-6">
+                      {section.content.map((item) => (
+                        <li key={item}>{item}</li>
+                      ))}
+                    </ul>
+                  ) : (
+                    <Markdown>{section.content}</Markdown>
+                  )}
+                </div>
+              ))}
             </TabsContent>
             <TabsContent value="password" className="relative">
               <Button